@@ -0,0 +1,362 @@
+use plotters_backend::{
+    text_anchor::{HPos, Pos, VPos},
+    DrawingBackend, DrawingErrorKind, FontStyle, FontTransform,
+};
+
+use crate::bands::band_rect;
+use crate::canvas::CanvasError;
+use crate::command::{CommandColor, CommandStyle, TextCommandStyle};
+use crate::coord::pixel_to_coord;
+use crate::geometry::Rect;
+use crate::layers::LayeredCanvas;
+use crate::viewport::{Axis, Viewport};
+
+/// A note anchored in data coordinates, or in pixels with [AnnotationPin::Pixel]
+#[derive(Clone, Debug)]
+pub enum Annotation {
+    /// A label with its upper left corner at `at`, moved by `offset` pixels
+    Text {
+        at: (f64, f64),
+        offset: (i32, i32),
+        text: String,
+        size: f64,
+        color: CommandColor,
+    },
+    /// An arrow pointing from `from` to `to`
+    Arrow {
+        from: (f64, f64),
+        to: (f64, f64),
+        width: u32,
+        color: CommandColor,
+    },
+    /// The range `from..to` on `axis`, shaded across the whole plot area
+    Range {
+        axis: Axis,
+        from: f64,
+        to: f64,
+        color: CommandColor,
+    },
+}
+
+/// What the coordinates of an annotation are relative to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnnotationPin {
+    /// Data coordinates, the annotation follows the feature it points at when panning or zooming
+    Data,
+    /// Pixels from the upper left corner of the plot area (a range is in pixels along its
+    /// axis), the annotation stays put on the screen and only follows the plot area on resize
+    Pixel,
+}
+
+impl Default for AnnotationPin {
+    fn default() -> Self {
+        AnnotationPin::Data
+    }
+}
+
+impl AnnotationPin {
+    fn project(&self, viewport: &Viewport, (x, y): (f64, f64)) -> (f64, f64) {
+        match self {
+            AnnotationPin::Data => viewport.to_pixel((x, y)),
+            AnnotationPin::Pixel => (viewport.area.left + x, viewport.area.top + y),
+        }
+    }
+
+    fn range(&self, viewport: &Viewport, axis: Axis, from: f64, to: f64) -> Option<Rect> {
+        let area = viewport.area;
+        match (self, axis) {
+            (AnnotationPin::Data, _) => band_rect(viewport, axis, from, to),
+            (AnnotationPin::Pixel, Axis::X) => {
+                Rect::new(area.left + from, area.top, area.left + to, area.bottom)
+                    .intersection(&area)
+            }
+            (AnnotationPin::Pixel, Axis::Y) => {
+                Rect::new(area.left, area.top + from, area.right, area.top + to).intersection(&area)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AnnotationId(u64);
+
+/// A set of annotations re-projected and re-rendered when the viewport changes or the canvas
+/// is resized
+pub struct AnnotationLayer {
+    annotations: Vec<(AnnotationId, Annotation, AnnotationPin)>,
+    next_id: u64,
+    viewport: Option<Viewport>,
+    dirty: bool,
+}
+
+impl Default for AnnotationLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnnotationLayer {
+    pub fn new() -> Self {
+        Self {
+            annotations: vec![],
+            next_id: 0,
+            viewport: None,
+            dirty: true,
+        }
+    }
+
+    /// Add an annotation anchored in data coordinates
+    pub fn add(&mut self, annotation: Annotation) -> AnnotationId {
+        self.add_pinned(annotation, AnnotationPin::Data)
+    }
+
+    pub fn add_pinned(&mut self, annotation: Annotation, pin: AnnotationPin) -> AnnotationId {
+        let id = AnnotationId(self.next_id);
+        self.next_id += 1;
+        self.annotations.push((id, annotation, pin));
+        self.dirty = true;
+        id
+    }
+
+    pub fn remove(&mut self, id: AnnotationId) {
+        self.annotations.retain(|(i, _, _)| *i != id);
+        self.dirty = true;
+    }
+
+    /// Change what the coordinates of an annotation are relative to
+    pub fn set_pin(&mut self, id: AnnotationId, pin: AnnotationPin) {
+        for (i, _, p) in self.annotations.iter_mut() {
+            if *i == id && *p != pin {
+                *p = pin;
+                self.dirty = true;
+            }
+        }
+    }
+
+    pub fn pin(&self, id: AnnotationId) -> Option<AnnotationPin> {
+        self.annotations
+            .iter()
+            .find(|(i, _, _)| *i == id)
+            .map(|(_, _, pin)| *pin)
+    }
+
+    pub fn clear(&mut self) {
+        self.annotations.clear();
+        self.dirty = true;
+    }
+
+    pub fn len(&self) -> usize {
+        self.annotations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.annotations.is_empty()
+    }
+
+    /// Set the mapping from data space to pixels, the layer is re-rendered if it changed
+    pub fn set_viewport(&mut self, viewport: Viewport) {
+        if self.viewport != Some(viewport) {
+            self.viewport = Some(viewport);
+            self.dirty = true;
+        }
+    }
+
+    /// Move the plot area after the canvas was resized, keeping the data ranges
+    ///  - Data pinned annotations are re-projected, pixel pinned ones follow the plot area corner
+    pub fn resize_area(&mut self, area: Rect) {
+        if let Some(viewport) = self.viewport {
+            self.set_viewport(Viewport { area, ..viewport });
+        }
+    }
+
+    /// The annotations or the viewport changed since the last render
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Draw the annotations on a backend, nothing is drawn before the viewport is set
+    pub fn render<DB: DrawingBackend>(
+        &mut self,
+        backend: &mut DB,
+    ) -> Result<(), DrawingErrorKind<DB::ErrorType>> {
+        let viewport = match self.viewport {
+            Some(viewport) => viewport,
+            None => return Ok(()),
+        };
+        for (_, annotation, pin) in self.annotations.iter() {
+            draw_annotation(backend, &viewport, *pin, annotation)?;
+        }
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Redraw the annotations on a layer of `layers`, only if they are dirty
+    pub fn render_layer(
+        &mut self,
+        layers: &LayeredCanvas,
+        name: &str,
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        if !self.dirty || self.viewport.is_none() {
+            return Ok(());
+        }
+        let mut backend = layers.layer_backend(name).ok_or_else(|| {
+            DrawingErrorKind::DrawingError(CanvasError::InvalidInput(format!(
+                "no layer named {}",
+                name
+            )))
+        })?;
+        layers
+            .clear_layer(name)
+            .map_err(DrawingErrorKind::DrawingError)?;
+        self.render(&mut backend)?;
+        backend.present()
+    }
+}
+
+fn draw_annotation<DB: DrawingBackend>(
+    backend: &mut DB,
+    viewport: &Viewport,
+    pin: AnnotationPin,
+    annotation: &Annotation,
+) -> Result<(), DrawingErrorKind<DB::ErrorType>> {
+    let round = |(x, y): (f64, f64)| pixel_to_coord(x, y);
+    match annotation {
+        Annotation::Text {
+            at,
+            offset,
+            text,
+            size,
+            color,
+        } => {
+            let (x, y) = round(pin.project(viewport, *at));
+            let style = TextCommandStyle {
+                family: "sans-serif".to_string(),
+                style: FontStyle::Normal,
+                size: *size,
+                color: *color,
+                anchor: Pos::new(HPos::Left, VPos::Top),
+                transform: FontTransform::None,
+            };
+            backend.draw_text(text, &style, (x + offset.0, y + offset.1))
+        }
+        Annotation::Arrow {
+            from,
+            to,
+            width,
+            color,
+        } => {
+            let style = CommandStyle {
+                color: *color,
+                stroke_width: *width,
+            };
+            let (fx, fy) = pin.project(viewport, *from);
+            let (tx, ty) = pin.project(viewport, *to);
+            backend.draw_line(round((fx, fy)), round((tx, ty)), &style)?;
+
+            let angle = (ty - fy).atan2(tx - fx);
+            let head = 6.0 + f64::from(*width) * 2.0;
+            for side in [-1.0, 1.0].iter() {
+                let a = angle + std::f64::consts::PI + side * std::f64::consts::FRAC_PI_6;
+                let end = (tx + head * a.cos(), ty + head * a.sin());
+                backend.draw_line(round((tx, ty)), round(end), &style)?;
+            }
+            Ok(())
+        }
+        Annotation::Range {
+            axis,
+            from,
+            to,
+            color,
+        } => {
+            let rect = match pin.range(viewport, *axis, *from, *to) {
+                Some(rect) => rect,
+                None => return Ok(()),
+            };
+            let style = CommandStyle {
+                color: *color,
+                stroke_width: 0,
+            };
+            backend.draw_rect(
+                pixel_to_coord(rect.left, rect.top),
+                pixel_to_coord(rect.right, rect.bottom),
+                &style,
+                true,
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::canvas::OffscreenCanvasBackend;
+    use wasm_bindgen_test::*;
+    use web_sys::OffscreenCanvas;
+
+    #[wasm_bindgen_test]
+    fn test_annotation_dirty_tracking() {
+        let canvas = OffscreenCanvas::new(100, 100).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        let color = CommandColor {
+            rgb: (255, 0, 0),
+            alpha: 1.0,
+        };
+
+        let mut layer = AnnotationLayer::new();
+        layer.add(Annotation::Range {
+            axis: Axis::X,
+            from: 2.0,
+            to: 4.0,
+            color,
+        });
+        let viewport = Viewport::new((0.0, 10.0), (0.0, 1.0), Rect::new(0.0, 0.0, 100.0, 100.0));
+        layer.set_viewport(viewport);
+        layer.render(&mut backend).unwrap();
+        assert!(!layer.is_dirty());
+
+        layer.set_viewport(viewport);
+        assert!(!layer.is_dirty());
+        layer.set_viewport(Viewport::new((0.0, 5.0), (0.0, 1.0), viewport.area));
+        assert!(layer.is_dirty());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_pinned_annotations_follow_resize() {
+        let viewport = Viewport::new((0.0, 10.0), (0.0, 1.0), Rect::new(0.0, 0.0, 100.0, 100.0));
+        let resized = Viewport {
+            area: Rect::new(20.0, 10.0, 220.0, 110.0),
+            ..viewport
+        };
+
+        assert_eq!(
+            AnnotationPin::Data.project(&resized, (5.0, 1.0)),
+            (120.0, 10.0)
+        );
+        assert_eq!(
+            AnnotationPin::Pixel.project(&resized, (5.0, 1.0)),
+            (25.0, 11.0)
+        );
+
+        let mut layer = AnnotationLayer::new();
+        let id = layer.add_pinned(
+            Annotation::Arrow {
+                from: (0.0, 0.0),
+                to: (10.0, 10.0),
+                width: 1,
+                color: CommandColor {
+                    rgb: (0, 0, 0),
+                    alpha: 1.0,
+                },
+            },
+            AnnotationPin::Pixel,
+        );
+        assert_eq!(layer.pin(id), Some(AnnotationPin::Pixel));
+        layer.set_viewport(viewport);
+        let canvas = OffscreenCanvas::new(100, 100).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        layer.render(&mut backend).unwrap();
+
+        layer.resize_area(resized.area);
+        assert!(layer.is_dirty());
+    }
+}
@@ -0,0 +1,161 @@
+use plotters_backend::DrawingErrorKind;
+use web_sys::{OffscreenCanvas, OffscreenCanvasRenderingContext2d};
+
+use crate::canvas::{context_2d, error_cast, js_error_in, CanvasError, OffscreenCanvasBackend};
+use crate::color::set_stroke_color;
+use crate::command::CommandColor;
+use crate::geometry::Rect;
+use crate::stats::PrimitiveKind;
+use crate::viewport::Viewport;
+
+/// A line series which only grows, rendered incrementally
+///
+/// The part of the line drawn so far is kept on a cache canvas, an update only strokes the
+/// segments of the new points. Changing the viewport invalidates the cache and the whole
+/// series is drawn again on the next update.
+pub struct AppendOnlySeries {
+    cache: OffscreenCanvas,
+    context: OffscreenCanvasRenderingContext2d,
+    points: Vec<(f64, f64)>,
+    rendered: usize,
+    viewport: Option<Viewport>,
+    color: CommandColor,
+    line_width: f64,
+}
+
+impl AppendOnlySeries {
+    /// Create the series with a cache of `width` x `height` pixels, the size of the target canvas
+    pub fn new(
+        width: u32,
+        height: u32,
+        color: CommandColor,
+        line_width: f64,
+    ) -> Result<Self, CanvasError> {
+        let cache =
+            OffscreenCanvas::new(width, height).map_err(js_error_in("OffscreenCanvas::new"))?;
+        let context = context_2d(&cache)?;
+        Ok(Self {
+            cache,
+            context,
+            points: vec![],
+            rendered: 0,
+            viewport: None,
+            color,
+            line_width,
+        })
+    }
+
+    /// Append a point, its x must not be lower than the one of the last point
+    pub fn push(&mut self, point: (f64, f64)) {
+        self.points.push(point);
+    }
+
+    pub fn extend<I: IntoIterator<Item = (f64, f64)>>(&mut self, points: I) {
+        self.points.extend(points);
+    }
+
+    pub fn points(&self) -> &[(f64, f64)] {
+        &self.points
+    }
+
+    /// Number of points already on the cache
+    pub fn rendered_len(&self) -> usize {
+        self.rendered
+    }
+
+    /// Set the mapping from data space to pixels, the cache is dropped if it changed
+    pub fn set_viewport(&mut self, viewport: Viewport) {
+        if self.viewport != Some(viewport) {
+            self.viewport = Some(viewport);
+            self.invalidate();
+        }
+    }
+
+    /// Drop the cache, the whole series is drawn on the next update
+    pub fn invalidate(&mut self) {
+        self.rendered = 0;
+        self.context.clear_rect(
+            0.0,
+            0.0,
+            f64::from(self.cache.width()),
+            f64::from(self.cache.height()),
+        );
+    }
+
+    /// Draw the points appended since the last update on the cache
+    pub fn update(&mut self) {
+        let viewport = match self.viewport {
+            Some(viewport) => viewport,
+            None => return,
+        };
+        // Start from the last rendered point, so that the line stays connected
+        let from = self.rendered.saturating_sub(1);
+        if self.points.len() - from < 2 {
+            self.rendered = self.points.len();
+            return;
+        }
+
+        set_stroke_color(&self.context, self.color.to_backend_color());
+        self.context.set_line_width(self.line_width);
+        self.context.begin_path();
+        let (x, y) = viewport.to_pixel(self.points[from]);
+        self.context.move_to(x, y);
+        for &p in self.points[from + 1..].iter() {
+            let (x, y) = viewport.to_pixel(p);
+            self.context.line_to(x, y);
+        }
+        self.context.stroke();
+        self.rendered = self.points.len();
+    }
+
+    pub fn cache(&self) -> &OffscreenCanvas {
+        &self.cache
+    }
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Update the series and copy its cache onto the canvas
+    pub fn draw_append_only_series(
+        &mut self,
+        series: &mut AppendOnlySeries,
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        series.update();
+        let (w, h) = (
+            f64::from(series.cache.width()),
+            f64::from(series.cache.height()),
+        );
+        if !self.begin_primitive(PrimitiveKind::Bitmap, Rect::new(0.0, 0.0, w, h)) {
+            return Ok(());
+        }
+        self.context
+            .draw_image_with_offscreen_canvas(&series.cache, 0.0, 0.0)
+            .map_err(error_cast)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_append_only_series() {
+        let color = CommandColor {
+            rgb: (0, 0, 255),
+            alpha: 1.0,
+        };
+        let mut series = AppendOnlySeries::new(100, 100, color, 1.0).unwrap();
+        let viewport = Viewport::new((0.0, 10.0), (0.0, 1.0), Rect::new(0.0, 0.0, 100.0, 100.0));
+        series.set_viewport(viewport);
+
+        series.extend(vec![(0.0, 0.0), (1.0, 0.5)]);
+        series.update();
+        assert_eq!(series.rendered_len(), 2);
+        series.push((2.0, 0.3));
+        series.update();
+        assert_eq!(series.rendered_len(), 3);
+
+        series.set_viewport(Viewport::new((0.0, 5.0), (0.0, 1.0), viewport.area));
+        assert_eq!(series.rendered_len(), 0);
+    }
+}
@@ -0,0 +1,293 @@
+use plotters::coord::Shift;
+use plotters::drawing::{DrawingArea, DrawingAreaErrorKind, IntoDrawingArea};
+use web_sys::{ImageBitmap, OffscreenCanvas};
+
+use crate::canvas::{context_2d, js_error, js_error_in, CanvasError, OffscreenCanvasBackend};
+use crate::geometry::Rect;
+use crate::inset::draw_inset;
+use crate::replay::drawing_error;
+
+/// Handle of a sparkline inside an atlas
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SparklineId(usize);
+
+/// The pixel rectangle of the atlas canvas owned by a sparkline
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AtlasRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+struct Shelf {
+    y: u32,
+    height: u32,
+    next_x: u32,
+}
+
+/// Render many small charts (e.g. sparklines embedded in a table) into regions of one large canvas
+///
+/// Regions are packed in shelves, every sparkline is drawn through a drawing area restricted to
+/// its region, and the sparklines drawn since the last call to `take_dirty` can be extracted as
+/// individual ImageBitmaps.
+pub struct SparklineAtlas {
+    canvas: OffscreenCanvas,
+    padding: u32,
+    shelves: Vec<Shelf>,
+    regions: Vec<AtlasRegion>,
+    dirty: Vec<bool>,
+}
+
+impl SparklineAtlas {
+    /// Create an atlas backed by a new canvas of the given size
+    ///  - `padding` pixels are left between regions, so that antialiased edges don't bleed
+    pub fn new(width: u32, height: u32, padding: u32) -> Result<Self, CanvasError> {
+        Ok(Self {
+            canvas: OffscreenCanvas::new(width, height)
+                .map_err(js_error_in("OffscreenCanvas::new"))?,
+            padding,
+            shelves: vec![],
+            regions: vec![],
+            dirty: vec![],
+        })
+    }
+
+    /// The atlas canvas, which can be used as a drawImage source directly
+    pub fn canvas(&self) -> &OffscreenCanvas {
+        &self.canvas
+    }
+
+    pub fn region(&self, id: SparklineId) -> AtlasRegion {
+        self.regions[id.0]
+    }
+
+    pub fn len(&self) -> usize {
+        self.regions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    /// Reserve a region for a new sparkline, None if the atlas is full
+    pub fn allocate(&mut self, width: u32, height: u32) -> Option<SparklineId> {
+        let (atlas_w, atlas_h) = (self.canvas.width(), self.canvas.height());
+        let w = width + self.padding;
+        let h = height + self.padding;
+        if w > atlas_w {
+            return None;
+        }
+
+        let shelf = match self
+            .shelves
+            .iter()
+            .position(|s| s.height >= h && s.next_x + w <= atlas_w)
+        {
+            Some(index) => index,
+            None => {
+                let y = self.shelves.last().map(|s| s.y + s.height).unwrap_or(0);
+                if y + h > atlas_h {
+                    return None;
+                }
+                self.shelves.push(Shelf {
+                    y,
+                    height: h,
+                    next_x: 0,
+                });
+                self.shelves.len() - 1
+            }
+        };
+
+        let shelf = &mut self.shelves[shelf];
+        let region = AtlasRegion {
+            x: shelf.next_x,
+            y: shelf.y,
+            width,
+            height,
+        };
+        shelf.next_x += w;
+
+        self.regions.push(region);
+        self.dirty.push(false);
+        Some(SparklineId(self.regions.len() - 1))
+    }
+
+    /// Clear the region of the sparkline and draw it through a drawing area covering that region only
+    ///  - The backend is clipped to the region meanwhile, wide strokes and texts at the edges of
+    ///    the area don't paint over the neighbouring sparklines
+    pub fn render<R, F: FnOnce(&DrawingArea<OffscreenCanvasBackend<'_>, Shift>) -> R>(
+        &mut self,
+        id: SparklineId,
+        draw: F,
+    ) -> Result<R, CanvasError> {
+        let region = self.regions[id.0];
+        let backend = OffscreenCanvasBackend::try_new(&self.canvas)?;
+        backend.context.clear_rect(
+            f64::from(region.x),
+            f64::from(region.y),
+            f64::from(region.width),
+            f64::from(region.height),
+        );
+
+        let rect = Rect::new(
+            f64::from(region.x),
+            f64::from(region.y),
+            f64::from(region.x + region.width),
+            f64::from(region.y + region.height),
+        );
+        let result = draw_inset(&backend.into_drawing_area(), rect, draw).map_err(|e| match e {
+            DrawingAreaErrorKind::BackendError(e) => drawing_error(e),
+            e => CanvasError::Other(e.to_string()),
+        })?;
+
+        self.dirty[id.0] = true;
+        Ok(result)
+    }
+
+    /// The sparklines rendered since the last call
+    pub fn take_dirty(&mut self) -> Vec<SparklineId> {
+        let mut ids = vec![];
+        for (index, dirty) in self.dirty.iter_mut().enumerate() {
+            if *dirty {
+                ids.push(SparklineId(index));
+                *dirty = false;
+            }
+        }
+        ids
+    }
+
+    /// Copy the region of a sparkline into its own ImageBitmap
+    pub fn bitmap(&self, id: SparklineId) -> Result<ImageBitmap, CanvasError> {
+        let region = self.regions[id.0];
+        let target = OffscreenCanvas::new(region.width, region.height)
+            .map_err(js_error_in("OffscreenCanvas::new"))?;
+        let context = context_2d(&target)?;
+
+        let (x, y, w, h) = (
+            f64::from(region.x),
+            f64::from(region.y),
+            f64::from(region.width),
+            f64::from(region.height),
+        );
+        context
+            .draw_image_with_offscreen_canvas_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                &self.canvas,
+                x,
+                y,
+                w,
+                h,
+                0.0,
+                0.0,
+                w,
+                h,
+            )
+            .map_err(js_error)?;
+
+        target
+            .transfer_to_image_bitmap()
+            .map_err(js_error_in("transferToImageBitmap"))
+    }
+
+    /// The sparklines rendered since the last call, each with the bitmap of its region
+    pub fn take_dirty_bitmaps(&mut self) -> Result<Vec<(SparklineId, ImageBitmap)>, CanvasError> {
+        self.take_dirty()
+            .into_iter()
+            .map(|id| self.bitmap(id).map(|bitmap| (id, bitmap)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::style_ext::{CanvasShapeStyle, Shadow};
+    use plotters::prelude::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_sparklines_stay_in_their_region() {
+        let mut atlas = SparklineAtlas::new(42, 20, 2).unwrap();
+        let first = atlas.allocate(20, 20).unwrap();
+        let second = atlas.allocate(20, 20).unwrap();
+        assert_eq!(atlas.region(second).x, 22);
+
+        // The shadow would fall on the second sparkline if it carried over to its backend
+        let shadowed = CanvasShapeStyle::new(RED.filled()).shadow(Shadow {
+            color: BLUE.to_backend_color().into(),
+            blur: 0.0,
+            offset: (-22.0, 0.0),
+        });
+        atlas
+            .render(first, |area| {
+                area.draw(&Rectangle::new([(0, 0), (20, 20)], shadowed))
+            })
+            .unwrap()
+            .unwrap();
+        atlas
+            .render(second, |area| {
+                area.draw(&Rectangle::new([(0, 0), (20, 20)], GREEN.filled()))
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(atlas.take_dirty(), vec![first, second]);
+
+        let context = context_2d(atlas.canvas()).unwrap();
+        let pixel = |x: f64| {
+            context
+                .get_image_data(x, 10.0, 1.0, 1.0)
+                .unwrap()
+                .data()
+                .to_vec()
+        };
+        assert_eq!(pixel(10.0), vec![255, 0, 0, 255]);
+        assert_eq!(pixel(21.0), vec![0, 0, 0, 0]);
+        assert_eq!(pixel(32.0), vec![0, 255, 0, 255]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_sparklines_are_clipped_to_their_region() {
+        let mut atlas = SparklineAtlas::new(42, 20, 2).unwrap();
+        let first = atlas.allocate(20, 20).unwrap();
+        atlas.allocate(20, 20).unwrap();
+
+        // The line goes on past the right edge of the first region
+        atlas
+            .render(first, |area| {
+                area.draw(&PathElement::new(
+                    vec![(0, 10), (40, 10)],
+                    BLACK.stroke_width(6),
+                ))
+            })
+            .unwrap()
+            .unwrap();
+
+        let context = context_2d(atlas.canvas()).unwrap();
+        let alpha = |x: f64| context.get_image_data(x, 10.0, 1.0, 1.0).unwrap().data()[3];
+        assert_eq!(alpha(10.0), 255);
+        assert_eq!(alpha(21.0), 0);
+        assert_eq!(alpha(30.0), 0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_sparkline_pixels_are_flushed() {
+        let mut atlas = SparklineAtlas::new(20, 20, 0).unwrap();
+        let id = atlas.allocate(10, 10).unwrap();
+        atlas
+            .render(id, |area| {
+                (0..10).try_for_each(|x| area.draw_pixel((x, 5), &RED))
+            })
+            .unwrap()
+            .unwrap();
+
+        // The batched pixels are drawn when the backend of the drawing area is dropped
+        let bitmap = atlas.bitmap(id).unwrap();
+        let canvas = OffscreenCanvas::new(10, 10).unwrap();
+        let context = context_2d(&canvas).unwrap();
+        context
+            .draw_image_with_image_bitmap(&bitmap, 0.0, 0.0)
+            .unwrap();
+        let data = context.get_image_data(0.0, 5.0, 10.0, 1.0).unwrap().data();
+        assert!(data.chunks(4).all(|p| p == [255, 0, 0, 255]));
+    }
+}
@@ -0,0 +1,114 @@
+use plotters_backend::DrawingErrorKind;
+
+use crate::canvas::{CanvasError, OffscreenCanvasBackend};
+use crate::command::CommandColor;
+use crate::geometry::Rect;
+use crate::stats::PrimitiveKind;
+use crate::viewport::{Axis, Viewport};
+
+/// A shaded range of an axis, spanning the whole plot area in the other direction
+///  - e.g. recession periods on a time axis, or alert thresholds on the value axis
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Band {
+    pub axis: Axis,
+    pub from: f64,
+    pub to: f64,
+    pub color: CommandColor,
+}
+
+/// The pixels covered by the range `from..to` of `axis`, clipped to the plot area
+///  - None if the range is outside of the viewport
+pub fn band_rect(viewport: &Viewport, axis: Axis, from: f64, to: f64) -> Option<Rect> {
+    let area = viewport.area;
+    let clip = |a: f64, b: f64, min: f64, max: f64| {
+        let (lo, hi) = (a.min(b).max(min), a.max(b).min(max));
+        if hi > lo {
+            Some((lo, hi))
+        } else {
+            None
+        }
+    };
+    match axis {
+        Axis::X => {
+            let (left, right) = clip(
+                viewport.x_to_pixel(from),
+                viewport.x_to_pixel(to),
+                area.left,
+                area.right,
+            )?;
+            Some(Rect::new(left, area.top, right, area.bottom))
+        }
+        Axis::Y => {
+            let (top, bottom) = clip(
+                viewport.y_to_pixel(from),
+                viewport.y_to_pixel(to),
+                area.top,
+                area.bottom,
+            )?;
+            Some(Rect::new(area.left, top, area.right, bottom))
+        }
+    }
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Shade the bands, meant to be called before the series so that they end up below them
+    ///  - Bands of the same color are filled with a single call
+    pub fn draw_bands(
+        &mut self,
+        viewport: &Viewport,
+        bands: &[Band],
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        let mut groups: Vec<(CommandColor, Vec<Rect>)> = vec![];
+        for band in bands {
+            if band.color.alpha == 0.0 {
+                continue;
+            }
+            let rect = match band_rect(viewport, band.axis, band.from, band.to) {
+                Some(rect) => rect,
+                None => continue,
+            };
+            match groups.iter_mut().find(|(color, _)| *color == band.color) {
+                Some((_, rects)) => rects.push(rect),
+                None => groups.push((band.color, vec![rect])),
+            }
+        }
+
+        for (color, rects) in groups {
+            let bounds = rects.iter().skip(1).fold(rects[0], |b, r| b.union(*r));
+            if !self.begin_primitive(PrimitiveKind::Rect, bounds) {
+                continue;
+            }
+            self.context.begin_path();
+            for r in rects.iter() {
+                self.context.rect(r.left, r.top, r.width(), r.height());
+            }
+            self.fill_color(color.to_backend_color());
+            self.context.fill();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_band_rect() {
+        let viewport = Viewport::new((0.0, 10.0), (0.0, 1.0), Rect::new(0.0, 0.0, 100.0, 50.0));
+        assert_eq!(
+            band_rect(&viewport, Axis::X, 2.0, 4.0),
+            Some(Rect::new(20.0, 0.0, 40.0, 50.0))
+        );
+        assert_eq!(
+            band_rect(&viewport, Axis::X, -5.0, 5.0),
+            Some(Rect::new(0.0, 0.0, 50.0, 50.0))
+        );
+        assert_eq!(
+            band_rect(&viewport, Axis::Y, 0.5, 2.0),
+            Some(Rect::new(0.0, 0.0, 100.0, 25.0))
+        );
+        assert_eq!(band_rect(&viewport, Axis::X, 12.0, 20.0), None);
+    }
+}
@@ -1,81 +1,657 @@
 
 use js_sys::JSON;
-use plotters_backend::{BackendColor, BackendStyle, DrawingBackend, DrawingErrorKind, FontTransform, text_anchor::HPos};
+use plotters_backend::{
+    text_anchor::{HPos, Pos, VPos},
+    BackendCoord, BackendStyle, BackendTextStyle, DrawingBackend, DrawingErrorKind, FontTransform,
+};
+use std::borrow::Cow;
+use std::collections::HashMap;
 use wasm_bindgen::{JsCast, JsValue};
-use web_sys::{OffscreenCanvas, OffscreenCanvasRenderingContext2d};
+use web_sys::{
+    console, CanvasPattern, OffscreenCanvas, OffscreenCanvasRenderingContext2d, Path2d,
+};
+
+use crate::color::CompositingMode;
+use crate::command::{CommandStyle, DrawCommand, TextCommandStyle};
+use crate::coord::CoordPolicy;
+use crate::debug::DebugGrid;
+use crate::degenerate::{DegeneratePolicy, ZeroAreaShape, ZeroLengthLine};
+use crate::double_buffer::FrontBuffer;
+use crate::geometry::Rect;
+use crate::marker::MarkerKind;
+use crate::middleware::Middleware;
+use crate::observer::DrawObserver;
+use crate::outline::Shape;
+use crate::pattern::PatternFill;
+use crate::pixel_batch::BatchedPixel;
+use crate::placement::PixelPlacement;
+use crate::postprocess::PostEffect;
+use crate::report::FrameReport;
+use crate::series::{LineBatch, SeriesHint};
+use crate::snap::SnapIndex;
+use crate::stats::{BudgetWarning, FrameBudget, FrameHistogram, FrameStats, PrimitiveKind};
+use crate::stream::FrameOutput;
+use crate::stroke_align::StrokeAlignment;
+use crate::style_cache::StyleCache;
+use crate::style_ext::{LineCap, LineJoin, StyleExtras};
+use crate::text_effect::TextEffect;
+use crate::text_rotation::{anchored_center, rotation_radians, TextRotation};
+use crate::time::{default_time_source, SharedTimeSource, TimeSource};
+use crate::warn::warn_once;
 
 pub struct OffscreenCanvasBackend<'a> {
-    canvas: &'a OffscreenCanvas,
-    context: OffscreenCanvasRenderingContext2d,
+    /// Borrowed, or owned by the backend for a `'static` drawing area
+    pub(crate) canvas: Cow<'a, OffscreenCanvas>,
+    pub(crate) context: OffscreenCanvasRenderingContext2d,
+    pub(crate) marker_cache: HashMap<(MarkerKind, u32), Path2d>,
+    frame: Option<FrameStats>,
+    budget: Option<FrameBudget>,
+    budget_handler: Option<Box<dyn FnMut(&BudgetWarning)>>,
+    timings: FrameHistogram,
+    pub(crate) debug_grid: Option<DebugGrid>,
+    pub(crate) debug_bounds: bool,
+    pub(crate) recorded_bounds: Vec<(PrimitiveKind, Rect)>,
+    culling: bool,
+    coord_policy: CoordPolicy,
+    pub(crate) degenerate: DegeneratePolicy,
+    pub(crate) stroke_alignment: StrokeAlignment,
+    pub(crate) text_rotation: TextRotation,
+    pub(crate) text_effect: TextEffect,
+    pub(crate) stroke_placement: PixelPlacement,
+    pub(crate) fill_placement: PixelPlacement,
+    pub(crate) miter_limit: f64,
+    pub(crate) acute_join_threshold: Option<f64>,
+    /// Cap and join of the styles which don't set their own
+    pub(crate) line_cap: LineCap,
+    pub(crate) line_join: Option<LineJoin>,
+    /// The style extras set on the context, None if the context state is unknown
+    pub(crate) applied_extras: Option<StyleExtras>,
+    pub(crate) style_cache: StyleCache,
+    pub(crate) line_dash: Option<(Vec<f64>, f64)>,
+    pub(crate) compositing: CompositingMode,
+    pub(crate) patterns: Vec<(PatternFill, CanvasPattern)>,
+    pub(crate) frame_sink: Option<FrameOutput>,
+    pub(crate) front: Option<FrontBuffer<'a>>,
+    pub(crate) invalidated: Vec<Rect>,
+    pub(crate) post_effects: Vec<PostEffect>,
+    dormant: bool,
+    pub(crate) middleware: Vec<Box<dyn Middleware>>,
+    pub(crate) observers: Vec<Box<dyn DrawObserver>>,
+    pub(crate) reset_on_frame_start: bool,
+    pub(crate) pixel_ratio: f64,
+    pub(crate) axis_scale: (f64, f64),
+    pub(crate) logical_size: Option<(u32, u32)>,
+    time_source: SharedTimeSource,
+    pub(crate) clip_stack: Vec<Rect>,
+    pub(crate) clip_saved: Vec<bool>,
+    pub(crate) last_clip: Option<Rect>,
+    pub(crate) series: Option<SeriesHint>,
+    pub(crate) line_batch: Option<LineBatch>,
+    pub(crate) pixel_batch: Vec<BatchedPixel>,
+    pub(crate) series_started: f64,
+    pub(crate) read_frequently: bool,
+    pub(crate) readbacks: u32,
+    pub(crate) geometry: Option<SnapIndex>,
+}
+
+/// Why a canvas operation failed
+///  - The categories let callers react to the failure modes, e.g. recreate the canvas when its
+///    context is unavailable, or stop reading pixels back from a tainted canvas
+#[derive(Clone, PartialEq)]
+#[non_exhaustive]
+pub enum CanvasError {
+    /// The canvas couldn't provide a 2d context, or lost it
+    ContextUnavailable(String),
+    /// A browser API call threw
+    JsError { op: &'static str, detail: String },
+    /// Pixels couldn't be read back, e.g. a security error on a tainted canvas
+    Readback(String),
+    /// A value couldn't be converted from or to its JS representation
+    Encoding(String),
+    /// A font couldn't be created, registered or used
+    Font(String),
+    /// An argument was rejected, e.g. a bitmap buffer of the wrong length
+    InvalidInput(String),
+    /// The API isn't available in the current global scope
+    Unsupported(String),
+    /// Any other failure, including the ones of user supplied renderers
+    Other(String),
 }
 
-pub struct CanvasError(String);
+impl CanvasError {
+    /// The description of the failure, without its category
+    pub fn message(&self) -> &str {
+        match self {
+            CanvasError::JsError { detail, .. } => detail,
+            CanvasError::ContextUnavailable(m)
+            | CanvasError::Readback(m)
+            | CanvasError::Encoding(m)
+            | CanvasError::Font(m)
+            | CanvasError::InvalidInput(m)
+            | CanvasError::Unsupported(m)
+            | CanvasError::Other(m) => m,
+        }
+    }
+}
 
 impl std::fmt::Display for CanvasError {
     fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        return write!(fmt, "Canvas Error: {}", self.0);
+        match self {
+            CanvasError::JsError { op, detail } => write!(fmt, "Canvas Error: {}: {}", op, detail),
+            _ => write!(fmt, "Canvas Error: {}", self.message()),
+        }
     }
 }
 
 impl std::fmt::Debug for CanvasError {
     fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        return write!(fmt, "CanvasError({})", self.0);
+        match self {
+            CanvasError::JsError { op, detail } => {
+                write!(fmt, "CanvasError::JsError({}: {})", op, detail)
+            }
+            CanvasError::ContextUnavailable(m) => {
+                write!(fmt, "CanvasError::ContextUnavailable({})", m)
+            }
+            CanvasError::Readback(m) => write!(fmt, "CanvasError::Readback({})", m),
+            CanvasError::Encoding(m) => write!(fmt, "CanvasError::Encoding({})", m),
+            CanvasError::Font(m) => write!(fmt, "CanvasError::Font({})", m),
+            CanvasError::InvalidInput(m) => write!(fmt, "CanvasError::InvalidInput({})", m),
+            CanvasError::Unsupported(m) => write!(fmt, "CanvasError::Unsupported({})", m),
+            CanvasError::Other(m) => write!(fmt, "CanvasError::Other({})", m),
+        }
     }
 }
 
 impl std::error::Error for CanvasError {}
 
 impl<'a> OffscreenCanvasBackend<'a> {
-    fn init_backend(canvas: &'a OffscreenCanvas) -> Option<Self> {
-        let context: OffscreenCanvasRenderingContext2d =
-            canvas.get_context("2d").ok()??.dyn_into().ok()?;
-        Some(OffscreenCanvasBackend { canvas, context })
+    fn init_backend(canvas: Cow<'a, OffscreenCanvas>) -> Result<Self, CanvasError> {
+        let context = canvas
+            .get_context("2d")
+            .map_err(|e| {
+                CanvasError::ContextUnavailable(format!(
+                    "getContext(\"2d\") failed: {}",
+                    js_error(e).message()
+                ))
+            })?
+            .ok_or_else(|| {
+                CanvasError::ContextUnavailable(
+                    "the canvas has no 2d context, it may already have a context of another type"
+                        .to_string(),
+                )
+            })?
+            .dyn_into::<OffscreenCanvasRenderingContext2d>()
+            .map_err(|v| {
+                CanvasError::ContextUnavailable(format!(
+                    "getContext(\"2d\") returned {}, not an OffscreenCanvasRenderingContext2d",
+                    js_error(v.into()).message()
+                ))
+            })?;
+        Ok(Self::with_canvas_context(canvas, context))
+    }
+
+    /// Create the backend from a context which was already obtained for the canvas
+    pub(crate) fn with_context(
+        canvas: &'a OffscreenCanvas,
+        context: OffscreenCanvasRenderingContext2d,
+    ) -> Self {
+        Self::with_canvas_context(Cow::Borrowed(canvas), context)
+    }
+
+    fn with_canvas_context(
+        canvas: Cow<'a, OffscreenCanvas>,
+        context: OffscreenCanvasRenderingContext2d,
+    ) -> Self {
+        OffscreenCanvasBackend {
+            canvas,
+            context,
+            marker_cache: HashMap::new(),
+            frame: None,
+            budget: None,
+            budget_handler: None,
+            timings: FrameHistogram::default(),
+            debug_grid: None,
+            debug_bounds: false,
+            recorded_bounds: vec![],
+            culling: true,
+            coord_policy: CoordPolicy::default(),
+            degenerate: DegeneratePolicy::default(),
+            stroke_alignment: StrokeAlignment::default(),
+            text_rotation: TextRotation::default(),
+            text_effect: TextEffect::default(),
+            stroke_placement: PixelPlacement::default(),
+            fill_placement: PixelPlacement::default(),
+            miter_limit: 10.0,
+            acute_join_threshold: Some(30.0),
+            line_cap: LineCap::Butt,
+            line_join: None,
+            // The context may have drawn already, e.g. a pooled or layer context
+            applied_extras: None,
+            style_cache: StyleCache::default(),
+            line_dash: None,
+            compositing: CompositingMode::default(),
+            patterns: vec![],
+            frame_sink: None,
+            front: None,
+            invalidated: vec![],
+            post_effects: vec![],
+            dormant: false,
+            middleware: vec![],
+            observers: vec![],
+            reset_on_frame_start: false,
+            pixel_ratio: 1.0,
+            axis_scale: (1.0, 1.0),
+            logical_size: None,
+            time_source: default_time_source(),
+            clip_stack: vec![],
+            clip_saved: vec![],
+            last_clip: None,
+            series: None,
+            line_batch: None,
+            pixel_batch: vec![],
+            series_started: 0.0,
+            read_frequently: false,
+            readbacks: 0,
+            geometry: None,
+        }
     }
 
     /// Create a new drawing backend backed with an ofscreen canvas object
     ///  - Return either thte drawing backend, or non in error case
     pub fn new(canvas: &'a OffscreenCanvas) -> Option<Self> {
-        Self::init_backend(canvas)
+        Self::try_new(canvas).ok()
+    }
+
+    /// Create a new drawing backend backed with an offscreen canvas object
+    ///  - Return why the 2d context couldn't be obtained in error case
+    pub fn try_new(canvas: &'a OffscreenCanvas) -> Result<Self, CanvasError> {
+        Self::init_backend(Cow::Borrowed(canvas))
+    }
+
+    /// The canvas the backend draws on, a hidden one when double buffering is enabled
+    pub fn canvas(&self) -> &OffscreenCanvas {
+        &self.canvas
+    }
+
+    /// Size of the canvas bitmap, in device pixels
+    pub(crate) fn physical_size(&self) -> (u32, u32) {
+        (self.canvas.width(), self.canvas.height())
+    }
+
+    /// Set the per-frame budget, frames going over it emit a warning on present()
+    pub fn set_frame_budget(&mut self, budget: Option<FrameBudget>) {
+        self.budget = budget;
+    }
+
+    /// Handle the budget warnings, instead of logging them to the console
+    pub fn on_budget_exceeded<F: FnMut(&BudgetWarning) + 'static>(&mut self, handler: F) {
+        self.budget_handler = Some(Box::new(handler));
+    }
+
+    /// Draw calls issued since the current frame started, None if nothing was drawn yet
+    pub fn frame_stats(&self) -> Option<&FrameStats> {
+        self.frame.as_ref()
+    }
+
+    pub(crate) fn frame_stats_mut(&mut self) -> Option<&mut FrameStats> {
+        self.frame.as_mut()
+    }
+
+    /// Skip primitives which are entirely outside of the visible area (on by default)
+    pub fn set_culling(&mut self, enabled: bool) {
+        self.culling = enabled;
+    }
+
+    /// Set how coordinates outside of the safe range are handled
+    pub fn set_coord_policy(&mut self, policy: CoordPolicy) {
+        self.coord_policy = policy;
+    }
+
+    /// Convert a coordinate to canvas space, None if the primitive must be skipped
+    pub(crate) fn coord(
+        &self,
+        p: BackendCoord,
+    ) -> Result<Option<(f64, f64)>, DrawingErrorKind<CanvasError>> {
+        self.coord_policy
+            .apply(p)
+            .map_err(DrawingErrorKind::DrawingError)
+    }
+
+    /// The area primitives have to intersect to be drawn, in the coordinates of the draw calls
+    pub(crate) fn visible_area(&self) -> Rect {
+        let (w, h) = self.get_size();
+        let canvas = Rect::new(0.0, 0.0, f64::from(w), f64::from(h));
+        match self.clip_bounds() {
+            Some(clip) => clip.intersection(&canvas).unwrap_or(clip),
+            None => canvas,
+        }
+    }
+
+    /// Set the clock the frame timings are measured with (`performance.now()` by default)
+    pub fn set_time_source(&mut self, source: SharedTimeSource) {
+        self.time_source = source;
+    }
+
+    pub fn time_source(&self) -> &SharedTimeSource {
+        &self.time_source
+    }
+
+    /// Durations of the frames presented so far
+    pub fn frame_timings(&self) -> &FrameHistogram {
+        &self.timings
+    }
+
+    pub fn reset_frame_timings(&mut self) {
+        self.timings.reset();
+    }
+
+    /// Start a frame before its first draw call, e.g. to reset the state with
+    /// set_reset_on_frame_start() before pushing the clips of the frame
+    ///  - The first draw call starts the frame otherwise, without the reset if clips are
+    ///    already pushed since the reset would pop them
+    pub fn begin_frame(&mut self) {
+        if self.frame.is_none() && !self.dormant {
+            self.start_frame(true);
+        }
+    }
+
+    fn start_frame(&mut self, reset: bool) {
+        if self.reset_on_frame_start {
+            if reset {
+                self.reset_state();
+            } else {
+                warn_once(
+                    "the state isn't reset at the start of a frame with clips pushed, call \
+                     begin_frame() before pushing them",
+                );
+            }
+        }
+        for observer in self.observers.iter_mut() {
+            observer.on_frame_start();
+        }
+        self.frame = Some(FrameStats::start(self.time_source.now_ms()));
+    }
+
+    /// Account a draw call covering `bounds`, the first one starts a new frame
+    ///  - Return false if the primitive is culled and must not be drawn
+    pub(crate) fn begin_primitive(&mut self, kind: PrimitiveKind, bounds: Rect) -> bool {
+        if self.dormant {
+            return false;
+        }
+        match kind {
+            PrimitiveKind::Line => self.flush_pixels(),
+            PrimitiveKind::Pixel => self.flush_lines(),
+            _ => self.flush_batch(),
+        }
+        // Before culling, the reset changes the clips
+        if self.frame.is_none() {
+            self.start_frame(self.clip_depth() == 0);
+        }
+        let visible_area = self.visible_area();
+        let visible = !self.culling || bounds.intersects(&visible_area);
+
+        let frame = match self.frame.as_mut() {
+            Some(frame) => frame,
+            None => return false,
+        };
+        if !visible {
+            frame.record_culled();
+            if let Some(series) = &self.series {
+                frame.series_entry(&series.name).culled += 1;
+            }
+            return false;
+        }
+        frame.record(kind);
+        // What lies outside of the canvas and the clips isn't painted
+        frame.record_dirty(bounds.intersection(&visible_area).unwrap_or(bounds));
+        if let Some(series) = &self.series {
+            frame.series_entry(&series.name).draw_calls += 1;
+        }
+
+        for observer in self.observers.iter_mut() {
+            observer.on_primitive(kind, &bounds);
+        }
+        if self.debug_bounds {
+            self.recorded_bounds.push((kind, bounds));
+        }
+        true
+    }
+
+    pub(crate) fn end_frame(&mut self) -> FrameReport {
+        let mut frame = match self.frame.take() {
+            Some(frame) => frame,
+            None => return FrameReport::skipped(),
+        };
+
+        let now = self.time_source.now_ms();
+        // A series still open at present is accounted up to now, and continues in the next frame
+        if let Some(series) = &self.series {
+            frame.series_entry(&series.name).elapsed_ms += now - self.series_started;
+            self.series_started = now;
+        }
+        let elapsed = now - frame.started_at;
+        self.timings.record(elapsed);
+        for observer in self.observers.iter_mut() {
+            observer.on_present(&frame, elapsed);
+        }
+
+        let mut warnings = vec![];
+        if let Some(budget) = &self.budget {
+            if let Some(warning) = budget.check(&frame, elapsed) {
+                match &mut self.budget_handler {
+                    Some(handler) => handler(&warning),
+                    None => console::warn_1(&warning.to_string().into()),
+                }
+                warnings.push(warning);
+            }
+        }
+
+        FrameReport {
+            duration_ms: elapsed,
+            dirty_rect: frame.dirty_rect(),
+            dirty_regions: frame.dirty_regions().to_vec(),
+            stats: frame,
+            skipped: false,
+            warnings,
+        }
+    }
+
+    /// Drop the frame being drawn without presenting it
+    pub(crate) fn discard_frame(&mut self) -> Option<FrameStats> {
+        self.frame.take()
+    }
+
+    /// Free the memory held by the backend: drop the caches and shrink the canvas to 0x0, which
+    /// releases its bitmap
+    ///  - The backend stays dormant (every draw call is a no-op) until revive() is called
+    pub fn release(&mut self) {
+        self.marker_cache.clear();
+        self.marker_cache.shrink_to_fit();
+        self.recorded_bounds = vec![];
+        self.frame = None;
+        self.line_batch = None;
+        self.pixel_batch = vec![];
+
+        self.set_canvas_size(0, 0);
+        // Resizing resets the context state
+        self.applied_extras = Some(StyleExtras::default());
+        self.style_cache.forget();
+        self.forget_clips();
+        self.dormant = true;
+    }
+
+    /// Give the canvas a size again after release(), the content has to be redrawn
+    ///  - The size is in physical pixels, use resize_to_css() afterwards to get a scaled canvas
+    pub fn revive(&mut self, width: u32, height: u32) {
+        self.set_canvas_size(width, height);
+        self.applied_extras = Some(StyleExtras::default());
+        self.style_cache.forget();
+        self.forget_clips();
+        self.pixel_ratio = 1.0;
+        self.axis_scale = (1.0, 1.0);
+        self.logical_size = None;
+        self.dormant = false;
+    }
+
+    pub fn is_dormant(&self) -> bool {
+        self.dormant
+    }
+
+    /// Set how zero-length lines, zero-area shapes and zero stroke widths are drawn
+    pub fn set_degenerate_policy(&mut self, policy: DegeneratePolicy) {
+        self.degenerate = policy;
+    }
+
+    /// The line width to stroke the style with, None if the stroke must be skipped
+    pub(crate) fn line_width(&self, style: &impl BackendStyle) -> Option<f64> {
+        self.degenerate.line_width(style.stroke_width())
     }
 
-    // pub fn with_offscreen_canvas_object(canvas: OffscreenCanvas) -> Option<Self> {
-    //     Self::init_backend(canvas)
-    // }
+    /// Set the fill color, independently of the stroke color and width set by [Self::set_line_style]
+    pub(crate) fn set_fill_style(&mut self, style: &impl BackendStyle) {
+        self.fill_color(style.color());
+        self.apply_style_extras(style, false);
+        self.apply_fill_gradient();
+        self.apply_fill_pattern();
+    }
+
+    pub(crate) fn set_line_style(&mut self, style: &impl BackendStyle) {
+        self.stroke_color(style.color());
+        self.style_cache
+            .set_line_width(&self.context, self.line_width(style).unwrap_or(1.0));
+        self.apply_style_extras(style, false);
+    }
 
-    fn set_line_style(&mut self, style: &impl BackendStyle) {
-        self.context
-            .set_stroke_style(&make_canvas_color(style.color()));
-        self.context.set_line_width(style.stroke_width() as f64);
+    /// Set the stroke style of a line or a path, which also follows the line dash mode
+    pub(crate) fn set_polyline_style(&mut self, style: &impl BackendStyle) {
+        self.stroke_color(style.color());
+        self.style_cache
+            .set_line_width(&self.context, self.line_width(style).unwrap_or(1.0));
+        self.apply_style_extras(style, true);
     }
 }
 
-fn make_canvas_color(color: BackendColor) -> JsValue {
-    let (r, g, b) = color.rgb;
-    let a = color.alpha;
-    format!("rgba({},{},{},{}", r, g, b, a).into()
+/// The CSS font of a text style, as set on the context
+pub(crate) fn font_string<TStyle: BackendTextStyle>(style: &TStyle) -> String {
+    format!(
+        "{} {}px {}",
+        style.style().as_str(),
+        style.size(),
+        style.family().as_str(),
+    )
+}
+
+/// Approximate extent of a text, from the font size and the number of characters
+pub(crate) fn text_bounds(
+    pos: (f64, f64),
+    text: &str,
+    size: f64,
+    anchor: Pos,
+    transform: FontTransform,
+) -> Rect {
+    let w = text.chars().count() as f64 * size * 0.6;
+    let left = match anchor.h_pos {
+        HPos::Left => 0.0,
+        HPos::Right => -w,
+        HPos::Center => -w / 2.0,
+    };
+    let (top, bottom) = match anchor.v_pos {
+        VPos::Top => (0.0, size),
+        VPos::Center => (-size / 2.0, size / 2.0),
+        VPos::Bottom => (-size, 0.0),
+    };
+    let (x0, y0, x1, y1) = (left, top, left + w, bottom);
+    let (x0, y0, x1, y1) = match transform {
+        FontTransform::None => (x0, y0, x1, y1),
+        FontTransform::Rotate90 => (-y1, x0, -y0, x1),
+        FontTransform::Rotate180 => (-x1, -y1, -x0, -y0),
+        FontTransform::Rotate270 => (y0, -x1, y1, -x0),
+    };
+    let (x, y) = pos;
+    Rect::new(x + x0, y + y0, x + x1, y + y1)
+}
+
+pub(crate) fn js_error(e: JsValue) -> CanvasError {
+    js_error_in("canvas")(e)
+}
+
+/// Map the exception thrown by the browser API `op` to a CanvasError
+pub(crate) fn js_error_in(op: &'static str) -> impl Fn(JsValue) -> CanvasError {
+    move |e| {
+        // Exceptions have no enumerable properties, JSON would only give "{}"
+        if let Some(error) = e.dyn_ref::<js_sys::Error>() {
+            let (name, message) = (String::from(error.name()), String::from(error.message()));
+            return CanvasError::JsError {
+                op,
+                detail: format!("{}: {}", name, message),
+            };
+        }
+        match JSON::stringify(&e) {
+            Ok(s) => CanvasError::JsError {
+                op,
+                detail: s.into(),
+            },
+            Err(_) => CanvasError::Encoding(format!(
+                "{} threw a value which can't be stringified",
+                op
+            )),
+        }
+    }
+}
+
+impl<'a> Drop for OffscreenCanvasBackend<'a> {
+    /// Draw the batched lines and pixels, so that a backend dropped without present() (e.g. the
+    /// one of a drawing area) keeps everything it was asked to draw
+    fn drop(&mut self) {
+        self.flush_batch();
+    }
+}
+
+/// Get the 2d context of a canvas the crate creates internally (scratch canvases, copies, ...)
+pub(crate) fn context_2d(
+    canvas: &OffscreenCanvas,
+) -> Result<OffscreenCanvasRenderingContext2d, CanvasError> {
+    canvas
+        .get_context("2d")
+        .map_err(|e| CanvasError::ContextUnavailable(js_error(e).message().to_string()))?
+        .ok_or_else(|| {
+            CanvasError::ContextUnavailable("2d context is not available".to_string())
+        })?
+        .dyn_into()
+        .map_err(|_| CanvasError::ContextUnavailable("not a 2d rendering context".to_string()))
+}
+
+pub(crate) fn error_cast(e: JsValue) -> DrawingErrorKind<CanvasError> {
+    DrawingErrorKind::DrawingError(js_error(e))
 }
 
-fn error_cast(e: JsValue) -> DrawingErrorKind<CanvasError> {
-    DrawingErrorKind::DrawingError(CanvasError(
-        JSON::stringify(&e)
-            .map(|s| Into::<String>::into(&s))
-            .unwrap_or_else(|_| "unknown".to_string())
-    ))
+impl OffscreenCanvasBackend<'static> {
+    /// Create a drawing backend owning its canvas, e.g. the one received by a worker from
+    /// postMessage, so that the backend and its drawing area are `'static`
+    pub fn with_canvas(canvas: OffscreenCanvas) -> Option<Self> {
+        Self::try_with_canvas(canvas).ok()
+    }
+
+    /// Same as with_canvas, returning why the 2d context couldn't be obtained in error case
+    pub fn try_with_canvas(canvas: OffscreenCanvas) -> Result<Self, CanvasError> {
+        Self::init_backend(Cow::Owned(canvas))
+    }
 }
 
 impl<'a> DrawingBackend for OffscreenCanvasBackend<'a> {
     type ErrorType = CanvasError;
 
     fn ensure_prepared(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.flush_pixels();
         Ok(())
     }
 
     fn present(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
-        Ok(())
+        self.present_with_report().map(|_| ())
     }
 
     fn get_size(&self) -> (u32, u32) {
-        (self.canvas.width(), self.canvas.height())
+        self.logical_size.unwrap_or_else(|| self.physical_size())
     }
 
     fn draw_pixel(
@@ -83,64 +659,443 @@ impl<'a> DrawingBackend for OffscreenCanvasBackend<'a> {
         point: plotters_backend::BackendCoord,
         style: plotters_backend::BackendColor,
     ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        if self.middleware.is_empty() {
+            return self.execute_pixel(point, style);
+        }
+        self.submit(DrawCommand::Pixel {
+            point,
+            color: style.into(),
+        })
+    }
+
+    fn draw_line<S: BackendStyle>(
+        &mut self,
+        from: plotters_backend::BackendCoord,
+        to: plotters_backend::BackendCoord,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        if self.middleware.is_empty() {
+            return self.execute_line(from, to, style);
+        }
+        self.submit(DrawCommand::Line {
+            from,
+            to,
+            style: CommandStyle::from_style(style),
+        })
+    }
+
+    fn draw_rect<S: BackendStyle>(
+        &mut self,
+        upper_left: BackendCoord,
+        bottom_right: BackendCoord,
+        style: &S,
+        fill: bool,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        if self.middleware.is_empty() {
+            return self.execute_rect(upper_left, bottom_right, style, fill);
+        }
+        self.submit(DrawCommand::Rect {
+            upper_left,
+            bottom_right,
+            style: CommandStyle::from_style(style),
+            fill,
+        })
+    }
+
+    fn draw_circle<S: BackendStyle>(
+        &mut self,
+        center: BackendCoord,
+        radius: u32,
+        style: &S,
+        fill: bool,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        if self.middleware.is_empty() {
+            return self.execute_circle(center, radius, style, fill);
+        }
+        self.submit(DrawCommand::Circle {
+            center,
+            radius,
+            style: CommandStyle::from_style(style),
+            fill,
+        })
+    }
+
+    fn draw_path<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        path: I,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let points: Vec<_> = path.into_iter().collect();
+        if self.middleware.is_empty() {
+            return self.execute_path(&points, style);
+        }
+        self.submit(DrawCommand::Path {
+            points,
+            style: CommandStyle::from_style(style),
+        })
+    }
+
+    fn fill_polygon<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        vert: I,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let points: Vec<_> = vert.into_iter().collect();
+        if self.middleware.is_empty() {
+            return self.execute_polygon(&points, style);
+        }
+        self.submit(DrawCommand::Polygon {
+            points,
+            style: CommandStyle::from_style(style),
+        })
+    }
+
+    fn estimate_text_size<TStyle: BackendTextStyle>(
+        &self,
+        text: &str,
+        style: &TStyle,
+    ) -> Result<(u32, u32), DrawingErrorKind<Self::ErrorType>> {
+        self.style_cache
+            .set_font(&self.context, &font_string(style));
+        let metrics = self.context.measure_text(text).map_err(error_cast)?;
+        // The bounding box metrics are missing from older browsers, fall back to the font size
+        let has_bounding_box = js_sys::Reflect::has(&metrics, &"actualBoundingBoxAscent".into())
+            .unwrap_or(false);
+        let height = if has_bounding_box {
+            metrics.actual_bounding_box_ascent() + metrics.actual_bounding_box_descent()
+        } else {
+            warn_once("TextMetrics has no bounding box, text heights are estimated");
+            style.size()
+        };
+        Ok((metrics.width().ceil() as u32, height.ceil() as u32))
+    }
+
+    fn blit_bitmap(
+        &mut self,
+        pos: BackendCoord,
+        (iw, ih): (u32, u32),
+        src: &[u8],
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        if self.middleware.is_empty() {
+            return self.execute_bitmap(pos, (iw, ih), src);
+        }
+        self.submit(DrawCommand::Bitmap {
+            pos,
+            size: (iw, ih),
+            data: src.to_vec(),
+        })
+    }
+
+    fn draw_text<TStyle: BackendTextStyle>(
+        &mut self,
+        text: &str,
+        style: &TStyle,
+        pos: plotters_backend::BackendCoord,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        if self.middleware.is_empty() {
+            return self.execute_text(text, style, pos);
+        }
+        self.submit(DrawCommand::Text {
+            text: text.to_string(),
+            pos,
+            style: TextCommandStyle::from_style(style),
+        })
+    }
+}
+
+/// The primitives, once they went through the middleware chain
+impl<'a> OffscreenCanvasBackend<'a> {
+    pub(crate) fn execute_pixel(
+        &mut self,
+        point: plotters_backend::BackendCoord,
+        style: plotters_backend::BackendColor,
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
         if style.color().alpha == 0.0 {
             return Ok(());
         }
 
-        self.context
-            .set_fill_style(&make_canvas_color(style.color()));
-        self.context
-            .fill_rect(f64::from(point.0), f64::from(point.1), 1.0, 1.0);
+        let (x, y) = match self.fill_coord(point)? {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+        if !self.begin_primitive(PrimitiveKind::Pixel, Rect::new(x, y, x + 1.0, y + 1.0)) {
+            return Ok(());
+        }
+        self.batch_pixel(x, y, style.color());
+        self.record_geometry(&[(x, y)]);
 
         Ok(())
     }
 
-    fn draw_line<S: BackendStyle>(
+    pub(crate) fn execute_line<S: BackendStyle>(
         &mut self,
         from: plotters_backend::BackendCoord,
         to: plotters_backend::BackendCoord,
         style: &S,
-    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        if style.color().alpha == 0.0 {
+            return Ok(());
+        }
+
+        let width = match self.line_width(style) {
+            Some(width) => width,
+            None => return Ok(()),
+        };
+        let (from, to) = match (self.stroke_coord(from)?, self.stroke_coord(to)?) {
+            (Some(from), Some(to)) => (from, to),
+            _ => return Ok(()),
+        };
+        if from == to && self.degenerate.zero_length_line == ZeroLengthLine::Skip {
+            return Ok(());
+        }
+        if !self.begin_primitive(
+            PrimitiveKind::Line,
+            Rect::new(from.0, from.1, to.0, to.1).inflate(width / 2.0),
+        ) {
+            return Ok(());
+        }
+
+        self.record_geometry(&[from, to]);
+        if from == to {
+            self.flush_batch();
+            self.fill_color(style.color());
+            self.context
+                .fill_rect(from.0 - width / 2.0, from.1 - width / 2.0, width, width);
+            return Ok(());
+        }
+
+        if self.batch_line(from, to, style) {
+            return Ok(());
+        }
+        self.set_polyline_style(style);
+        self.context.begin_path();
+        self.context.move_to(from.0, from.1);
+        self.context.line_to(to.0, to.1);
+        self.stroke_current_path();
+        Ok(())
+    }
+
+    /// Stroke a polyline as a single path
+    ///  - Points rejected by the coordinate policy break the line instead of dropping all of it
+    pub(crate) fn execute_path<S: BackendStyle>(
+        &mut self,
+        points: &[BackendCoord],
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
         if style.color().alpha == 0.0 {
             return Ok(());
         }
+        if points.len() < 2 {
+            return match points.first() {
+                Some(&p) => self.execute_line(p, p, style),
+                None => Ok(()),
+            };
+        }
 
-        self.set_line_style(style);
+        let width = match self.line_width(style) {
+            Some(width) => width,
+            None => return Ok(()),
+        };
+        let mut mapped = Vec::with_capacity(points.len());
+        for &p in points {
+            mapped.push(self.stroke_coord(p)?);
+        }
+        let drawn: Vec<_> = mapped.iter().flatten().copied().collect();
+        let bounds = match Rect::bounding(&drawn) {
+            Some(bounds) => bounds,
+            None => return Ok(()),
+        };
+        if !self.begin_primitive(PrimitiveKind::Path, bounds.inflate(width / 2.0)) {
+            return Ok(());
+        }
+
+        self.record_geometry(&drawn);
+        self.set_polyline_style(style);
+        self.apply_line_join(&drawn, false);
         self.context.begin_path();
-        self.context.move_to(f64::from(from.0), f64::from(from.1));
-        self.context.line_to(f64::from(to.0), f64::from(to.1));
-        self.context.stroke();
+        let mut pen_down = false;
+        for p in mapped {
+            match p {
+                Some((x, y)) if pen_down => self.context.line_to(x, y),
+                Some((x, y)) => {
+                    self.context.move_to(x, y);
+                    pen_down = true;
+                }
+                None => pen_down = false,
+            }
+        }
+        self.stroke_current_path();
         Ok(())
     }
 
-    fn draw_text<TStyle: plotters_backend::BackendTextStyle>(
+    /// Fill a closed polygon with a single path
+    pub(crate) fn execute_polygon<S: BackendStyle>(
+        &mut self,
+        points: &[BackendCoord],
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        if style.color().alpha == 0.0 {
+            return Ok(());
+        }
+        if points.len() < 3 {
+            return match self.degenerate.zero_area_shape {
+                ZeroAreaShape::Outline => self.execute_path(points, style),
+                ZeroAreaShape::Skip => Ok(()),
+            };
+        }
+
+        let mut mapped = Vec::with_capacity(points.len());
+        for &p in points {
+            match self.fill_coord(p)? {
+                Some(p) => mapped.push(p),
+                None => return Ok(()),
+            }
+        }
+        self.record_geometry(&mapped);
+        self.draw_shape(
+            PrimitiveKind::Polygon,
+            Shape::Polygon(mapped),
+            Some(CommandStyle::from_style(style)),
+            None,
+        )
+    }
+
+    pub(crate) fn execute_rect<S: BackendStyle>(
+        &mut self,
+        upper_left: BackendCoord,
+        bottom_right: BackendCoord,
+        style: &S,
+        fill: bool,
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        if style.color().alpha == 0.0 {
+            return Ok(());
+        }
+
+        if upper_left.0 == bottom_right.0 || upper_left.1 == bottom_right.1 {
+            return match self.degenerate.zero_area_shape {
+                ZeroAreaShape::Outline => self.execute_line(upper_left, bottom_right, style),
+                ZeroAreaShape::Skip => Ok(()),
+            };
+        }
+
+        let corners = if fill {
+            (self.fill_coord(upper_left)?, self.fill_coord(bottom_right)?)
+        } else {
+            (self.stroke_coord(upper_left)?, self.stroke_coord(bottom_right)?)
+        };
+        let (a, b) = match corners {
+            (Some(a), Some(b)) => (a, b),
+            _ => return Ok(()),
+        };
+        let style = CommandStyle::from_style(style);
+        let (fill, stroke) = if fill {
+            (Some(style), None)
+        } else {
+            (None, Some(style))
+        };
+        self.draw_shape(
+            PrimitiveKind::Rect,
+            Shape::Rect(Rect::new(a.0, a.1, b.0, b.1)),
+            fill,
+            stroke,
+        )
+    }
+
+    pub(crate) fn execute_circle<S: BackendStyle>(
+        &mut self,
+        center: BackendCoord,
+        radius: u32,
+        style: &S,
+        fill: bool,
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        if style.color().alpha == 0.0 {
+            return Ok(());
+        }
+        if radius == 0 {
+            return match self.degenerate.zero_area_shape {
+                ZeroAreaShape::Outline => self.execute_line(center, center, style),
+                ZeroAreaShape::Skip => Ok(()),
+            };
+        }
+
+        let c = if fill {
+            self.fill_coord(center)?
+        } else {
+            self.stroke_coord(center)?
+        };
+        let c = match c {
+            Some(c) => c,
+            None => return Ok(()),
+        };
+        let style = CommandStyle::from_style(style);
+        let (fill, stroke) = if fill {
+            (Some(style), None)
+        } else {
+            (None, Some(style))
+        };
+        self.draw_shape(
+            PrimitiveKind::Circle,
+            Shape::Circle(c, f64::from(radius)),
+            fill,
+            stroke,
+        )
+    }
+
+    pub(crate) fn execute_text<TStyle: BackendTextStyle>(
         &mut self,
         text: &str,
         style: &TStyle,
         pos: plotters_backend::BackendCoord,
-    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
         let color = style.color();
         if color.alpha == 0.0 {
             return Ok(());
         }
 
-        let (mut x, mut y) = (pos.0, pos.1);
+        let (x, y) = match self.coord(pos)? {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+
+        let angle = rotation_radians(style.transform());
+        if self.text_rotation == TextRotation::AnchorAfterRotation {
+            return self.execute_text_anchored_after_rotation(text, style, (x, y), angle);
+        }
 
-        let degree = match style.transform() {
-            FontTransform::None => 0.0,
-            FontTransform::Rotate90 => 90.0,
-            FontTransform::Rotate180 => 180.0,
-            FontTransform::Rotate270 => 270.0,
-        } / 100.0 * std::f64::consts::PI;
+        // The extent is only estimated here, keep a margin so that wide glyphs are never culled
+        let bounds = text_bounds((x, y), text, style.size(), style.anchor(), style.transform());
+        if !self.begin_primitive(PrimitiveKind::Text, bounds.inflate(style.size())) {
+            return Ok(());
+        }
 
-        if degree != 0.0 {
+        // Set before the save, so that the font and the color outlive the rotation
+        self.set_text_font(&font_string(style));
+        self.fill_color(color);
+        let rotated = angle != 0.0;
+        if rotated {
             self.context.save();
-            self.context
-                .translate(f64::from(x), f64::from(y))
-                .map_err(error_cast)?;
-            self.context.rotate(degree).map_err(error_cast)?;
-            x = 0;
-            y = 0;
+        }
+        // Restored on errors too, the rotation would apply to everything drawn after
+        let result = self.fill_anchored_text(text, style, (x, y), angle);
+        if rotated {
+            self.context.restore();
+        }
+        result
+    }
+
+    /// Draw a text at its anchor and rotated around it, see execute_text()
+    fn fill_anchored_text<TStyle: BackendTextStyle>(
+        &self,
+        text: &str,
+        style: &TStyle,
+        (mut x, mut y): (f64, f64),
+        angle: f64,
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        if angle != 0.0 {
+            self.context.translate(x, y).map_err(error_cast)?;
+            self.context.rotate(angle).map_err(error_cast)?;
+            x = 0.0;
+            y = 0.0;
         }
 
         let text_align = match style.anchor().h_pos {
@@ -150,23 +1105,54 @@ impl<'a> DrawingBackend for OffscreenCanvasBackend<'a> {
         };
         self.context.set_text_align(text_align);
 
-        self.context
-            .set_fill_style(&make_canvas_color(color.clone()));
-        self.context.set_font(&format!(
-            "{} {}px {}",
-            style.style().as_str(),
-            style.size(),
-            style.family().as_str(),
-        ));
-        self.context
-            .fill_text(text, f64::from(x), f64::from(y))
-            .map_err(error_cast)?;
-
-        if degree != 0.0 {
-            self.context.restore();
+        let text_baseline = match style.anchor().v_pos {
+            VPos::Top => "top",
+            VPos::Center => "middle",
+            VPos::Bottom => "alphabetic",
+        };
+        self.context.set_text_baseline(text_baseline);
+        if style.anchor().v_pos == VPos::Bottom {
+            // Lift the descenders above the anchor, as the bottom of the text box is anchored
+            let metrics = self.context.measure_text(text).map_err(error_cast)?;
+            y -= metrics.actual_bounding_box_descent().max(0.0);
         }
 
-        Ok(())
+        self.fill_text_with_effect(text, x, y)
+    }
+
+    /// Draw a text whose anchor applies to its rotated box, see [TextRotation]
+    fn execute_text_anchored_after_rotation<TStyle: BackendTextStyle>(
+        &mut self,
+        text: &str,
+        style: &TStyle,
+        pos: (f64, f64),
+        angle: f64,
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        let (w, h) = self.estimate_text_size(text, style)?;
+        let (w, h) = (f64::from(w), f64::from(h));
+        let (cx, cy) = anchored_center(pos, (w, h), style.anchor(), style.transform());
+
+        let half = w.max(h) / 2.0;
+        let bounds = Rect::new(cx - half, cy - half, cx + half, cy + half);
+        if !self.begin_primitive(PrimitiveKind::Text, bounds.inflate(style.size())) {
+            return Ok(());
+        }
+
+        self.set_text_font(&font_string(style));
+        self.fill_color(style.color());
+        self.context.save();
+        let result = self
+            .context
+            .translate(cx, cy)
+            .and_then(|_| self.context.rotate(angle))
+            .map_err(error_cast)
+            .and_then(|_| {
+                self.context.set_text_align("center");
+                self.context.set_text_baseline("middle");
+                self.fill_text_with_effect(text, 0.0, 0.0)
+            });
+        self.context.restore();
+        result
     }
 }
 
@@ -198,6 +1184,205 @@ mod test {
         }
     }
 
+    #[wasm_bindgen_test]
+    fn test_try_new_reports_the_failure() {
+        let canvas = create_canvas(10, 10);
+        canvas.get_context("bitmaprenderer").unwrap();
+        let error = OffscreenCanvasBackend::try_new(&canvas).err().unwrap();
+        assert!(error.to_string().contains("no 2d context"));
+        assert!(OffscreenCanvasBackend::new(&canvas).is_none());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_error_categories() {
+        let canvas = create_canvas(10, 10);
+        canvas.get_context("bitmaprenderer").unwrap();
+        match OffscreenCanvasBackend::try_new(&canvas) {
+            Err(CanvasError::ContextUnavailable(_)) => {}
+            other => panic!("unexpected result {:?}", other.err()),
+        }
+
+        let error = js_error_in("getImageData")(js_sys::Error::new("tainted").into());
+        assert_eq!(error.to_string(), "Canvas Error: getImageData: Error: tainted");
+        assert_eq!(
+            CanvasError::InvalidInput("bad size".to_string()).to_string(),
+            "Canvas Error: bad size"
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_text_bounds_vertical_anchor() {
+        let bounds = |v_pos| {
+            let anchor = Pos::new(HPos::Left, v_pos);
+            let r = text_bounds((0.0, 0.0), "ab", 10.0, anchor, FontTransform::None);
+            (r.top, r.bottom)
+        };
+        assert_eq!(bounds(VPos::Top), (0.0, 10.0));
+        assert_eq!(bounds(VPos::Center), (-5.0, 5.0));
+        assert_eq!(bounds(VPos::Bottom), (-10.0, 0.0));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_frame_budget_warning() {
+        let canvas = create_canvas(100, 100);
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+
+        let warned = std::rc::Rc::new(std::cell::Cell::new(0));
+        let counter = warned.clone();
+        backend.set_frame_budget(Some(FrameBudget {
+            max_draw_calls: Some(1),
+            max_ms: None,
+        }));
+        backend.on_budget_exceeded(move |w| {
+            assert_eq!(w.top, vec![(PrimitiveKind::Pixel, 2)]);
+            counter.set(counter.get() + 1);
+        });
+
+        backend.draw_pixel((1, 1), BLACK.to_backend_color()).unwrap();
+        backend.draw_pixel((2, 2), BLACK.to_backend_color()).unwrap();
+        backend.present().unwrap();
+        assert_eq!(warned.get(), 1);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_mock_time_source() {
+        let canvas = create_canvas(100, 100);
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        let clock = crate::time::MockClock::new(1000.0);
+        backend.set_time_source(std::rc::Rc::new(clock.clone()));
+
+        backend.draw_pixel((1, 1), BLACK.to_backend_color()).unwrap();
+        clock.advance(12.0);
+        backend.present().unwrap();
+        assert_eq!(backend.frame_timings().count(), 1);
+        assert_eq!(backend.frame_timings().summary().max, 12.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_cull_offscreen_primitives() {
+        let canvas = create_canvas(100, 100);
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+
+        backend.draw_pixel((50, 50), BLACK.to_backend_color()).unwrap();
+        backend.draw_pixel((500, 50), BLACK.to_backend_color()).unwrap();
+        backend
+            .draw_line((-50, -50), (-10, -10), &BLACK.to_backend_color())
+            .unwrap();
+
+        let stats = backend.frame_stats().unwrap();
+        assert_eq!(stats.total(), 1);
+        assert_eq!(stats.culled(), 2);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_release_and_revive() {
+        let canvas = create_canvas(100, 100);
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+
+        backend.release();
+        assert!(backend.is_dormant());
+        assert_eq!(backend.get_size(), (0, 0));
+        backend.draw_pixel((1, 1), BLACK.to_backend_color()).unwrap();
+        assert!(backend.frame_stats().is_none());
+
+        backend.revive(50, 60);
+        assert_eq!(backend.get_size(), (50, 60));
+        backend.draw_pixel((1, 1), BLACK.to_backend_color()).unwrap();
+        assert_eq!(backend.frame_stats().unwrap().total(), 1);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_draw_rect_natively() {
+        let canvas = create_canvas(100, 100);
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+
+        backend
+            .draw_rect((10, 10), (20, 40), &BLACK.to_backend_color(), true)
+            .unwrap();
+        backend
+            .draw_rect((30, 10), (40, 40), &BLACK.to_backend_color(), false)
+            .unwrap();
+
+        let stats = backend.frame_stats().unwrap();
+        assert_eq!(stats.count(PrimitiveKind::Rect), 2);
+        assert_eq!(stats.count(PrimitiveKind::Pixel), 0);
+        assert_eq!(stats.count(PrimitiveKind::Line), 0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_draw_circle_natively() {
+        let canvas = create_canvas(100, 100);
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+
+        backend
+            .draw_circle((50, 50), 10, &BLACK.to_backend_color(), true)
+            .unwrap();
+        backend
+            .draw_circle((20, 20), 5, &BLACK.to_backend_color(), false)
+            .unwrap();
+
+        let stats = backend.frame_stats().unwrap();
+        assert_eq!(stats.count(PrimitiveKind::Circle), 2);
+        assert_eq!(stats.total(), 2);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_draw_path_strokes_once() {
+        let canvas = create_canvas(100, 100);
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+
+        let points: Vec<_> = (0..50).map(|i| (i * 2, 50 + (i % 7))).collect();
+        backend
+            .draw_path(points, &BLACK.to_backend_color())
+            .unwrap();
+
+        let stats = backend.frame_stats().unwrap();
+        assert_eq!(stats.count(PrimitiveKind::Path), 1);
+        assert_eq!(stats.total(), 1);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_fill_polygon_natively() {
+        let canvas = create_canvas(100, 100);
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+
+        backend
+            .fill_polygon(
+                vec![(0, 100), (20, 40), (50, 60), (100, 100)],
+                &BLACK.to_backend_color(),
+            )
+            .unwrap();
+
+        let stats = backend.frame_stats().unwrap();
+        assert_eq!(stats.count(PrimitiveKind::Polygon), 1);
+        assert_eq!(stats.count(PrimitiveKind::Pixel), 0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_estimate_text_size_from_metrics() {
+        let canvas = create_canvas(100, 100);
+        let backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        let style = ("sans-serif", 20).into_font();
+
+        let (short, height) = backend.estimate_text_size("1", &style).unwrap();
+        let (long, _) = backend.estimate_text_size("1000000", &style).unwrap();
+        assert!(long > short);
+        assert!(height > 0 && height <= 30);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_owned_canvas_backend() {
+        fn static_area() -> DrawingArea<OffscreenCanvasBackend<'static>, plotters::coord::Shift> {
+            OffscreenCanvasBackend::with_canvas(create_canvas(60, 40))
+                .expect("cannot find canvas")
+                .into_drawing_area()
+        }
+
+        let root = static_area();
+        assert_eq!(root.dim_in_pixel(), (60, 40));
+        root.fill(&WHITE).unwrap();
+    }
+
     fn check_content(_canvas: &OffscreenCanvas) {
         // let blob = canvas.convert_to_blob().unwrap();
         // blob.
@@ -0,0 +1,155 @@
+use std::cell::Cell;
+use std::iter::Peekable;
+use std::rc::Rc;
+
+/// A flag shared between the code requesting a render and the render itself
+///  - Clones share the same state, cancelling any of them cancels all
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Rc<Cell<bool>>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request the render to stop, it takes effect at the next batch boundary
+    pub fn cancel(&self) {
+        self.0.set(true);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.get()
+    }
+}
+
+/// State of a chunked render after a slice of work
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChunkStatus {
+    /// There are still items left, call `run` again in the next slice
+    Pending,
+    /// All the items have been rendered
+    Done,
+    /// The token was cancelled, the remaining items are dropped
+    Cancelled,
+}
+
+/// A render split into batches of items, which can be spread across several frames
+///
+/// The cancellation token is checked before every batch, so a superseded render (the user
+/// already changed the zoom) stops without wasting more frames.
+pub struct ChunkedRender<I: Iterator> {
+    /// Peeked after every batch, so that the last one already reports Done
+    items: Peekable<I>,
+    batch_size: usize,
+    token: CancellationToken,
+    batch: Vec<I::Item>,
+    done: bool,
+}
+
+impl<I: Iterator> ChunkedRender<I> {
+    pub fn new<T: IntoIterator<IntoIter = I>>(
+        items: T,
+        batch_size: usize,
+        token: CancellationToken,
+    ) -> Self {
+        Self {
+            items: items.into_iter().peekable(),
+            batch_size: batch_size.max(1),
+            token,
+            batch: Vec::new(),
+            done: false,
+        }
+    }
+
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// Render at most `max_batches` batches
+    pub fn run<E, F: FnMut(&[I::Item]) -> Result<(), E>>(
+        &mut self,
+        max_batches: usize,
+        mut render: F,
+    ) -> Result<ChunkStatus, E> {
+        self.run_while(|n| n < max_batches, |batch| render(batch))
+    }
+
+    /// Render batches as long as `keep_going(batches_rendered_so_far)` returns true
+    ///  - Useful with a time budget: `|_| now() < deadline`
+    pub fn run_while<E, C: FnMut(usize) -> bool, F: FnMut(&[I::Item]) -> Result<(), E>>(
+        &mut self,
+        mut keep_going: C,
+        mut render: F,
+    ) -> Result<ChunkStatus, E> {
+        let mut rendered = 0;
+        loop {
+            if self.done {
+                return Ok(ChunkStatus::Done);
+            }
+            if self.token.is_cancelled() {
+                self.done = true;
+                return Ok(ChunkStatus::Cancelled);
+            }
+            if !keep_going(rendered) {
+                return Ok(ChunkStatus::Pending);
+            }
+
+            self.batch.clear();
+            self.batch
+                .extend(self.items.by_ref().take(self.batch_size));
+            if self.items.peek().is_none() {
+                self.done = true;
+            }
+            if !self.batch.is_empty() {
+                render(&self.batch)?;
+            }
+            rendered += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_chunked_render_cancel() {
+        let token = CancellationToken::new();
+        let mut render = ChunkedRender::new(0..100, 10, token.clone());
+
+        let mut seen = 0;
+        let status = render
+            .run(3, |batch| -> Result<(), ()> {
+                seen += batch.len();
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(status, ChunkStatus::Pending);
+        assert_eq!(seen, 30);
+
+        token.cancel();
+        let status = render
+            .run(3, |batch| -> Result<(), ()> {
+                seen += batch.len();
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(status, ChunkStatus::Cancelled);
+        assert_eq!(seen, 30);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_chunked_render_done_with_last_full_batch() {
+        let mut render = ChunkedRender::new(0..30, 10, CancellationToken::new());
+        let mut batches = 0;
+        let status = render
+            .run(3, |_| -> Result<(), ()> {
+                batches += 1;
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(status, ChunkStatus::Done);
+        assert_eq!(batches, 3);
+    }
+}
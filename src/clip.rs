@@ -0,0 +1,202 @@
+use plotters_backend::{BackendCoord, DrawingErrorKind};
+use web_sys::Path2d;
+
+use crate::canvas::{CanvasError, OffscreenCanvasBackend};
+use crate::geometry::Rect;
+
+/// An area nothing is inside of, used when nested clips don't overlap
+const NOWHERE: Rect = Rect {
+    left: f64::INFINITY,
+    top: f64::INFINITY,
+    right: f64::NEG_INFINITY,
+    bottom: f64::NEG_INFINITY,
+};
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Only draw the following primitives inside the polygon, until the matching pop_clip()
+    ///  - Clips nest, each one is intersected with the ones pushed before it
+    pub fn push_clip_path(
+        &mut self,
+        points: &[BackendCoord],
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        // The batched lines are drawn under the previous clip
+        self.flush_batch();
+        let mut coords = Vec::with_capacity(points.len());
+        for p in points {
+            match self.coord(*p)? {
+                Some(p) => coords.push(p),
+                None => {
+                    self.push_clip_bounds(NOWHERE);
+                    return Ok(());
+                }
+            }
+        }
+        let bounds = match Rect::bounding(&coords) {
+            Some(bounds) => bounds,
+            None => {
+                self.push_clip_bounds(NOWHERE);
+                return Ok(());
+            }
+        };
+
+        self.context.save();
+        self.context.begin_path();
+        let mut iter = coords.iter();
+        if let Some(&(x, y)) = iter.next() {
+            self.context.move_to(x, y);
+        }
+        for &(x, y) in iter {
+            self.context.line_to(x, y);
+        }
+        self.context.close_path();
+        self.context.clip();
+        self.push_clip_bounds(bounds);
+        Ok(())
+    }
+
+    /// Only draw the following primitives inside the rectangle, e.g. the plotting area of a
+    /// chart so that the series don't bleed over the axes, until the matching pop_clip()
+    ///  - A negative width or height extends the rectangle left or up of (x, y)
+    pub fn push_clip_rect(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        self.flush_batch();
+        let corners = (
+            self.coord((x, y))?,
+            self.coord((x.saturating_add(width), y.saturating_add(height)))?,
+        );
+        let (a, b) = match corners {
+            (Some(a), Some(b)) => (a, b),
+            _ => {
+                self.push_clip_bounds(NOWHERE);
+                return Ok(());
+            }
+        };
+        let bounds = Rect::new(a.0.min(b.0), a.1.min(b.1), a.0.max(b.0), a.1.max(b.1));
+
+        self.context.save();
+        self.context.begin_path();
+        self.context
+            .rect(bounds.left, bounds.top, bounds.width(), bounds.height());
+        self.context.clip();
+        self.push_clip_bounds(bounds);
+        Ok(())
+    }
+
+    /// Clip to an arbitrary path, e.g. a gauge arc, `bounds` is its bounding box and is used to
+    /// cull the primitives outside of it
+    pub fn push_clip_path_2d(&mut self, path: &Path2d, bounds: Rect) {
+        self.flush_batch();
+        self.context.save();
+        self.context.clip_with_path_2d(path);
+        self.push_clip_bounds(bounds);
+    }
+
+    /// Remove the last clip, return false if there was none
+    pub fn pop_clip(&mut self) -> bool {
+        self.flush_batch();
+        if self.clip_stack.pop().is_none() {
+            return false;
+        }
+        if self.clip_saved.pop() == Some(true) {
+            self.context.restore();
+            // The restored state may have any line dash, shadow, ...
+            self.applied_extras = None;
+            self.style_cache.forget();
+        }
+        true
+    }
+
+    /// Number of clips currently pushed
+    pub fn clip_depth(&self) -> usize {
+        self.clip_stack.len()
+    }
+
+    fn push_clip_bounds(&mut self, bounds: Rect) {
+        // An empty clip only has to cull, the context state is left as is
+        let saved = bounds != NOWHERE;
+        let bounds = match self.clip_stack.last() {
+            Some(outer) => outer.intersection(&bounds).unwrap_or(NOWHERE),
+            None => {
+                if saved {
+                    self.last_clip = Some(bounds);
+                }
+                bounds
+            }
+        };
+        self.clip_stack.push(bounds);
+        self.clip_saved.push(saved);
+    }
+
+    /// The area the current clip lets primitives through, None without clip
+    pub(crate) fn clip_bounds(&self) -> Option<Rect> {
+        self.clip_stack.last().copied()
+    }
+
+    /// Drop the clips, after the context state was reset
+    pub(crate) fn forget_clips(&mut self) {
+        self.clip_stack.clear();
+        self.clip_saved.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use plotters::prelude::*;
+    use plotters_backend::DrawingBackend;
+    use wasm_bindgen_test::*;
+    use web_sys::OffscreenCanvas;
+
+    #[wasm_bindgen_test]
+    fn test_clip_culls_outside_primitives() {
+        let canvas = OffscreenCanvas::new(100, 100).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+
+        backend
+            .push_clip_path(&[(0, 0), (40, 0), (20, 40)])
+            .unwrap();
+        backend
+            .draw_pixel((20, 10), BLACK.to_backend_color())
+            .unwrap();
+        backend
+            .draw_pixel((80, 80), BLACK.to_backend_color())
+            .unwrap();
+        assert_eq!(backend.frame_stats().unwrap().culled(), 1);
+
+        backend
+            .push_clip_path(&[(60, 60), (90, 60), (90, 90)])
+            .unwrap();
+        backend
+            .draw_pixel((80, 80), BLACK.to_backend_color())
+            .unwrap();
+        assert_eq!(backend.frame_stats().unwrap().culled(), 2);
+
+        assert!(backend.pop_clip());
+        assert!(backend.pop_clip());
+        assert!(!backend.pop_clip());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_clip_rect() {
+        let canvas = OffscreenCanvas::new(100, 100).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+
+        backend.push_clip_rect(60, 60, -40, -40).unwrap();
+        assert_eq!(
+            backend.clip_bounds(),
+            Some(Rect::new(20.0, 20.0, 60.0, 60.0))
+        );
+        backend
+            .draw_line((0, 40), (100, 40), &BLACK.stroke_width(2))
+            .unwrap();
+        assert!(backend.pop_clip());
+
+        assert_eq!(backend.pick_color(40, 40).unwrap().alpha, 1.0);
+        assert_eq!(backend.pick_color(10, 40).unwrap().alpha, 0.0);
+    }
+}
@@ -0,0 +1,168 @@
+use std::fmt::Write;
+
+use plotters_backend::BackendColor;
+use web_sys::OffscreenCanvasRenderingContext2d;
+
+use crate::canvas::OffscreenCanvasBackend;
+
+/// How translucent colors are put on the canvas
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompositingMode {
+    /// Let the canvas blend the colors, in its own color space
+    Native,
+    /// Blend the colors against a known opaque background before painting, the way the bitmap
+    /// backend composites, so that the exported images are pixel comparable to server
+    /// rendered charts
+    ///  - Only exact for shapes drawn directly over the background, overlapping translucent
+    ///    shapes cover each other instead of blending
+    PreBlend((u8, u8, u8)),
+}
+
+impl Default for CompositingMode {
+    fn default() -> Self {
+        CompositingMode::Native
+    }
+}
+
+impl CompositingMode {
+    pub fn apply(&self, color: BackendColor) -> BackendColor {
+        match self {
+            CompositingMode::Native => color,
+            CompositingMode::PreBlend(background) => pre_blend(color, *background),
+        }
+    }
+}
+
+/// The opaque color of `color` painted over `background`
+pub fn pre_blend(color: BackendColor, background: (u8, u8, u8)) -> BackendColor {
+    let alpha = color.alpha.max(0.0).min(1.0);
+    // Truncated like the fixed point blending of the bitmap backend
+    let mix = |c: u8, b: u8| (f64::from(c) * alpha + f64::from(b) * (1.0 - alpha)) as u8;
+    BackendColor {
+        alpha: 1.0,
+        rgb: (
+            mix(color.rgb.0, background.0),
+            mix(color.rgb.1, background.1),
+            mix(color.rgb.2, background.2),
+        ),
+    }
+}
+
+/// Write the CSS representation of the color into `buf`, replacing its content
+pub(crate) fn write_canvas_color(buf: &mut String, color: BackendColor) {
+    let (r, g, b) = color.rgb;
+    buf.clear();
+    let _ = write!(buf, "rgba({},{},{},{})", r, g, b, color.alpha);
+}
+
+pub(crate) fn make_canvas_color(color: BackendColor) -> String {
+    let mut buf = String::with_capacity(24);
+    write_canvas_color(&mut buf, color);
+    buf
+}
+
+/// Set the fill style from a CSS color string
+///  - With the `str-style-setters` feature the string setter of newer web-sys (>= 0.3.70) is
+///    used, which doesn't go through a JsValue
+#[cfg(feature = "str-style-setters")]
+pub(crate) fn set_fill_css(context: &OffscreenCanvasRenderingContext2d, css: &str) {
+    context.set_fill_style_str(css);
+}
+
+#[cfg(not(feature = "str-style-setters"))]
+pub(crate) fn set_fill_css(context: &OffscreenCanvasRenderingContext2d, css: &str) {
+    context.set_fill_style(&wasm_bindgen::JsValue::from_str(css));
+}
+
+/// Set the stroke style from a CSS color string, see [set_fill_css]
+#[cfg(feature = "str-style-setters")]
+pub(crate) fn set_stroke_css(context: &OffscreenCanvasRenderingContext2d, css: &str) {
+    context.set_stroke_style_str(css);
+}
+
+#[cfg(not(feature = "str-style-setters"))]
+pub(crate) fn set_stroke_css(context: &OffscreenCanvasRenderingContext2d, css: &str) {
+    context.set_stroke_style(&wasm_bindgen::JsValue::from_str(css));
+}
+
+/// Set the fill style to a gradient, see [set_fill_css]
+#[cfg(feature = "str-style-setters")]
+pub(crate) fn set_fill_gradient(
+    context: &OffscreenCanvasRenderingContext2d,
+    gradient: &web_sys::CanvasGradient,
+) {
+    context.set_fill_style_canvas_gradient(gradient);
+}
+
+#[cfg(not(feature = "str-style-setters"))]
+pub(crate) fn set_fill_gradient(
+    context: &OffscreenCanvasRenderingContext2d,
+    gradient: &web_sys::CanvasGradient,
+) {
+    context.set_fill_style(gradient);
+}
+
+/// Set the fill style to a pattern, see [set_fill_css]
+#[cfg(feature = "str-style-setters")]
+pub(crate) fn set_fill_pattern(
+    context: &OffscreenCanvasRenderingContext2d,
+    pattern: &web_sys::CanvasPattern,
+) {
+    context.set_fill_style_canvas_pattern(pattern);
+}
+
+#[cfg(not(feature = "str-style-setters"))]
+pub(crate) fn set_fill_pattern(
+    context: &OffscreenCanvasRenderingContext2d,
+    pattern: &web_sys::CanvasPattern,
+) {
+    context.set_fill_style(pattern);
+}
+
+pub(crate) fn set_fill_color(context: &OffscreenCanvasRenderingContext2d, color: BackendColor) {
+    set_fill_css(context, &make_canvas_color(color));
+}
+
+pub(crate) fn set_stroke_color(context: &OffscreenCanvasRenderingContext2d, color: BackendColor) {
+    set_stroke_css(context, &make_canvas_color(color));
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    pub fn set_compositing_mode(&mut self, mode: CompositingMode) {
+        self.compositing = mode;
+    }
+
+    pub fn compositing_mode(&self) -> CompositingMode {
+        self.compositing
+    }
+
+    /// Set the fill color of the context, through the compositing mode
+    pub(crate) fn fill_color(&self, color: BackendColor) {
+        self.style_cache
+            .set_fill(&self.context, self.compositing.apply(color));
+    }
+
+    /// Set the stroke color of the context, through the compositing mode
+    pub(crate) fn stroke_color(&self, color: BackendColor) {
+        self.style_cache
+            .set_stroke(&self.context, self.compositing.apply(color));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_pre_blend() {
+        let red = BackendColor {
+            alpha: 0.5,
+            rgb: (255, 0, 0),
+        };
+        let blended = pre_blend(red, (255, 255, 255));
+        assert_eq!(blended.rgb, (255, 127, 127));
+        assert_eq!(blended.alpha, 1.0);
+        assert_eq!(CompositingMode::Native.apply(red).alpha, 0.5);
+    }
+}
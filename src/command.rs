@@ -0,0 +1,272 @@
+use std::fmt;
+
+use plotters_backend::{
+    text_anchor::Pos, BackendColor, BackendCoord, BackendStyle, BackendTextStyle, FontFamily,
+    FontStyle, FontTransform,
+};
+
+use crate::canvas::{text_bounds, CanvasError};
+
+/// An owned copy of a backend color
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CommandColor {
+    pub rgb: (u8, u8, u8),
+    pub alpha: f64,
+}
+
+impl From<BackendColor> for CommandColor {
+    fn from(color: BackendColor) -> Self {
+        Self {
+            rgb: color.rgb,
+            alpha: color.alpha,
+        }
+    }
+}
+
+impl CommandColor {
+    pub fn to_backend_color(&self) -> BackendColor {
+        BackendColor {
+            alpha: self.alpha,
+            rgb: self.rgb,
+        }
+    }
+}
+
+/// An owned copy of a shape style
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CommandStyle {
+    pub color: CommandColor,
+    pub stroke_width: u32,
+}
+
+impl CommandStyle {
+    pub fn from_style<S: BackendStyle>(style: &S) -> Self {
+        Self {
+            color: style.color().into(),
+            stroke_width: style.stroke_width(),
+        }
+    }
+}
+
+impl BackendStyle for CommandStyle {
+    fn color(&self) -> BackendColor {
+        self.color.to_backend_color()
+    }
+
+    fn stroke_width(&self) -> u32 {
+        self.stroke_width
+    }
+}
+
+/// An owned copy of a text style
+#[derive(Clone)]
+pub struct TextCommandStyle {
+    pub family: String,
+    pub style: FontStyle,
+    pub size: f64,
+    pub color: CommandColor,
+    pub anchor: Pos,
+    pub transform: FontTransform,
+}
+
+impl TextCommandStyle {
+    pub fn from_style<S: BackendTextStyle>(style: &S) -> Self {
+        Self {
+            family: style.family().as_str().to_string(),
+            style: style.style(),
+            size: style.size(),
+            color: style.color().into(),
+            anchor: style.anchor(),
+            transform: style.transform(),
+        }
+    }
+}
+
+impl BackendTextStyle for TextCommandStyle {
+    type FontError = CanvasError;
+
+    fn color(&self) -> BackendColor {
+        self.color.to_backend_color()
+    }
+
+    fn size(&self) -> f64 {
+        self.size
+    }
+
+    fn transform(&self) -> FontTransform {
+        self.transform.clone()
+    }
+
+    fn style(&self) -> FontStyle {
+        self.style
+    }
+
+    fn anchor(&self) -> Pos {
+        self.anchor
+    }
+
+    fn family(&self) -> FontFamily {
+        FontFamily::from(self.family.as_str())
+    }
+
+    fn layout_box(&self, text: &str) -> Result<((i32, i32), (i32, i32)), Self::FontError> {
+        let rect = text_bounds(
+            (0.0, 0.0),
+            text,
+            self.size,
+            self.anchor,
+            self.transform.clone(),
+        );
+        Ok((
+            (rect.left.floor() as i32, rect.top.floor() as i32),
+            (rect.right.ceil() as i32, rect.bottom.ceil() as i32),
+        ))
+    }
+
+    /// The style is only used to replay commands on the canvas, which rasterizes text itself
+    fn draw<E, DrawFunc: FnMut(i32, i32, BackendColor) -> Result<(), E>>(
+        &self,
+        _text: &str,
+        _pos: BackendCoord,
+        _draw: DrawFunc,
+    ) -> Result<Result<(), E>, Self::FontError> {
+        Ok(Ok(()))
+    }
+}
+
+/// A primitive of the drawing backend API, with owned arguments
+#[derive(Clone)]
+pub enum DrawCommand {
+    Pixel {
+        point: BackendCoord,
+        color: CommandColor,
+    },
+    Line {
+        from: BackendCoord,
+        to: BackendCoord,
+        style: CommandStyle,
+    },
+    Text {
+        text: String,
+        pos: BackendCoord,
+        style: TextCommandStyle,
+    },
+    Rect {
+        upper_left: BackendCoord,
+        bottom_right: BackendCoord,
+        style: CommandStyle,
+        fill: bool,
+    },
+    Circle {
+        center: BackendCoord,
+        radius: u32,
+        style: CommandStyle,
+        fill: bool,
+    },
+    Path {
+        points: Vec<BackendCoord>,
+        style: CommandStyle,
+    },
+    Polygon {
+        points: Vec<BackendCoord>,
+        style: CommandStyle,
+    },
+    Bitmap {
+        pos: BackendCoord,
+        size: (u32, u32),
+        /// RGB or RGBA pixels
+        data: Vec<u8>,
+    },
+}
+
+impl DrawCommand {
+    /// Move the command by (dx, dy)
+    pub fn translate(&mut self, dx: i32, dy: i32) {
+        let shift = |p: &mut BackendCoord| {
+            p.0 = p.0.saturating_add(dx);
+            p.1 = p.1.saturating_add(dy);
+        };
+        match self {
+            DrawCommand::Pixel { point, .. } => shift(point),
+            DrawCommand::Line { from, to, .. } => {
+                shift(from);
+                shift(to);
+            }
+            DrawCommand::Text { pos, .. } => shift(pos),
+            DrawCommand::Rect {
+                upper_left,
+                bottom_right,
+                ..
+            } => {
+                shift(upper_left);
+                shift(bottom_right);
+            }
+            DrawCommand::Circle { center, .. } => shift(center),
+            DrawCommand::Path { points, .. } | DrawCommand::Polygon { points, .. } => {
+                points.iter_mut().for_each(shift)
+            }
+            DrawCommand::Bitmap { pos, .. } => shift(pos),
+        }
+    }
+
+    /// Name of the primitive, as used in logs
+    pub fn name(&self) -> &'static str {
+        match self {
+            DrawCommand::Pixel { .. } => "pixel",
+            DrawCommand::Line { .. } => "line",
+            DrawCommand::Text { .. } => "text",
+            DrawCommand::Rect { .. } => "rect",
+            DrawCommand::Circle { .. } => "circle",
+            DrawCommand::Path { .. } => "path",
+            DrawCommand::Polygon { .. } => "polygon",
+            DrawCommand::Bitmap { .. } => "bitmap",
+        }
+    }
+}
+
+impl fmt::Debug for DrawCommand {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DrawCommand::Pixel { point, color } => {
+                write!(fmt, "Pixel({:?}, {:?})", point, color)
+            }
+            DrawCommand::Line { from, to, style } => {
+                write!(fmt, "Line({:?} -> {:?}, {:?})", from, to, style)
+            }
+            DrawCommand::Text { text, pos, style } => write!(
+                fmt,
+                "Text({:?} at {:?}, {}px {})",
+                text, pos, style.size, style.family
+            ),
+            DrawCommand::Rect {
+                upper_left,
+                bottom_right,
+                style,
+                fill,
+            } => write!(
+                fmt,
+                "Rect({:?} -> {:?}, {:?}, fill: {})",
+                upper_left, bottom_right, style, fill
+            ),
+            DrawCommand::Circle {
+                center,
+                radius,
+                style,
+                fill,
+            } => write!(
+                fmt,
+                "Circle({:?}, r: {}, {:?}, fill: {})",
+                center, radius, style, fill
+            ),
+            DrawCommand::Path { points, style } => {
+                write!(fmt, "Path({} points, {:?})", points.len(), style)
+            }
+            DrawCommand::Polygon { points, style } => {
+                write!(fmt, "Polygon({} points, {:?})", points.len(), style)
+            }
+            DrawCommand::Bitmap { pos, size, .. } => {
+                write!(fmt, "Bitmap({:?}, {}x{})", pos, size.0, size.1)
+            }
+        }
+    }
+}
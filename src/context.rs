@@ -0,0 +1,138 @@
+use js_sys::{Object, Reflect};
+use wasm_bindgen::JsCast;
+use web_sys::{OffscreenCanvas, OffscreenCanvasRenderingContext2d};
+
+use crate::canvas::{js_error, CanvasError, OffscreenCanvasBackend};
+
+/// Attributes requested when the 2d context of a canvas is created
+///  - The attributes are only honored by the first getContext call of a canvas, a canvas
+///    which already has a context keeps the attributes it was created with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ContextOptions {
+    alpha: bool,
+    desynchronized: bool,
+    will_read_frequently: bool,
+}
+
+impl Default for ContextOptions {
+    fn default() -> Self {
+        ContextOptions {
+            alpha: true,
+            desynchronized: false,
+            will_read_frequently: false,
+        }
+    }
+}
+
+impl ContextOptions {
+    /// The browser defaults: transparent, synchronized, GPU accelerated
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// With `false` the canvas is opaque, its pixels start black and the chart must paint
+    /// its own background. Compositing the canvas is cheaper
+    pub fn alpha(mut self, alpha: bool) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Let the browser show the canvas without waiting for the event loop, at the cost of
+    /// tearing. Lowers the latency of streaming charts
+    pub fn desynchronized(mut self, desynchronized: bool) -> Self {
+        self.desynchronized = desynchronized;
+        self
+    }
+
+    /// Keep the bitmap in memory the CPU can read, for backends doing many pixel readbacks
+    ///  - Drawing may be slower, as the canvas is not GPU accelerated anymore
+    pub fn will_read_frequently(mut self, will_read_frequently: bool) -> Self {
+        self.will_read_frequently = will_read_frequently;
+        self
+    }
+
+    pub fn is_opaque(&self) -> bool {
+        !self.alpha
+    }
+
+    pub fn is_desynchronized(&self) -> bool {
+        self.desynchronized
+    }
+
+    pub fn reads_frequently(&self) -> bool {
+        self.will_read_frequently
+    }
+
+    /// The `CanvasRenderingContext2DSettings` dictionary passed to getContext
+    pub fn to_js(&self) -> Result<Object, CanvasError> {
+        let options = Object::new();
+        Reflect::set(&options, &"alpha".into(), &self.alpha.into()).map_err(js_error)?;
+        Reflect::set(
+            &options,
+            &"desynchronized".into(),
+            &self.desynchronized.into(),
+        )
+        .map_err(js_error)?;
+        Reflect::set(
+            &options,
+            &"willReadFrequently".into(),
+            &self.will_read_frequently.into(),
+        )
+        .map_err(js_error)?;
+        Ok(options)
+    }
+}
+
+/// Get the 2d context of a canvas with the given attributes
+pub(crate) fn context_with_options(
+    canvas: &OffscreenCanvas,
+    options: &ContextOptions,
+) -> Result<OffscreenCanvasRenderingContext2d, CanvasError> {
+    canvas
+        .get_context_with_context_options("2d", &options.to_js()?)
+        .map_err(|e| CanvasError::ContextUnavailable(js_error(e).message().to_string()))?
+        .ok_or_else(|| CanvasError::ContextUnavailable("cannot get a 2d context".to_string()))?
+        .dyn_into()
+        .map_err(|_| CanvasError::ContextUnavailable("not a 2d rendering context".to_string()))
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Create a backend whose context gets the given attributes
+    ///  - Return None if the canvas can't provide a 2d context
+    pub fn new_with_options(canvas: &'a OffscreenCanvas, options: &ContextOptions) -> Option<Self> {
+        let context = context_with_options(canvas, options).ok()?;
+        let mut backend = Self::with_context(canvas, context);
+        backend.read_frequently = options.reads_frequently();
+        Some(backend)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_context_options() {
+        let options = ContextOptions::new().alpha(false).desynchronized(true);
+        let js = options.to_js().unwrap();
+        assert_eq!(
+            Reflect::get(&js, &"alpha".into()).unwrap().as_bool(),
+            Some(false)
+        );
+        assert_eq!(
+            Reflect::get(&js, &"desynchronized".into())
+                .unwrap()
+                .as_bool(),
+            Some(true)
+        );
+
+        let canvas = OffscreenCanvas::new(10, 10).unwrap();
+        let backend = OffscreenCanvasBackend::new_with_options(
+            &canvas,
+            &ContextOptions::new().will_read_frequently(true),
+        )
+        .expect("cannot find canvas");
+        assert!(backend.read_frequently);
+    }
+}
@@ -0,0 +1,96 @@
+use plotters_backend::BackendCoord;
+
+use crate::canvas::CanvasError;
+use crate::warn::warn_once;
+
+/// Largest coordinate magnitude which is passed to the canvas unchanged
+///  - The canvas rasterizes in single precision, beyond 2^24 paths misrender or throw
+pub const SAFE_COORD_LIMIT: i32 = 1 << 24;
+
+/// A coordinate component in canvas space, None if it's outside of the safe range
+///  - Every component in the safe range is exactly representable as a f32, which the canvas
+///    rasterizes with, and as a f64
+pub fn checked_component(v: i32) -> Option<f64> {
+    if (-SAFE_COORD_LIMIT..=SAFE_COORD_LIMIT).contains(&v) {
+        Some(f64::from(v))
+    } else {
+        None
+    }
+}
+
+/// Round a position in canvas space to a backend coordinate
+///  - Values which don't fit in an i32 saturate and NaN is sent past the safe range, so the
+///    coordinate policy of the backend decides what happens to them instead of the cast
+pub fn pixel_to_coord(x: f64, y: f64) -> BackendCoord {
+    let round = |v: f64| {
+        if v.is_nan() {
+            i32::MAX
+        } else {
+            v.round() as i32
+        }
+    };
+    (round(x), round(y))
+}
+
+/// What to do with a coordinate outside of the safe range (usually overflow from bad data)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CoordPolicy {
+    /// Clamp the coordinate to the safe range (the default)
+    Clamp,
+    /// Silently skip the primitive
+    Skip,
+    /// Fail the draw call
+    Error,
+}
+
+impl Default for CoordPolicy {
+    fn default() -> Self {
+        CoordPolicy::Clamp
+    }
+}
+
+impl CoordPolicy {
+    /// Convert a backend coordinate to canvas space according to the policy
+    ///  - Return None if the primitive using the coordinate must be skipped
+    pub fn apply(&self, p: BackendCoord) -> Result<Option<(f64, f64)>, CanvasError> {
+        if let (Some(x), Some(y)) = (checked_component(p.0), checked_component(p.1)) {
+            return Ok(Some((x, y)));
+        }
+
+        match self {
+            CoordPolicy::Clamp => {
+                warn_once(
+                    "coordinates outside of the safe range were clamped, \
+                     the chart may be drawn with the wrong shapes",
+                );
+                let clamp = |v: i32| f64::from(v.max(-SAFE_COORD_LIMIT).min(SAFE_COORD_LIMIT));
+                Ok(Some((clamp(p.0), clamp(p.1))))
+            }
+            CoordPolicy::Skip => Ok(None),
+            CoordPolicy::Error => Err(CanvasError::InvalidInput(format!(
+                "coordinate ({}, {}) is outside of the safe range",
+                p.0, p.1
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_checked_conversions() {
+        assert_eq!(pixel_to_coord(1.4, -2.6), (1, -3));
+        assert_eq!(pixel_to_coord(1e12, -1e12), (i32::MAX, i32::MIN));
+        assert_eq!(checked_component(pixel_to_coord(f64::NAN, 0.0).0), None);
+
+        assert_eq!(CoordPolicy::Skip.apply((i32::MAX, 0)).unwrap(), None);
+        assert_eq!(
+            CoordPolicy::Clamp.apply((i32::MIN, 5)).unwrap(),
+            Some((-f64::from(SAFE_COORD_LIMIT), 5.0))
+        );
+        assert!(CoordPolicy::Error.apply((0, i32::MAX)).is_err());
+    }
+}
@@ -0,0 +1,104 @@
+use plotters::style::ShapeStyle;
+
+use crate::canvas::OffscreenCanvasBackend;
+use crate::style_ext::CanvasShapeStyle;
+
+/// A style whose strokes are dashed on this backend, e.g. for mesh lines
+///  - `pattern` alternates the lengths of dashes and gaps, in pixels
+///  - Other backends draw the style as a solid line
+///
+/// ```ignore
+/// chart
+///     .configure_mesh()
+///     .light_line_style(dashed(BLACK.mix(0.1), &[4.0, 4.0]))
+///     .draw()?;
+/// ```
+pub fn dashed<S: Into<ShapeStyle>>(style: S, pattern: &[f64]) -> ShapeStyle {
+    CanvasShapeStyle::new(style).dash(pattern).into()
+}
+
+/// A style whose strokes are dotted on this backend, dots as large as the line width
+pub fn dotted<S: Into<ShapeStyle>>(style: S) -> ShapeStyle {
+    let style = style.into();
+    let w = f64::from(style.stroke_width.max(1));
+    dashed(style, &[w, w * 2.0])
+}
+
+/// Dash any style this backend accepts, e.g. `BLACK.mix(0.1).dashed(&[4.0, 4.0])`
+pub trait LineStyleExt: Into<ShapeStyle> {
+    /// See [dashed]
+    fn dashed(self, pattern: &[f64]) -> ShapeStyle {
+        dashed(self, pattern)
+    }
+
+    /// See [dotted]
+    fn dotted(self) -> ShapeStyle {
+        dotted(self)
+    }
+
+    /// Dashed strokes starting `offset` pixels into the pattern
+    fn dashed_with_offset(self, pattern: &[f64], offset: f64) -> ShapeStyle {
+        CanvasShapeStyle::new(self)
+            .dash(pattern)
+            .dash_offset(offset)
+            .into()
+    }
+}
+
+impl<S: Into<ShapeStyle>> LineStyleExt for S {}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Dash the lines and paths drawn from now on, whose style doesn't have its own dash
+    ///  - Shape outlines (rectangles, circles, polygons) stay solid
+    pub fn set_line_dash(&mut self, pattern: &[f64], offset: f64) {
+        self.line_dash = Some((pattern.to_vec(), offset));
+    }
+
+    /// Go back to solid lines and paths
+    pub fn clear_line_dash(&mut self) {
+        self.line_dash = None;
+    }
+
+    /// Draw with the lines and paths dashed, e.g. the grid of a chart, then go back to the
+    /// previous line dash mode
+    pub fn with_line_dash<R, F: FnOnce(&mut Self) -> R>(
+        &mut self,
+        pattern: &[f64],
+        offset: f64,
+        draw: F,
+    ) -> R {
+        let previous = self.line_dash.replace((pattern.to_vec(), offset));
+        let result = draw(self);
+        self.line_dash = previous;
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use plotters::prelude::*;
+    use plotters_backend::DrawingBackend;
+    use wasm_bindgen_test::*;
+    use web_sys::OffscreenCanvas;
+
+    #[wasm_bindgen_test]
+    fn test_line_dash_mode() {
+        let canvas = OffscreenCanvas::new(20, 20).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+
+        backend
+            .with_line_dash(&[2.0, 3.0], 1.0, |b| {
+                b.draw_line((0, 5), (20, 5), &BLACK.to_backend_color())
+            })
+            .unwrap();
+        assert_eq!(backend.context.get_line_dash().length(), 2);
+        assert_eq!(backend.context.line_dash_offset(), 1.0);
+        assert!(backend.line_dash.is_none());
+
+        // Styles with their own dash keep it
+        let style = BLACK.dashed_with_offset(&[1.0, 1.0, 4.0, 1.0], 0.0);
+        backend.draw_line((0, 10), (20, 10), &style).unwrap();
+        assert_eq!(backend.context.get_line_dash().length(), 4);
+    }
+}
@@ -0,0 +1,159 @@
+use plotters_backend::{DrawingBackend, DrawingErrorKind};
+
+use crate::canvas::{error_cast, CanvasError, OffscreenCanvasBackend};
+use crate::color::{set_fill_css, set_stroke_css};
+use crate::stats::PrimitiveKind;
+
+/// The labeled pixel grid drawn on top of the frame when the debug overlay is on
+#[derive(Clone, Debug)]
+pub struct DebugGrid {
+    /// Distance between grid lines, in physical pixels
+    pub spacing: u32,
+    /// Label every n-th grid line with its pixel coordinate
+    pub label_every: u32,
+    /// CSS color of the grid and the labels
+    pub color: String,
+}
+
+impl Default for DebugGrid {
+    fn default() -> Self {
+        Self {
+            spacing: 10,
+            label_every: 5,
+            color: "rgba(255,0,255,0.4)".to_string(),
+        }
+    }
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Turn the debug grid overlay on or off, it is drawn on top of the frame in present()
+    pub fn set_debug_grid(&mut self, grid: Option<DebugGrid>) {
+        self.debug_grid = grid;
+    }
+
+    pub fn debug_grid(&self) -> Option<&DebugGrid> {
+        self.debug_grid.as_ref()
+    }
+
+    /// Outline the bounding box of every primitive drawn in the frame, color coded by type
+    ///  - The outlines are drawn on top of the frame in present()
+    pub fn set_debug_bounds(&mut self, enabled: bool) {
+        self.debug_bounds = enabled;
+        if !enabled {
+            self.recorded_bounds.clear();
+        }
+    }
+
+    pub(crate) fn draw_debug_bounds(&mut self) -> Result<(), DrawingErrorKind<CanvasError>> {
+        if self.recorded_bounds.is_empty() {
+            return Ok(());
+        }
+
+        self.context.save();
+        self.context.set_line_width(1.0);
+        self.context
+            .set_line_dash(&js_sys::Array::new())
+            .map_err(error_cast)?;
+        for (kind, rect) in self.recorded_bounds.drain(..) {
+            set_stroke_css(&self.context, bounds_color(kind));
+            self.context.stroke_rect(
+                rect.left.floor() + 0.5,
+                rect.top.floor() + 0.5,
+                rect.width().ceil(),
+                rect.height().ceil(),
+            );
+        }
+        self.context.restore();
+        Ok(())
+    }
+
+    pub(crate) fn draw_debug_grid(&mut self) -> Result<(), DrawingErrorKind<CanvasError>> {
+        let grid = match &self.debug_grid {
+            Some(grid) => grid.clone(),
+            None => return Ok(()),
+        };
+
+        let (logical_w, logical_h) = self.get_size();
+        let (w, h) = self.physical_size();
+        let transform = self.context.get_transform().map_err(error_cast)?;
+
+        self.context.save();
+        self.context.reset_transform().map_err(error_cast)?;
+        self.context
+            .set_line_dash(&js_sys::Array::new())
+            .map_err(error_cast)?;
+
+        set_stroke_css(&self.context, &grid.color);
+        set_fill_css(&self.context, &grid.color);
+        self.context.set_line_width(1.0);
+        self.context.set_font("10px monospace");
+        self.context.set_text_align("start");
+
+        let spacing = grid.spacing.max(2);
+        let label_every = grid.label_every.max(1);
+
+        self.context.begin_path();
+        for (i, x) in (0..w).step_by(spacing as usize).enumerate() {
+            let x = f64::from(x) + 0.5;
+            self.context.move_to(x, 0.0);
+            self.context.line_to(x, f64::from(h));
+            if i as u32 % label_every == 0 {
+                self.context
+                    .fill_text(&format!("{}", x.floor()), x + 2.0, 10.0)
+                    .map_err(error_cast)?;
+            }
+        }
+        for (i, y) in (0..h).step_by(spacing as usize).enumerate() {
+            let y = f64::from(y) + 0.5;
+            self.context.move_to(0.0, y);
+            self.context.line_to(f64::from(w), y);
+            if i > 0 && i as u32 % label_every == 0 {
+                self.context
+                    .fill_text(&format!("{}", y.floor()), 2.0, y - 2.0)
+                    .map_err(error_cast)?;
+            }
+        }
+        self.context.stroke();
+
+        let info = [
+            format!("logical {}x{}", logical_w, logical_h),
+            format!("physical {}x{}", w, h),
+            format!(
+                "transform [{:.2} {:.2} {:.2} {:.2} {:.1} {:.1}]",
+                transform.a(),
+                transform.b(),
+                transform.c(),
+                transform.d(),
+                transform.e(),
+                transform.f()
+            ),
+        ];
+        for (i, line) in info.iter().enumerate() {
+            self.context
+                .fill_text(
+                    line,
+                    4.0,
+                    f64::from(h) - 4.0 - 12.0 * (info.len() - 1 - i) as f64,
+                )
+                .map_err(error_cast)?;
+        }
+
+        self.context.restore();
+        Ok(())
+    }
+}
+
+fn bounds_color(kind: PrimitiveKind) -> &'static str {
+    match kind {
+        PrimitiveKind::Pixel => "rgba(128,128,128,0.8)",
+        PrimitiveKind::Line => "rgba(0,160,255,0.8)",
+        PrimitiveKind::Rect => "rgba(255,140,0,0.8)",
+        PrimitiveKind::Path => "rgba(0,200,120,0.8)",
+        PrimitiveKind::Circle => "rgba(160,0,255,0.8)",
+        PrimitiveKind::Polygon => "rgba(255,200,0,0.8)",
+        PrimitiveKind::Text => "rgba(255,0,0,0.8)",
+        PrimitiveKind::Bitmap => "rgba(0,0,255,0.8)",
+        PrimitiveKind::Marker => "rgba(255,0,160,0.8)",
+        PrimitiveKind::Image => "rgba(0,120,60,0.8)",
+    }
+}
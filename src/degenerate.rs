@@ -0,0 +1,59 @@
+/// What to do with a line whose two ends are the same point
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ZeroLengthLine {
+    /// Draw a square dot of the stroke width, like the bitmap backend does
+    Dot,
+    /// Draw nothing, which is what the canvas does with butt caps
+    Skip,
+}
+
+/// What to do with a rectangle or polygon which has no area
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ZeroAreaShape {
+    /// Stroke it as a line (or a dot), so that a zero height bar stays visible
+    Outline,
+    /// Draw nothing
+    Skip,
+}
+
+/// What to do with a stroke width of zero
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ZeroStrokeWidth {
+    /// Stroke with a 1 pixel wide line
+    Hairline,
+    /// Draw nothing
+    Skip,
+}
+
+/// How the backend handles degenerate primitives
+///
+/// Left alone, the canvas silently ignores `lineWidth = 0` (the previous width stays in effect)
+/// and draws nothing for zero-length lines, which makes the result depend on what was drawn
+/// before. The defaults match the output of the bitmap backend.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DegeneratePolicy {
+    pub zero_length_line: ZeroLengthLine,
+    pub zero_area_shape: ZeroAreaShape,
+    pub zero_stroke_width: ZeroStrokeWidth,
+}
+
+impl Default for DegeneratePolicy {
+    fn default() -> Self {
+        Self {
+            zero_length_line: ZeroLengthLine::Dot,
+            zero_area_shape: ZeroAreaShape::Outline,
+            zero_stroke_width: ZeroStrokeWidth::Hairline,
+        }
+    }
+}
+
+impl DegeneratePolicy {
+    /// The line width to stroke with, None if the stroke must be skipped
+    pub(crate) fn line_width(&self, stroke_width: u32) -> Option<f64> {
+        match (stroke_width, self.zero_stroke_width) {
+            (0, ZeroStrokeWidth::Hairline) => Some(1.0),
+            (0, ZeroStrokeWidth::Skip) => None,
+            (w, _) => Some(f64::from(w)),
+        }
+    }
+}
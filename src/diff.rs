@@ -0,0 +1,160 @@
+use wasm_bindgen::Clamped;
+use web_sys::{ImageBitmap, ImageData, OffscreenCanvas};
+
+use crate::canvas::{context_2d, js_error, js_error_in, CanvasError, OffscreenCanvasBackend};
+use crate::command::DrawCommand;
+use crate::geometry::Rect;
+use crate::replay::drawing_error;
+
+/// Color the changed pixels are painted with in the diff image
+pub const DIFF_HIGHLIGHT: (u8, u8, u8) = (255, 0, 255);
+
+/// The pixels which differ between two renders of a chart
+pub struct ChartDiff {
+    /// The render after the revision, faded to gray, with the changed pixels highlighted
+    pub image: ImageBitmap,
+    pub changed_pixels: usize,
+    pub total_pixels: usize,
+    /// Bounding box of the changed pixels, None if the renders are identical
+    pub changed_area: Option<Rect>,
+}
+
+impl ChartDiff {
+    /// Fraction of the pixels which changed, from 0 to 1
+    pub fn changed_ratio(&self) -> f64 {
+        if self.total_pixels == 0 {
+            0.0
+        } else {
+            self.changed_pixels as f64 / self.total_pixels as f64
+        }
+    }
+}
+
+/// Compare two RGBA buffers of the same size
+///  - A pixel changed when one of its channels differs by more than `tolerance`
+///  - Return the diff image, the number of changed pixels and their bounding box
+pub(crate) fn diff_pixels(
+    before: &[u8],
+    after: &[u8],
+    width: u32,
+    tolerance: u8,
+) -> (Vec<u8>, usize, Option<Rect>) {
+    let mut image = Vec::with_capacity(after.len());
+    let mut changed = 0;
+    let mut area: Option<Rect> = None;
+
+    for (i, (a, b)) in before
+        .chunks_exact(4)
+        .zip(after.chunks_exact(4))
+        .enumerate()
+    {
+        let differs = a
+            .iter()
+            .zip(b.iter())
+            .any(|(x, y)| (i16::from(*x) - i16::from(*y)).abs() > i16::from(tolerance));
+        if differs {
+            changed += 1;
+            let (x, y) = ((i as u32 % width) as f64, (i as u32 / width) as f64);
+            let pixel = Rect::new(x, y, x + 1.0, y + 1.0);
+            area = Some(area.map_or(pixel, |r| r.union(pixel)));
+            let (r, g, b) = DIFF_HIGHLIGHT;
+            image.extend_from_slice(&[r, g, b, 255]);
+        } else {
+            // Faded gray, so that the changes stand out and the chart stays recognizable
+            let luma = (u32::from(b[0]) * 3 + u32::from(b[1]) * 6 + u32::from(b[2])) / 10;
+            let gray = (luma as u8) / 2 + 127;
+            image.extend_from_slice(&[gray, gray, gray, b[3] / 3]);
+        }
+    }
+    (image, changed, area)
+}
+
+/// Render a chart on a new canvas of the given size and read its pixels back
+fn render_pixels<F>(width: u32, height: u32, render: F) -> Result<Vec<u8>, CanvasError>
+where
+    F: FnOnce(&mut OffscreenCanvasBackend) -> Result<(), CanvasError>,
+{
+    let canvas =
+        OffscreenCanvas::new(width, height).map_err(js_error_in("OffscreenCanvas::new"))?;
+    let mut backend = OffscreenCanvasBackend::new_read_frequently(&canvas).ok_or_else(|| {
+        CanvasError::ContextUnavailable("cannot create the diff context".to_string())
+    })?;
+    render(&mut backend)?;
+    backend.read_pixels(None)
+}
+
+/// Render a chart twice, e.g. from two revisions of its data, and highlight what changed
+///  - The renders are drawn on `width` x `height` canvases, in physical pixels
+pub fn diff_renders<F, G>(
+    width: u32,
+    height: u32,
+    tolerance: u8,
+    before: F,
+    after: G,
+) -> Result<ChartDiff, CanvasError>
+where
+    F: FnOnce(&mut OffscreenCanvasBackend) -> Result<(), CanvasError>,
+    G: FnOnce(&mut OffscreenCanvasBackend) -> Result<(), CanvasError>,
+{
+    let a = render_pixels(width, height, before)?;
+    let b = render_pixels(width, height, after)?;
+    let (image, changed_pixels, changed_area) = diff_pixels(&a, &b, width, tolerance);
+
+    let data = ImageData::new_with_u8_clamped_array_and_sh(Clamped(&image), width, height)
+        .map_err(|e| CanvasError::Encoding(js_error(e).message().to_string()))?;
+    let canvas =
+        OffscreenCanvas::new(width, height).map_err(js_error_in("OffscreenCanvas::new"))?;
+    context_2d(&canvas)?
+        .put_image_data(&data, 0.0, 0.0)
+        .map_err(js_error_in("putImageData"))?;
+
+    Ok(ChartDiff {
+        image: canvas
+            .transfer_to_image_bitmap()
+            .map_err(js_error_in("transferToImageBitmap"))?,
+        changed_pixels,
+        total_pixels: width as usize * height as usize,
+        changed_area,
+    })
+}
+
+/// Replay two command lists, e.g. recorded frames of two revisions of a chart, and highlight
+/// what changed
+pub fn diff_commands(
+    width: u32,
+    height: u32,
+    tolerance: u8,
+    before: &[DrawCommand],
+    after: &[DrawCommand],
+) -> Result<ChartDiff, CanvasError> {
+    let replay = |commands: &[DrawCommand], backend: &mut OffscreenCanvasBackend| {
+        for command in commands {
+            backend.execute(command).map_err(drawing_error)?;
+        }
+        Ok(())
+    };
+    diff_renders(
+        width,
+        height,
+        tolerance,
+        |b| replay(before, b),
+        |b| replay(after, b),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_diff_pixels() {
+        let before = [0, 0, 0, 255, 10, 10, 10, 255, 0, 0, 0, 0];
+        let after = [0, 0, 0, 255, 12, 10, 10, 255, 200, 0, 0, 255];
+        let (image, changed, area) = diff_pixels(&before, &after, 3, 4);
+        assert_eq!(changed, 1);
+        assert_eq!(area, Some(Rect::new(2.0, 0.0, 3.0, 1.0)));
+        assert_eq!(&image[8..12], &[255, 0, 255, 255]);
+        assert_eq!(diff_pixels(&before, &after, 3, 0).1, 2);
+    }
+}
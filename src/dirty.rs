@@ -0,0 +1,122 @@
+use plotters_backend::DrawingErrorKind;
+
+use crate::canvas::{CanvasError, OffscreenCanvasBackend};
+use crate::geometry::Rect;
+use crate::replay::drawing_error;
+use crate::stats::PrimitiveKind;
+
+/// Regions kept apart before they are merged into their bounding box, more regions cost more
+/// clips than they save
+pub const MAX_DIRTY_REGIONS: usize = 8;
+
+/// Add a region to a list of disjoint regions, merging it with the regions it overlaps
+pub(crate) fn add_region(regions: &mut Vec<Rect>, region: Rect) {
+    let mut region = region;
+    // A merged region can reach regions it didn't overlap before
+    while let Some(i) = regions.iter().position(|r| r.intersects(&region)) {
+        region = region.union(regions.swap_remove(i));
+    }
+    regions.push(region);
+    if regions.len() > MAX_DIRTY_REGIONS {
+        let bounds = regions.iter().skip(1).fold(regions[0], |b, r| b.union(*r));
+        regions.clear();
+        regions.push(bounds);
+    }
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Mark a region, in logical pixels, as needing a redraw, see redraw_invalidated()
+    pub fn invalidate(&mut self, region: Rect) {
+        if !region.is_empty() {
+            add_region(&mut self.invalidated, region);
+        }
+    }
+
+    /// The regions marked by invalidate() which weren't redrawn yet
+    pub fn invalidated_regions(&self) -> &[Rect] {
+        &self.invalidated
+    }
+
+    /// Make a region transparent, it counts as drawn in the frame
+    pub fn clear_rect(&mut self, region: Rect) -> Result<(), DrawingErrorKind<CanvasError>> {
+        if !self.begin_primitive(PrimitiveKind::Rect, region) {
+            return Ok(());
+        }
+        self.context
+            .clear_rect(region.left, region.top, region.width(), region.height());
+        Ok(())
+    }
+
+    /// Redraw only the invalidated regions: each one is cleared, and drawn by `draw` with a
+    /// clip so that the primitives crossing it don't paint over what is around it
+    ///  - `draw` can draw the whole chart, culling skips what is outside of the clip
+    ///  - Return the number of regions redrawn
+    pub fn redraw_invalidated<F>(&mut self, mut draw: F) -> Result<usize, CanvasError>
+    where
+        F: FnMut(&mut Self, Rect) -> Result<(), CanvasError>,
+    {
+        let regions = std::mem::take(&mut self.invalidated);
+        for &region in regions.iter() {
+            // Whole pixels, so that the antialiased edges of the previous frame are cleared
+            let (left, top) = (region.left.floor(), region.top.floor());
+            let (right, bottom) = (region.right.ceil(), region.bottom.ceil());
+            let pixels = Rect::new(left, top, right, bottom);
+            self.push_clip_rect(
+                left as i32,
+                top as i32,
+                (right - left) as i32,
+                (bottom - top) as i32,
+            )
+            .map_err(drawing_error)?;
+            let result = self
+                .clear_rect(pixels)
+                .map_err(drawing_error)
+                .and_then(|_| draw(self, pixels));
+            self.pop_clip();
+            result?;
+        }
+        Ok(regions.len())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use plotters::prelude::*;
+    use plotters_backend::DrawingBackend;
+    use wasm_bindgen_test::*;
+    use web_sys::OffscreenCanvas;
+
+    #[wasm_bindgen_test]
+    fn test_redraw_invalidated() {
+        let mut regions = vec![];
+        add_region(&mut regions, Rect::new(0.0, 0.0, 10.0, 10.0));
+        add_region(&mut regions, Rect::new(20.0, 0.0, 30.0, 10.0));
+        add_region(&mut regions, Rect::new(5.0, 5.0, 25.0, 8.0));
+        assert_eq!(regions, vec![Rect::new(0.0, 0.0, 30.0, 10.0)]);
+
+        let canvas = OffscreenCanvas::new(40, 20).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        backend.draw_rect((0, 0), (40, 20), &RED, true).unwrap();
+        backend.present().unwrap();
+
+        backend.invalidate(Rect::new(0.0, 0.0, 10.0, 10.0));
+        let redrawn = backend
+            .redraw_invalidated(|b, _| {
+                b.draw_rect((0, 0), (40, 20), &BLUE, true)
+                    .map_err(drawing_error)
+            })
+            .unwrap();
+        assert_eq!(redrawn, 1);
+        assert!(backend.invalidated_regions().is_empty());
+
+        // Only the invalidated region is repainted
+        assert_eq!(backend.pick_color(5, 5).unwrap().rgb, (0, 0, 255));
+        assert_eq!(backend.pick_color(15, 5).unwrap().rgb, (255, 0, 0));
+        let frame = backend.frame_stats().unwrap();
+        assert_eq!(
+            frame.dirty_regions(),
+            &[Rect::new(0.0, 0.0, 10.0, 10.0)][..]
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use std::borrow::Cow;
+
+use plotters_backend::DrawingErrorKind;
+use web_sys::{OffscreenCanvas, OffscreenCanvasRenderingContext2d};
+
+use crate::canvas::{js_error_in, CanvasError, OffscreenCanvasBackend};
+use crate::context::{context_with_options, ContextOptions};
+use crate::style_ext::StyleExtras;
+
+/// The visible canvas while the backend draws on a hidden one
+pub(crate) struct FrontBuffer<'a> {
+    pub(crate) canvas: Cow<'a, OffscreenCanvas>,
+    /// Only used to copy the frames, see prepare_front()
+    context: OffscreenCanvasRenderingContext2d,
+}
+
+/// Set up the visible context to copy the frames as they are, its state is saved so that it
+/// gets it back when double buffering is disabled
+fn prepare_front(context: &OffscreenCanvasRenderingContext2d) {
+    context.save();
+    let _ = context.reset_transform();
+    context.set_global_alpha(1.0);
+    let _ = context.set_global_composite_operation("copy");
+    context.set_shadow_color("rgba(0,0,0,0)");
+    context.set_filter("none");
+}
+
+/// Replace the content of `context`'s canvas with `source`, transparent pixels included
+fn copy_frame(
+    source: &OffscreenCanvas,
+    context: &OffscreenCanvasRenderingContext2d,
+) -> Result<(), CanvasError> {
+    context
+        .draw_image_with_offscreen_canvas(source, 0.0, 0.0)
+        .map_err(js_error_in("drawImage"))
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Draw on a hidden canvas and copy the finished frame onto the visible one in present(),
+    /// so that a chart taking several milliseconds to rebuild is never seen half drawn
+    ///  - The hidden canvas starts with the visible content and keeps it between frames, the
+    ///    incremental drawing (dirty regions, series appended to) keeps working
+    ///  - canvas() returns the hidden canvas while enabled, visible_canvas() the displayed one
+    ///  - Costs a second bitmap and a copy of the whole frame on every present which drew
+    ///    something
+    ///  - Fails if clips are pushed, the clips are on the context which is swapped
+    pub fn set_double_buffered(&mut self, enabled: bool) -> Result<(), CanvasError> {
+        if enabled == self.is_double_buffered() {
+            return Ok(());
+        }
+        if self.clip_depth() > 0 {
+            return Err(CanvasError::InvalidInput(
+                "cannot switch the buffering with clips pushed".to_string(),
+            ));
+        }
+        self.flush_batch();
+
+        match self.front.take() {
+            None => {
+                let (width, height) = self.physical_size();
+                let back = OffscreenCanvas::new(width, height)
+                    .map_err(js_error_in("new OffscreenCanvas"))?;
+                let options = ContextOptions::new().will_read_frequently(self.read_frequently);
+                let back_context = context_with_options(&back, &options)?;
+                copy_frame(&self.canvas, &back_context)?;
+
+                let context = std::mem::replace(&mut self.context, back_context);
+                let canvas = std::mem::replace(&mut self.canvas, Cow::Owned(back));
+                prepare_front(&context);
+                self.front = Some(FrontBuffer { canvas, context });
+                // The hidden context has the default state
+                self.applied_extras = Some(StyleExtras::default());
+            }
+            Some(front) => {
+                // The visible canvas gets what was drawn since the last present
+                copy_frame(&self.canvas, &front.context)?;
+                front.context.restore();
+                self.canvas = front.canvas;
+                self.context = front.context;
+                // The visible context may have been reset by a resize meanwhile
+                self.applied_extras = None;
+            }
+        }
+        self.style_cache.forget();
+        self.apply_pixel_ratio();
+        Ok(())
+    }
+
+    pub fn is_double_buffered(&self) -> bool {
+        self.front.is_some()
+    }
+
+    /// The canvas which is displayed, the same as canvas() unless double buffering is enabled
+    pub fn visible_canvas(&self) -> &OffscreenCanvas {
+        match &self.front {
+            Some(front) => &front.canvas,
+            None => &self.canvas,
+        }
+    }
+
+    /// Copy the finished frame onto the visible canvas, if double buffering is enabled
+    pub(crate) fn copy_to_front(&self) -> Result<(), DrawingErrorKind<CanvasError>> {
+        match &self.front {
+            Some(front) => {
+                copy_frame(&self.canvas, &front.context).map_err(DrawingErrorKind::DrawingError)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Resize the canvas, and the visible canvas behind it when double buffering is enabled
+    ///  - Resizing resets the context state and clears the canvas
+    pub(crate) fn set_canvas_size(&mut self, width: u32, height: u32) {
+        self.canvas.set_width(width);
+        self.canvas.set_height(height);
+        if let Some(front) = &self.front {
+            front.canvas.set_width(width);
+            front.canvas.set_height(height);
+            // The resize reset the context state, the saved one included
+            prepare_front(&front.context);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use plotters::prelude::*;
+    use plotters_backend::DrawingBackend;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_double_buffered() {
+        let canvas = OffscreenCanvas::new(20, 20).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        backend.set_double_buffered(true).unwrap();
+        assert!(backend.is_double_buffered());
+
+        let visible = backend.visible_canvas().clone();
+        let visible_color = || {
+            let context = crate::canvas::context_2d(&visible).unwrap();
+            context.get_image_data(5.0, 5.0, 1.0, 1.0).unwrap().data()[0]
+        };
+
+        // Nothing shows up until the frame is presented
+        backend.draw_rect((0, 0), (20, 20), &RED, true).unwrap();
+        assert_eq!(visible_color(), 0);
+        backend.present().unwrap();
+        assert_eq!(visible_color(), 255);
+
+        backend.set_double_buffered(false).unwrap();
+        assert_eq!(backend.pick_color(5, 5).unwrap().rgb, (255, 0, 0));
+    }
+}
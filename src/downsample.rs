@@ -0,0 +1,104 @@
+/// Reduce a series to `threshold` points with largest-triangle-three-buckets
+///  - The shape of the series is kept, use the pixel width of the plot area as the threshold
+///  - The points must be sorted by x, the first and the last ones are always kept
+pub fn lttb(data: &[(f64, f64)], threshold: usize) -> Vec<(f64, f64)> {
+    if threshold >= data.len() || threshold < 3 {
+        return data.to_vec();
+    }
+
+    let mut sampled = Vec::with_capacity(threshold);
+    sampled.push(data[0]);
+
+    // The first and last points are kept, the others are split into threshold - 2 buckets
+    let every = (data.len() - 2) as f64 / (threshold - 2) as f64;
+    let mut a = 0;
+    for i in 0..threshold - 2 {
+        let start = (i as f64 * every) as usize + 1;
+        let end = (((i + 1) as f64 * every) as usize + 1).min(data.len() - 1);
+
+        // Average of the next bucket, the last point for the last bucket
+        let next_end = (((i + 2) as f64 * every) as usize + 1).min(data.len());
+        let next = &data[end..next_end.max(end + 1)];
+        let n = next.len() as f64;
+        let (avg_x, avg_y) = next
+            .iter()
+            .fold((0.0, 0.0), |(x, y), p| (x + p.0 / n, y + p.1 / n));
+
+        let (ax, ay) = data[a];
+        let mut best = start;
+        let mut best_area = -1.0;
+        for (j, &(x, y)) in data[start..end].iter().enumerate() {
+            let area = ((ax - avg_x) * (y - ay) - (ax - x) * (avg_y - ay)).abs();
+            if area > best_area {
+                best_area = area;
+                best = start + j;
+            }
+        }
+        sampled.push(data[best]);
+        a = best;
+    }
+
+    sampled.push(data[data.len() - 1]);
+    sampled
+}
+
+/// Reduce a series to the minimum and the maximum of `buckets` equal slices of it
+///  - Keeps every spike, which LTTB may drop, at the cost of up to 2 points per bucket
+///  - Use the pixel width of the plot area as the number of buckets
+pub fn min_max(data: &[(f64, f64)], buckets: usize) -> Vec<(f64, f64)> {
+    if buckets == 0 || data.len() <= buckets * 2 {
+        return data.to_vec();
+    }
+
+    let mut sampled = Vec::with_capacity(buckets * 2);
+    for i in 0..buckets {
+        let start = i * data.len() / buckets;
+        let end = (i + 1) * data.len() / buckets;
+        let bucket = &data[start..end];
+        let (mut min, mut max) = (0, 0);
+        for (j, p) in bucket.iter().enumerate() {
+            if p.1 < bucket[min].1 {
+                min = j;
+            }
+            if p.1 > bucket[max].1 {
+                max = j;
+            }
+        }
+        // Keep the x order of the two points
+        let (first, second) = if min <= max { (min, max) } else { (max, min) };
+        sampled.push(bucket[first]);
+        if second != first {
+            sampled.push(bucket[second]);
+        }
+    }
+    sampled
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_lttb() {
+        let data: Vec<_> = (0..1000)
+            .map(|i| (i as f64, (i as f64 / 10.0).sin()))
+            .collect();
+        let sampled = lttb(&data, 100);
+        assert_eq!(sampled.len(), 100);
+        assert_eq!(sampled[0], data[0]);
+        assert_eq!(sampled[99], data[999]);
+        assert!(sampled.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_min_max_keeps_spikes() {
+        let mut data: Vec<_> = (0..1000).map(|i| (i as f64, 0.0)).collect();
+        data[501].1 = 100.0;
+        data[502].1 = -100.0;
+        let sampled = min_max(&data, 50);
+        assert!(sampled.len() <= 100);
+        assert!(sampled.contains(&(501.0, 100.0)));
+        assert!(sampled.contains(&(502.0, -100.0)));
+    }
+}
@@ -0,0 +1,161 @@
+#[cfg(feature = "blob-export")]
+use js_sys::{Reflect, Uint8Array};
+#[cfg(feature = "blob-export")]
+use wasm_bindgen::JsCast;
+#[cfg(feature = "blob-export")]
+use wasm_bindgen_futures::JsFuture;
+#[cfg(feature = "blob-export")]
+use web_sys::{Blob, ImageEncodeOptions};
+use web_sys::{ImageBitmap, OffscreenCanvas};
+
+use crate::canvas::{context_2d, js_error, js_error_in, CanvasError, OffscreenCanvasBackend};
+use crate::geometry::Rect;
+
+/// The encodings the canvas can be exported to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    WebP,
+}
+
+impl ImageFormat {
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "image/png",
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::WebP => "image/webp",
+        }
+    }
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Encode the canvas, e.g. to let the user download the chart rendered in a worker
+    ///  - `quality` goes from 0 to 1 and only applies to the lossy formats, the browser picks
+    ///    its default without it
+    ///  - Browsers without WebP encoding fall back to PNG, check the type of the blob
+    #[cfg(feature = "blob-export")]
+    pub async fn export_blob(
+        &mut self,
+        format: ImageFormat,
+        quality: Option<f64>,
+    ) -> Result<Blob, CanvasError> {
+        self.flush_batch();
+        // Set as plain properties, the setters of the dictionary changed across web-sys versions
+        let options = ImageEncodeOptions::new();
+        let _ = Reflect::set(&options, &"type".into(), &format.mime_type().into());
+        if let Some(quality) = quality {
+            let _ = Reflect::set(
+                &options,
+                &"quality".into(),
+                &quality.max(0.0).min(1.0).into(),
+            );
+        }
+        let promise = self
+            .canvas
+            .convert_to_blob_with_options(&options)
+            .map_err(js_error_in("convertToBlob"))?;
+        JsFuture::from(promise)
+            .await
+            .map_err(|e| CanvasError::Encoding(js_error(e).message().to_string()))?
+            .dyn_into()
+            .map_err(|_| CanvasError::Encoding("convertToBlob didn't return a blob".to_string()))
+    }
+
+    /// Encode the canvas and return the bytes of the file, see [Self::export_blob]
+    #[cfg(feature = "blob-export")]
+    pub async fn export_bytes(
+        &mut self,
+        format: ImageFormat,
+        quality: Option<f64>,
+    ) -> Result<Vec<u8>, CanvasError> {
+        let blob = self.export_blob(format, quality).await?;
+        let buffer = JsFuture::from(blob.array_buffer())
+            .await
+            .map_err(|e| CanvasError::Readback(js_error(e).message().to_string()))?;
+        Ok(Uint8Array::new(&buffer).to_vec())
+    }
+
+    /// Copy the plot area of the chart into a bitmap, without the margins, axes and labels
+    ///  - `area` is in logical pixels, without it the outermost clip pushed last is used (e.g.
+    ///    the plotting rectangle the chart clipped its series to), then the whole canvas
+    pub fn export_plot_area(&mut self, area: Option<Rect>) -> Result<ImageBitmap, CanvasError> {
+        self.flush_batch();
+        let (w, h) = self.physical_size();
+        let canvas_rect = Rect::new(0.0, 0.0, f64::from(w), f64::from(h));
+        let (sx, sy) = self.scale_factors();
+        let area = area.or(self.last_clip).map_or(canvas_rect, |a| {
+            Rect::new(a.left * sx, a.top * sy, a.right * sx, a.bottom * sy)
+        });
+        let area = area
+            .intersection(&canvas_rect)
+            .filter(|a| !a.is_empty())
+            .ok_or_else(|| {
+                CanvasError::InvalidInput("the plot area is outside of the canvas".to_string())
+            })?;
+
+        // Whole pixels only, so that the exported image isn't resampled
+        let (x, y) = (area.left.floor(), area.top.floor());
+        let (width, height) = (area.right.ceil() - x, area.bottom.ceil() - y);
+        let target = OffscreenCanvas::new(width as u32, height as u32)
+            .map_err(js_error_in("OffscreenCanvas::new"))?;
+        context_2d(&target)?
+            .draw_image_with_offscreen_canvas_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                &self.canvas,
+                x,
+                y,
+                width,
+                height,
+                0.0,
+                0.0,
+                width,
+                height,
+            )
+            .map_err(js_error)?;
+        target
+            .transfer_to_image_bitmap()
+            .map_err(js_error_in("transferToImageBitmap"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_export_plot_area() {
+        let canvas = OffscreenCanvas::new(100, 80).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+
+        let bitmap = backend.export_plot_area(None).unwrap();
+        assert_eq!((bitmap.width(), bitmap.height()), (100, 80));
+
+        backend
+            .push_clip_path(&[(10, 10), (60, 10), (60, 40), (10, 40)])
+            .unwrap();
+        backend.pop_clip();
+        let bitmap = backend.export_plot_area(None).unwrap();
+        assert_eq!((bitmap.width(), bitmap.height()), (50, 30));
+
+        let bitmap = backend
+            .export_plot_area(Some(Rect::new(90.0, 70.0, 120.0, 120.0)))
+            .unwrap();
+        assert_eq!((bitmap.width(), bitmap.height()), (10, 10));
+    }
+
+    #[cfg(feature = "blob-export")]
+    #[wasm_bindgen_test]
+    async fn test_export_bytes() {
+        let canvas = OffscreenCanvas::new(20, 10).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        let bytes = backend.export_bytes(ImageFormat::Png, None).await.unwrap();
+        assert_eq!(&bytes[1..4], b"PNG");
+
+        let blob = backend
+            .export_blob(ImageFormat::Jpeg, Some(0.8))
+            .await
+            .unwrap();
+        assert_eq!(blob.type_(), "image/jpeg");
+    }
+}
@@ -0,0 +1,45 @@
+use js_sys::{Reflect, Uint8Array};
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{FontFace, FontFaceSet};
+
+use crate::canvas::{js_error, CanvasError, OffscreenCanvasBackend};
+
+fn font_error(e: JsValue) -> CanvasError {
+    CanvasError::Font(js_error(e).message().to_string())
+}
+
+/// Register a font from raw bytes (ttf/otf/woff) under the given family name
+///  - The font is added to the font set of the current global scope (worker or window),
+///    so text styles can refer to it by `family`
+///  - Return the created FontFace, `FontFace::loaded` can be awaited if the font must be ready
+///    before the first draw
+pub fn load_font_bytes(family: &str, bytes: Vec<u8>) -> Result<FontFace, CanvasError> {
+    let buffer = Uint8Array::from(bytes.as_slice()).buffer();
+    let face = FontFace::new_with_array_buffer(family, &buffer).map_err(font_error)?;
+
+    let fonts: FontFaceSet = Reflect::get(&js_sys::global(), &"fonts".into())
+        .map_err(js_error)?
+        .dyn_into()
+        .map_err(|_| {
+            CanvasError::Unsupported("font set is not available in this scope".to_string())
+        })?;
+    fonts.add(&face).map_err(font_error)?;
+
+    Ok(face)
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Width and height of a text rendered with the given font, measured by the canvas
+    pub fn measure_text(
+        &self,
+        text: &str,
+        size: f64,
+        family: &str,
+    ) -> Result<(f64, f64), CanvasError> {
+        self.style_cache
+            .set_font(&self.context, &format!("{}px {}", size, family));
+        let metrics = self.context.measure_text(text).map_err(font_error)?;
+        let height = metrics.actual_bounding_box_ascent() + metrics.actual_bounding_box_descent();
+        Ok((metrics.width(), height))
+    }
+}
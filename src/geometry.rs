@@ -0,0 +1,102 @@
+use plotters_backend::BackendCoord;
+
+/// An axis aligned rectangle in canvas pixels
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    pub left: f64,
+    pub top: f64,
+    pub right: f64,
+    pub bottom: f64,
+}
+
+impl Rect {
+    /// Create a rectangle from two opposite corners, in any order
+    pub fn new(x0: f64, y0: f64, x1: f64, y1: f64) -> Self {
+        Self {
+            left: x0.min(x1),
+            top: y0.min(y1),
+            right: x0.max(x1),
+            bottom: y0.max(y1),
+        }
+    }
+
+    pub fn from_coords(a: BackendCoord, b: BackendCoord) -> Self {
+        Self::new(
+            f64::from(a.0),
+            f64::from(a.1),
+            f64::from(b.0),
+            f64::from(b.1),
+        )
+    }
+
+    /// The bounding box of the points, None if there are no points
+    pub fn from_points<I: IntoIterator<Item = BackendCoord>>(points: I) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Self::from_coords(first, first), |r, p| {
+            r.union(Self::from_coords(p, p))
+        }))
+    }
+
+    /// The bounding box of points in canvas space, None if there are no points
+    pub fn bounding(points: &[(f64, f64)]) -> Option<Self> {
+        let (&(x, y), rest) = points.split_first()?;
+        Some(rest.iter().fold(Self::new(x, y, x, y), |r, &(x, y)| {
+            r.union(Self::new(x, y, x, y))
+        }))
+    }
+
+    pub fn width(&self) -> f64 {
+        self.right - self.left
+    }
+
+    pub fn height(&self) -> f64 {
+        self.bottom - self.top
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width() <= 0.0 || self.height() <= 0.0
+    }
+
+    /// Grow the rectangle by `d` on every side
+    pub fn inflate(&self, d: f64) -> Self {
+        Self {
+            left: self.left - d,
+            top: self.top - d,
+            right: self.right + d,
+            bottom: self.bottom + d,
+        }
+    }
+
+    pub fn union(&self, other: Self) -> Self {
+        Self {
+            left: self.left.min(other.left),
+            top: self.top.min(other.top),
+            right: self.right.max(other.right),
+            bottom: self.bottom.max(other.bottom),
+        }
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.left <= other.right
+            && other.left <= self.right
+            && self.top <= other.bottom
+            && other.top <= self.bottom
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        if !self.intersects(other) {
+            return None;
+        }
+        Some(Self {
+            left: self.left.max(other.left),
+            top: self.top.max(other.top),
+            right: self.right.min(other.right),
+            bottom: self.bottom.min(other.bottom),
+        })
+    }
+
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.left && x <= self.right && y >= self.top && y <= self.bottom
+    }
+}
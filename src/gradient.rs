@@ -0,0 +1,163 @@
+use plotters_backend::BackendColor;
+use web_sys::{CanvasGradient, OffscreenCanvasRenderingContext2d};
+
+use crate::canvas::{js_error_in, CanvasError, OffscreenCanvasBackend};
+use crate::color::{make_canvas_color, set_fill_gradient};
+use crate::command::CommandColor;
+use crate::warn::warn_once;
+
+/// The geometry of a gradient, in logical pixels of the canvas
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GradientShape {
+    /// The colors vary along the line from `start` to `end`
+    Linear { start: (f64, f64), end: (f64, f64) },
+    /// The colors vary between the inner and the outer circle
+    Radial {
+        inner: (f64, f64),
+        inner_radius: f64,
+        outer: (f64, f64),
+        outer_radius: f64,
+    },
+}
+
+/// A gradient fill, with its color stops
+///
+/// ```ignore
+/// let fill = GradientStyle::linear((0.0, 0.0), (0.0, 300.0))
+///     .stop(0.0, BLUE.mix(0.8).to_backend_color().into())
+///     .stop(1.0, BLUE.mix(0.0).to_backend_color().into());
+/// chart.draw_series(AreaSeries::new(data, 0.0, CanvasShapeStyle::new(&BLUE).gradient(fill)))?;
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct GradientStyle {
+    pub shape: GradientShape,
+    /// Offsets from 0 to 1 along the gradient, and their colors
+    pub stops: Vec<(f64, CommandColor)>,
+}
+
+impl GradientStyle {
+    pub fn linear(start: (f64, f64), end: (f64, f64)) -> Self {
+        Self {
+            shape: GradientShape::Linear { start, end },
+            stops: vec![],
+        }
+    }
+
+    /// A radial gradient spreading from `center` to the circle of the given radius
+    pub fn radial(center: (f64, f64), radius: f64) -> Self {
+        Self::radial_between(center, 0.0, center, radius)
+    }
+
+    pub fn radial_between(
+        inner: (f64, f64),
+        inner_radius: f64,
+        outer: (f64, f64),
+        outer_radius: f64,
+    ) -> Self {
+        Self {
+            shape: GradientShape::Radial {
+                inner,
+                inner_radius,
+                outer,
+                outer_radius,
+            },
+            stops: vec![],
+        }
+    }
+
+    /// Add a color stop, `offset` is clamped to [0, 1]
+    pub fn stop(mut self, offset: f64, color: CommandColor) -> Self {
+        self.stops.push((offset.max(0.0).min(1.0), color));
+        self
+    }
+
+    /// Build the CanvasGradient on a context
+    pub(crate) fn create(
+        &self,
+        context: &OffscreenCanvasRenderingContext2d,
+        map_color: impl Fn(BackendColor) -> BackendColor,
+    ) -> Result<CanvasGradient, CanvasError> {
+        let gradient = match self.shape {
+            GradientShape::Linear { start, end } => {
+                context.create_linear_gradient(start.0, start.1, end.0, end.1)
+            }
+            GradientShape::Radial {
+                inner,
+                inner_radius,
+                outer,
+                outer_radius,
+            } => context
+                .create_radial_gradient(
+                    inner.0,
+                    inner.1,
+                    inner_radius.max(0.0),
+                    outer.0,
+                    outer.1,
+                    outer_radius.max(0.0),
+                )
+                .map_err(js_error_in("createRadialGradient"))?,
+        };
+        for (offset, color) in self.stops.iter() {
+            let color = map_color(color.to_backend_color());
+            gradient
+                .add_color_stop(*offset as f32, &make_canvas_color(color))
+                .map_err(js_error_in("addColorStop"))?;
+        }
+        Ok(gradient)
+    }
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Replace the fill color with the gradient of the applied style, if it has one
+    ///  - A gradient the canvas rejects (e.g. a negative radius) falls back to the fill color,
+    ///    with a warning
+    pub(crate) fn apply_fill_gradient(&self) {
+        let gradient = match self
+            .applied_extras
+            .as_ref()
+            .and_then(|e| e.gradient.as_ref())
+        {
+            Some(gradient) => gradient,
+            None => return,
+        };
+        let compositing = self.compositing;
+        match gradient.create(&self.context, |c| compositing.apply(c)) {
+            Ok(gradient) => {
+                set_fill_gradient(&self.context, &gradient);
+                self.style_cache.forget_fill();
+            }
+            Err(_) => {
+                warn_once("a gradient was rejected by the canvas, the fill color is used instead");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::style_ext::CanvasShapeStyle;
+    use plotters::prelude::*;
+    use plotters_backend::DrawingBackend;
+    use wasm_bindgen_test::*;
+    use web_sys::OffscreenCanvas;
+
+    #[wasm_bindgen_test]
+    fn test_gradient_fill() {
+        let canvas = OffscreenCanvas::new(100, 10).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        let gradient = GradientStyle::linear((0.0, 0.0), (100.0, 0.0))
+            .stop(0.0, RED.to_backend_color().into())
+            .stop(1.0, BLUE.to_backend_color().into())
+            .stop(2.0, BLUE.to_backend_color().into());
+        assert_eq!(gradient.stops[2].0, 1.0);
+
+        let style = CanvasShapeStyle::new(RED.filled()).gradient(gradient);
+        backend.draw_rect((0, 0), (100, 10), &style, true).unwrap();
+
+        let left = backend.pick_color(1, 5).unwrap().rgb;
+        let right = backend.pick_color(98, 5).unwrap().rgb;
+        assert!(left.0 > 200 && left.2 < 50);
+        assert!(right.2 > 200 && right.0 < 50);
+    }
+}
@@ -0,0 +1,61 @@
+use plotters_backend::{BackendCoord, DrawingErrorKind};
+use web_sys::ImageBitmap;
+
+use crate::canvas::{error_cast, CanvasError, OffscreenCanvasBackend};
+use crate::geometry::Rect;
+use crate::stats::PrimitiveKind;
+
+/// How an image is placed at each data point
+#[derive(Clone, Debug)]
+pub struct ImageMarkerOptions {
+    /// Scale factor applied to the natural size of the image
+    pub scale: f64,
+    /// Center the image on the point, otherwise the point is the upper left corner
+    pub centered: bool,
+}
+
+impl Default for ImageMarkerOptions {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            centered: true,
+        }
+    }
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Stamp the image (icon, avatar, ...) at each of the given points
+    pub fn draw_image_markers(
+        &mut self,
+        image: &ImageBitmap,
+        points: &[BackendCoord],
+        options: &ImageMarkerOptions,
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        let w = f64::from(image.width()) * options.scale;
+        let h = f64::from(image.height()) * options.scale;
+        if w <= 0.0 || h <= 0.0 {
+            return Ok(());
+        }
+
+        let (dx, dy) = if options.centered {
+            (w / 2.0, h / 2.0)
+        } else {
+            (0.0, 0.0)
+        };
+
+        for point in points {
+            let (x, y) = match self.coord(*point)? {
+                Some((x, y)) => (x - dx, y - dy),
+                None => continue,
+            };
+            if !self.begin_primitive(PrimitiveKind::Image, Rect::new(x, y, x + w, y + h)) {
+                continue;
+            }
+            self.context
+                .draw_image_with_image_bitmap_and_dw_and_dh(image, x, y, w, h)
+                .map_err(error_cast)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,154 @@
+use plotters::coord::Shift;
+use plotters::drawing::{DrawingArea, DrawingAreaErrorKind};
+use plotters::element::{Drawable, PointCollection};
+use plotters_backend::{BackendCoord, DrawingBackend, DrawingErrorKind};
+
+use crate::canvas::OffscreenCanvasBackend;
+use crate::coord::pixel_to_coord;
+use crate::geometry::Rect;
+use crate::shared::SharedBackend;
+
+/// Backends which can restrict the drawing to a rectangle
+pub trait ClipBackend: DrawingBackend {
+    /// Only draw inside the rectangle, until the matching pop_clip()
+    fn push_clip_rect(
+        &mut self,
+        upper_left: BackendCoord,
+        bottom_right: BackendCoord,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>>;
+
+    /// Remove the last clip, return false if there was none
+    fn pop_clip(&mut self) -> bool;
+}
+
+impl<'a> ClipBackend for OffscreenCanvasBackend<'a> {
+    fn push_clip_rect(
+        &mut self,
+        upper_left: BackendCoord,
+        bottom_right: BackendCoord,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let (a, b) = (upper_left, bottom_right);
+        self.push_clip_path(&[a, (b.0, a.1), b, (a.0, b.1)])
+    }
+
+    fn pop_clip(&mut self) -> bool {
+        OffscreenCanvasBackend::pop_clip(self)
+    }
+}
+
+impl ClipBackend for SharedBackend {
+    fn push_clip_rect(
+        &mut self,
+        upper_left: BackendCoord,
+        bottom_right: BackendCoord,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.borrow_mut().push_clip_rect(upper_left, bottom_right)
+    }
+
+    fn pop_clip(&mut self) -> bool {
+        self.borrow_mut().pop_clip()
+    }
+}
+
+/// Element pushing a clip rectangle, given by its corners in the coordinates of the area
+struct PushClip([BackendCoord; 2]);
+
+/// Element popping the clip pushed by [PushClip]
+struct PopClip;
+
+impl<'a> PointCollection<'a, BackendCoord> for &'a PushClip {
+    type Point = &'a BackendCoord;
+    type IntoIter = &'a [BackendCoord];
+    fn point_iter(self) -> Self::IntoIter {
+        &self.0
+    }
+}
+
+impl<DB: ClipBackend> Drawable<DB> for PushClip {
+    fn draw<I: Iterator<Item = BackendCoord>>(
+        &self,
+        mut points: I,
+        backend: &mut DB,
+        _: (u32, u32),
+    ) -> Result<(), DrawingErrorKind<DB::ErrorType>> {
+        if let (Some(a), Some(b)) = (points.next(), points.next()) {
+            backend.push_clip_rect(a, b)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> PointCollection<'a, BackendCoord> for &'a PopClip {
+    type Point = &'a BackendCoord;
+    type IntoIter = &'a [BackendCoord];
+    fn point_iter(self) -> Self::IntoIter {
+        &[]
+    }
+}
+
+impl<DB: ClipBackend> Drawable<DB> for PopClip {
+    fn draw<I: Iterator<Item = BackendCoord>>(
+        &self,
+        _: I,
+        backend: &mut DB,
+        _: (u32, u32),
+    ) -> Result<(), DrawingErrorKind<DB::ErrorType>> {
+        backend.pop_clip();
+        Ok(())
+    }
+}
+
+/// Render a mini chart (e.g. a zoom overview strip) inside `rect` of the parent area
+///  - `draw` gets an area translated to the upper left corner of `rect`, and the backend is
+///    clipped to `rect` meanwhile, so the inset can't bleed over the main chart. Primitives
+///    outside of it are culled and don't grow the dirty rectangle of the frame
+///  - To put the inset on its own layer, pass the area of the layer backend as `parent`
+pub fn draw_inset<DB: ClipBackend, R, F: FnOnce(&DrawingArea<DB, Shift>) -> R>(
+    parent: &DrawingArea<DB, Shift>,
+    rect: Rect,
+    draw: F,
+) -> Result<R, DrawingAreaErrorKind<DB::ErrorType>> {
+    let upper_left = pixel_to_coord(rect.left, rect.top);
+    let bottom_right = pixel_to_coord(rect.right, rect.bottom);
+    let size = (
+        bottom_right.0.saturating_sub(upper_left.0).max(0),
+        bottom_right.1.saturating_sub(upper_left.1).max(0),
+    );
+
+    parent.draw(&PushClip([upper_left, bottom_right]))?;
+    let area = parent.clone().shrink(upper_left, size);
+    let result = draw(&area);
+    parent.draw(&PopClip)?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use plotters::prelude::*;
+    use wasm_bindgen_test::*;
+    use web_sys::OffscreenCanvas;
+
+    #[wasm_bindgen_test]
+    fn test_inset_is_clipped_and_translated() {
+        let shared = OffscreenCanvasBackend::new_shared(OffscreenCanvas::new(200, 100).unwrap())
+            .expect("cannot find canvas");
+        let root = shared.clone().into_drawing_area();
+
+        let inset = Rect::new(120.0, 10.0, 190.0, 40.0);
+        draw_inset(&root, inset, |area| {
+            assert_eq!(area.dim_in_pixel(), (70, 30));
+            area.draw_pixel((5, 5), &BLACK).unwrap();
+            // Inside the parent, but outside of the inset
+            area.draw_pixel((-100, 50), &BLACK).unwrap();
+        })
+        .unwrap();
+
+        let mut backend = shared.borrow_mut();
+        assert_eq!(backend.clip_depth(), 0);
+        let stats = backend.frame_stats().unwrap();
+        assert_eq!(stats.total(), 1);
+        assert_eq!(stats.culled(), 1);
+        assert!(backend.present().is_ok());
+    }
+}
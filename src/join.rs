@@ -0,0 +1,151 @@
+use crate::canvas::OffscreenCanvasBackend;
+use crate::style_ext::{LineCap, LineJoin};
+
+/// The sharpest angle between consecutive segments of the polyline, in degrees
+///  - 180 means a straight continuation, values close to 0 are spikes folding back
+pub(crate) fn sharpest_angle(points: &[(f64, f64)], closed: bool) -> Option<f64> {
+    let n = points.len();
+    if n < 3 {
+        return None;
+    }
+
+    let vertices = if closed { 0..n } else { 1..n - 1 };
+    vertices
+        .filter_map(|i| {
+            let prev = points[(i + n - 1) % n];
+            let cur = points[i];
+            let next = points[(i + 1) % n];
+            let (ax, ay) = (prev.0 - cur.0, prev.1 - cur.1);
+            let (bx, by) = (next.0 - cur.0, next.1 - cur.1);
+            let len = (ax * ax + ay * ay).sqrt() * (bx * bx + by * by).sqrt();
+            if len == 0.0 {
+                return None;
+            }
+            let cos = ((ax * bx + ay * by) / len).max(-1.0).min(1.0);
+            Some(cos.acos().to_degrees())
+        })
+        .fold(None, |min: Option<f64>, a| {
+            Some(min.map_or(a, |m| m.min(a)))
+        })
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Set the canvas miterLimit, the ratio between the miter length and the line width beyond which
+    /// miter joins are beveled (10 by default)
+    pub fn set_miter_limit(&mut self, limit: f64) {
+        self.miter_limit = limit;
+    }
+
+    /// Use round joins for the polylines which have a corner sharper than `degrees`
+    ///  - Noisy line series have many acute corners, with miter joins they show spiky artifacts
+    ///  - None keeps miter joins everywhere, the default threshold is 30 degrees
+    pub fn set_acute_join_threshold(&mut self, degrees: Option<f64>) {
+        self.acute_join_threshold = degrees;
+    }
+
+    /// Set the cap of the strokes whose style doesn't set one (butt by default)
+    ///  - Round caps smooth the ends of thick line series
+    pub fn set_line_cap(&mut self, cap: LineCap) {
+        self.line_cap = cap;
+    }
+
+    /// Set the join of the strokes whose style doesn't set one
+    ///  - None picks miter or round joins from the sharpness of the corners, see
+    ///    [OffscreenCanvasBackend::set_acute_join_threshold]
+    pub fn set_line_join(&mut self, join: Option<LineJoin>) {
+        self.line_join = join;
+    }
+
+    pub fn line_cap(&self) -> LineCap {
+        self.line_cap
+    }
+
+    pub fn line_join(&self) -> Option<LineJoin> {
+        self.line_join
+    }
+
+    /// Round the joins of a polyline with a corner sharper than the acute join threshold, on
+    /// top of the join set with its style, unless the style or the backend set one
+    pub(crate) fn apply_line_join(&self, points: &[(f64, f64)], closed: bool) {
+        if self.style_line_join().is_some() {
+            return;
+        }
+        let acute = match self.acute_join_threshold {
+            Some(threshold) => sharpest_angle(points, closed).map_or(false, |a| a < threshold),
+            None => false,
+        };
+        let join = if acute {
+            LineJoin::Round
+        } else {
+            LineJoin::Miter
+        };
+        self.style_cache.set_line_join(&self.context, join);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_sharpest_angle() {
+        let zigzag = [(0.0, 0.0), (10.0, 1.0), (0.0, 2.0)];
+        assert!(sharpest_angle(&zigzag, false).unwrap() < 15.0);
+
+        let square = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+        assert!((sharpest_angle(&square, true).unwrap() - 90.0).abs() < 1e-9);
+
+        assert_eq!(sharpest_angle(&square[..2], false), None);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_backend_line_cap_and_join() {
+        use plotters::prelude::*;
+        use web_sys::OffscreenCanvas;
+
+        let canvas = OffscreenCanvas::new(10, 10).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        backend.set_line_cap(LineCap::Round);
+        backend.set_line_join(Some(LineJoin::Bevel));
+        backend.apply_style_extras(&BLUE.stroke_width(4), true);
+        assert_eq!(backend.context.line_cap(), "round");
+        assert_eq!(backend.style_line_join(), Some(LineJoin::Bevel));
+
+        // The style's own cap wins
+        let style = crate::style_ext::CanvasShapeStyle::new(BLUE.stroke_width(4))
+            .line_cap(LineCap::Square);
+        backend.apply_style_extras(&style, true);
+        assert_eq!(backend.context.line_cap(), "square");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_line_join_on_every_stroke() {
+        use plotters::prelude::*;
+        use plotters_backend::DrawingBackend;
+        use web_sys::OffscreenCanvas;
+
+        let canvas = OffscreenCanvas::new(40, 40).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        backend.set_miter_limit(4.0);
+
+        // The acute corner rounds the joins of this path only
+        let spike = BLACK.stroke_width(4);
+        backend
+            .draw_path(vec![(0, 0), (30, 5), (0, 10)], &spike)
+            .unwrap();
+        backend.present().unwrap();
+        assert_eq!(backend.context.line_join(), "round");
+        backend
+            .draw_rect((5, 5), (30, 30), &BLUE.stroke_width(4), false)
+            .unwrap();
+        assert_eq!(backend.context.line_join(), "miter");
+        assert_eq!(backend.context.miter_limit(), 4.0);
+
+        backend.set_line_join(Some(LineJoin::Bevel));
+        backend
+            .draw_circle((20, 20), 10, &BLUE.stroke_width(4), false)
+            .unwrap();
+        assert_eq!(backend.context.line_join(), "bevel");
+    }
+}
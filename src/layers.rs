@@ -0,0 +1,209 @@
+use web_sys::{OffscreenCanvas, OffscreenCanvasRenderingContext2d};
+
+use crate::canvas::{context_2d, js_error, js_error_in, CanvasError, OffscreenCanvasBackend};
+
+/// How a layer is blended with the layers below it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlendMode {
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    /// Additive blending
+    Lighter,
+    Difference,
+}
+
+impl Default for BlendMode {
+    fn default() -> Self {
+        BlendMode::Normal
+    }
+}
+
+impl BlendMode {
+    /// The value of globalCompositeOperation for the mode
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BlendMode::Normal => "source-over",
+            BlendMode::Multiply => "multiply",
+            BlendMode::Screen => "screen",
+            BlendMode::Overlay => "overlay",
+            BlendMode::Darken => "darken",
+            BlendMode::Lighten => "lighten",
+            BlendMode::Lighter => "lighter",
+            BlendMode::Difference => "difference",
+        }
+    }
+}
+
+struct Layer {
+    name: String,
+    canvas: OffscreenCanvas,
+    alpha: f64,
+    visible: bool,
+    z_index: i32,
+    blend: BlendMode,
+}
+
+/// A stack of offscreen layers composited onto a target canvas
+///
+/// Every layer is a canvas the size of the target which is drawn with its own backend, so an
+/// overlay (annotations, forecast bands, ...) can be faded or hidden at composite time without
+/// re-rendering it or the layers below.
+pub struct LayeredCanvas<'a> {
+    target: &'a OffscreenCanvas,
+    context: OffscreenCanvasRenderingContext2d,
+    layers: Vec<Layer>,
+}
+
+impl<'a> LayeredCanvas<'a> {
+    pub fn new(target: &'a OffscreenCanvas) -> Result<Self, CanvasError> {
+        Ok(Self {
+            target,
+            context: context_2d(target)?,
+            layers: vec![],
+        })
+    }
+
+    /// Add a layer on top of the existing ones with the same z-index (0)
+    pub fn add_layer(&mut self, name: &str) -> Result<(), CanvasError> {
+        self.add_layer_with_z_index(name, 0)
+    }
+
+    /// Add a layer, layers are composited by ascending z-index, then in the order they were added
+    pub fn add_layer_with_z_index(&mut self, name: &str, z_index: i32) -> Result<(), CanvasError> {
+        if self.find(name).is_some() {
+            return Err(CanvasError::InvalidInput(format!(
+                "layer {} already exists",
+                name
+            )));
+        }
+        let canvas = OffscreenCanvas::new(self.target.width(), self.target.height())
+            .map_err(js_error_in("OffscreenCanvas::new"))?;
+        self.layers.push(Layer {
+            name: name.to_string(),
+            canvas,
+            alpha: 1.0,
+            visible: true,
+            z_index,
+            blend: BlendMode::Normal,
+        });
+        Ok(())
+    }
+
+    pub fn remove_layer(&mut self, name: &str) {
+        self.layers.retain(|l| l.name != name);
+    }
+
+    fn find(&self, name: &str) -> Option<&Layer> {
+        self.layers.iter().find(|l| l.name == name)
+    }
+
+    fn find_mut(&mut self, name: &str) -> Option<&mut Layer> {
+        self.layers.iter_mut().find(|l| l.name == name)
+    }
+
+    /// The canvas of a layer
+    pub fn layer_canvas(&self, name: &str) -> Option<&OffscreenCanvas> {
+        self.find(name).map(|l| &l.canvas)
+    }
+
+    /// Create a drawing backend on a layer
+    pub fn layer_backend(&self, name: &str) -> Option<OffscreenCanvasBackend<'_>> {
+        OffscreenCanvasBackend::new(&self.find(name)?.canvas)
+    }
+
+    /// Erase the content of a layer
+    pub fn clear_layer(&self, name: &str) -> Result<(), CanvasError> {
+        if let Some(layer) = self.find(name) {
+            let context = context_2d(&layer.canvas)?;
+            context.clear_rect(
+                0.0,
+                0.0,
+                f64::from(layer.canvas.width()),
+                f64::from(layer.canvas.height()),
+            );
+        }
+        Ok(())
+    }
+
+    /// Set the opacity the layer is composited with, between 0 and 1
+    pub fn set_layer_alpha(&mut self, name: &str, alpha: f64) {
+        if let Some(layer) = self.find_mut(name) {
+            layer.alpha = alpha.max(0.0).min(1.0);
+        }
+    }
+
+    /// Show or hide a layer, hidden layers keep their content
+    pub fn set_layer_visible(&mut self, name: &str, visible: bool) {
+        if let Some(layer) = self.find_mut(name) {
+            layer.visible = visible;
+        }
+    }
+
+    pub fn set_layer_z_index(&mut self, name: &str, z_index: i32) {
+        if let Some(layer) = self.find_mut(name) {
+            layer.z_index = z_index;
+        }
+    }
+
+    pub fn set_layer_blend_mode(&mut self, name: &str, blend: BlendMode) {
+        if let Some(layer) = self.find_mut(name) {
+            layer.blend = blend;
+        }
+    }
+
+    pub fn is_layer_visible(&self, name: &str) -> bool {
+        self.find(name).map_or(false, |l| l.visible)
+    }
+
+    /// Resize the target and every layer, the layers have to be redrawn
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.target.set_width(width);
+        self.target.set_height(height);
+        for layer in self.layers.iter() {
+            layer.canvas.set_width(width);
+            layer.canvas.set_height(height);
+        }
+    }
+
+    /// Draw the visible layers onto the target canvas, by ascending z-index
+    pub fn composite(&self) -> Result<(), CanvasError> {
+        let (w, h) = (
+            f64::from(self.target.width()),
+            f64::from(self.target.height()),
+        );
+        self.context.clear_rect(0.0, 0.0, w, h);
+
+        // The sort is stable, layers with the same z-index keep the order they were added in
+        let mut order: Vec<&Layer> = self.layers.iter().collect();
+        order.sort_by_key(|l| l.z_index);
+
+        for layer in order {
+            if !layer.visible || layer.alpha == 0.0 {
+                continue;
+            }
+            self.context.set_global_alpha(layer.alpha);
+            self.context
+                .set_global_composite_operation(layer.blend.as_str())
+                .map_err(js_error)?;
+            self.context
+                .draw_image_with_offscreen_canvas(&layer.canvas, 0.0, 0.0)
+                .map_err(js_error)?;
+        }
+        self.context.set_global_alpha(1.0);
+        self.context
+            .set_global_composite_operation(BlendMode::Normal.as_str())
+            .map_err(js_error)?;
+
+        Ok(())
+    }
+
+    /// Present the frame: composite the layers onto the target canvas
+    ///  - Call it once all the layers which changed have been drawn
+    pub fn present(&self) -> Result<(), CanvasError> {
+        self.composite()
+    }
+}
@@ -0,0 +1,241 @@
+use plotters_backend::DrawingErrorKind;
+
+use crate::canvas::{error_cast, CanvasError, OffscreenCanvasBackend};
+use crate::command::CommandColor;
+use crate::geometry::Rect;
+use crate::stats::PrimitiveKind;
+
+/// How the series of a legend entry is pictured
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LegendSwatch {
+    /// A filled square
+    Box,
+    /// A horizontal line through the middle of the swatch
+    Line,
+}
+
+#[derive(Clone, Debug)]
+pub struct LegendEntry {
+    pub label: String,
+    pub color: CommandColor,
+    pub swatch: LegendSwatch,
+}
+
+/// The corner of the area the legend is placed in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LegendPosition {
+    UpperLeft,
+    UpperRight,
+    LowerLeft,
+    LowerRight,
+}
+
+/// A legend laid out from the text metrics of the canvas, so that the labels fit whatever
+/// font is actually used
+#[derive(Clone, Debug)]
+pub struct Legend {
+    pub entries: Vec<LegendEntry>,
+    pub font_size: f64,
+    pub font_family: String,
+    pub text_color: CommandColor,
+    pub background: Option<CommandColor>,
+    pub position: LegendPosition,
+    /// Entries are laid out row by row in this number of columns
+    pub columns: usize,
+    /// Side of the swatches, in pixels
+    pub swatch_size: f64,
+    /// Space around the entries and between the legend and the corner
+    pub padding: f64,
+    /// Space between a swatch and its label, and between the entries
+    pub spacing: f64,
+}
+
+/// Where the parts of a legend go
+#[derive(Clone, Debug, PartialEq)]
+pub struct LegendLayout {
+    pub bounds: Rect,
+    /// Swatch and left middle point of the label, for every entry
+    pub items: Vec<(Rect, (f64, f64))>,
+}
+
+impl Default for Legend {
+    fn default() -> Self {
+        Self {
+            entries: vec![],
+            font_size: 12.0,
+            font_family: "sans-serif".to_string(),
+            text_color: CommandColor {
+                rgb: (0, 0, 0),
+                alpha: 1.0,
+            },
+            background: Some(CommandColor {
+                rgb: (255, 255, 255),
+                alpha: 0.8,
+            }),
+            position: LegendPosition::UpperRight,
+            columns: 1,
+            swatch_size: 10.0,
+            padding: 6.0,
+            spacing: 6.0,
+        }
+    }
+}
+
+impl Legend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_entry(&mut self, label: &str, color: CommandColor, swatch: LegendSwatch) {
+        self.entries.push(LegendEntry {
+            label: label.to_string(),
+            color,
+            swatch,
+        });
+    }
+
+    /// Lay out the legend in a corner of `area`, from the widths of the labels
+    pub fn layout(&self, label_widths: &[f64], area: Rect) -> LegendLayout {
+        let columns = self.columns.max(1);
+        let rows = (self.entries.len() + columns - 1) / columns;
+        let row_height = self.font_size.max(self.swatch_size);
+
+        let mut column_widths = vec![0.0_f64; columns];
+        for (i, width) in label_widths.iter().enumerate() {
+            let w = self.swatch_size + self.spacing + width;
+            column_widths[i % columns] = column_widths[i % columns].max(w);
+        }
+
+        let inner_width = column_widths.iter().sum::<f64>()
+            + self.spacing * (columns.min(self.entries.len()).max(1) - 1) as f64;
+        let inner_height = rows as f64 * row_height + self.spacing * rows.saturating_sub(1) as f64;
+        let (width, height) = (
+            inner_width + 2.0 * self.padding,
+            inner_height + 2.0 * self.padding,
+        );
+
+        let left = match self.position {
+            LegendPosition::UpperLeft | LegendPosition::LowerLeft => area.left + self.padding,
+            _ => area.right - self.padding - width,
+        };
+        let top = match self.position {
+            LegendPosition::UpperLeft | LegendPosition::UpperRight => area.top + self.padding,
+            _ => area.bottom - self.padding - height,
+        };
+
+        let items = (0..self.entries.len())
+            .map(|i| {
+                let (row, col) = (i / columns, i % columns);
+                let x = left
+                    + self.padding
+                    + column_widths[..col].iter().sum::<f64>()
+                    + self.spacing * col as f64;
+                let y = top + self.padding + row as f64 * (row_height + self.spacing);
+                let middle = y + row_height / 2.0;
+                let half = self.swatch_size / 2.0;
+                (
+                    Rect::new(x, middle - half, x + self.swatch_size, middle + half),
+                    (x + self.swatch_size + self.spacing, middle),
+                )
+            })
+            .collect();
+
+        LegendLayout {
+            bounds: Rect::new(left, top, left + width, top + height),
+            items,
+        }
+    }
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Measure the labels and draw the legend in its corner of `area`
+    ///  - Draw it on the backend of a separate layer to keep it out of the chart redraws
+    pub fn draw_legend(
+        &mut self,
+        legend: &Legend,
+        area: Rect,
+    ) -> Result<LegendLayout, DrawingErrorKind<CanvasError>> {
+        let widths = legend
+            .entries
+            .iter()
+            .map(|e| {
+                self.measure_text(&e.label, legend.font_size, &legend.font_family)
+                    .map(|(w, _)| w)
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(DrawingErrorKind::DrawingError)?;
+        let layout = legend.layout(&widths, area);
+        if legend.entries.is_empty() || !self.begin_primitive(PrimitiveKind::Rect, layout.bounds) {
+            return Ok(layout);
+        }
+
+        if let Some(background) = legend.background {
+            let b = layout.bounds;
+            self.fill_color(background.to_backend_color());
+            self.context.fill_rect(b.left, b.top, b.width(), b.height());
+        }
+
+        self.context.save();
+        self.context.set_text_align("start");
+        self.context.set_text_baseline("middle");
+        self.context
+            .set_font(&format!("{}px {}", legend.font_size, legend.font_family));
+        let result = legend.entries.iter().zip(layout.items.iter()).try_for_each(
+            |(entry, (swatch, (x, y)))| {
+                self.fill_color(entry.color.to_backend_color());
+                match entry.swatch {
+                    LegendSwatch::Box => self.context.fill_rect(
+                        swatch.left,
+                        swatch.top,
+                        swatch.width(),
+                        swatch.height(),
+                    ),
+                    LegendSwatch::Line => {
+                        let middle = (swatch.top + swatch.bottom) / 2.0;
+                        self.context
+                            .fill_rect(swatch.left, middle - 1.0, swatch.width(), 2.0)
+                    }
+                }
+                self.fill_color(legend.text_color.to_backend_color());
+                self.context
+                    .fill_text(&entry.label, *x, *y)
+                    .map_err(error_cast)
+            },
+        );
+        // Also when a label failed, so that the cache follows the restored context
+        self.context.restore();
+        self.style_cache.forget();
+
+        result.map(|_| layout)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_legend_layout() {
+        let mut legend = Legend::new();
+        let color = CommandColor {
+            rgb: (255, 0, 0),
+            alpha: 1.0,
+        };
+        legend.add_entry("a", color, LegendSwatch::Box);
+        legend.add_entry("b", color, LegendSwatch::Line);
+        legend.add_entry("c", color, LegendSwatch::Box);
+        legend.columns = 2;
+        legend.position = LegendPosition::UpperLeft;
+
+        let layout = legend.layout(&[20.0, 30.0, 40.0], Rect::new(0.0, 0.0, 200.0, 200.0));
+        assert_eq!(layout.items.len(), 3);
+        // Column widths are 10 + 6 + 40 and 10 + 6 + 30, there are two rows of 12 pixels
+        assert_eq!(
+            layout.bounds,
+            Rect::new(6.0, 6.0, 6.0 + 56.0 + 6.0 + 46.0 + 12.0, 6.0 + 30.0 + 12.0)
+        );
+        assert_eq!((layout.items[1].0).left, 6.0 + 6.0 + 56.0 + 6.0);
+        assert_eq!((layout.items[2].1).1, 6.0 + 6.0 + 18.0 + 6.0);
+    }
+}
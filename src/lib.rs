@@ -1,3 +1,153 @@
+mod annotation;
+mod append;
+mod atlas;
+mod bands;
 mod canvas;
+mod chunked;
+mod clip;
+mod color;
+mod command;
+mod context;
+mod coord;
+mod dash;
+mod debug;
+mod degenerate;
+mod diff;
+mod dirty;
+mod double_buffer;
+#[cfg(feature = "decimation")]
+mod downsample;
+mod export;
+mod font;
+mod geometry;
+mod gradient;
+mod image_marker;
+mod inset;
+mod join;
+mod layers;
+mod legend;
+mod marker;
+mod mask;
+mod middleware;
+mod observer;
+mod outline;
+mod pattern;
+mod pixel_batch;
+mod placement;
+mod pool;
+mod postprocess;
+mod prerender;
+mod progressive;
+mod queue;
+mod raster;
+mod readback;
+#[cfg(feature = "remote-backend")]
+mod remote;
+mod recorder;
+mod render_cache;
+mod render_loop;
+mod replay;
+mod report;
+mod reset;
+mod scheduler;
+mod scratch;
+mod series;
+mod shared;
+mod sizing;
+mod sliding;
+mod snap;
+mod sprite;
+mod stats;
+mod stream;
+mod stroke_align;
+mod style_cache;
+mod style_ext;
+#[cfg(feature = "tessellation")]
+mod tessellate;
+mod text_batch;
+mod text_effect;
+mod text_rotation;
+mod time;
+mod tween;
+mod viewport;
+mod warn;
+mod watchdog;
+#[cfg(feature = "worker-pool")]
+mod worker_pool;
 
-pub use canvas::OffscreenCanvasBackend;
\ No newline at end of file
+pub use annotation::{Annotation, AnnotationId, AnnotationLayer, AnnotationPin};
+pub use append::AppendOnlySeries;
+pub use atlas::{AtlasRegion, SparklineAtlas, SparklineId};
+pub use bands::{band_rect, Band};
+pub use canvas::{CanvasError, OffscreenCanvasBackend};
+pub use chunked::{CancellationToken, ChunkStatus, ChunkedRender};
+pub use color::{pre_blend, CompositingMode};
+pub use command::{CommandColor, CommandStyle, DrawCommand, TextCommandStyle};
+pub use context::ContextOptions;
+pub use coord::{checked_component, pixel_to_coord, CoordPolicy, SAFE_COORD_LIMIT};
+pub use dash::{dashed, dotted, LineStyleExt};
+pub use debug::DebugGrid;
+pub use degenerate::{DegeneratePolicy, ZeroAreaShape, ZeroLengthLine, ZeroStrokeWidth};
+pub use diff::{diff_commands, diff_renders, ChartDiff, DIFF_HIGHLIGHT};
+pub use dirty::MAX_DIRTY_REGIONS;
+#[cfg(feature = "decimation")]
+pub use downsample::{lttb, min_max};
+pub use export::ImageFormat;
+pub use font::load_font_bytes;
+pub use geometry::Rect;
+pub use gradient::{GradientShape, GradientStyle};
+pub use image_marker::ImageMarkerOptions;
+pub use inset::{draw_inset, ClipBackend};
+pub use layers::{BlendMode, LayeredCanvas};
+pub use legend::{Legend, LegendEntry, LegendLayout, LegendPosition, LegendSwatch};
+pub use marker::{Marker, MarkerBackend, MarkerKind};
+pub use middleware::{Middleware, MiddlewareAction, Offset};
+pub use observer::DrawObserver;
+pub use outline::OutlineBackend;
+pub use pattern::{HatchKind, PatternFill};
+pub use placement::PixelPlacement;
+pub use pool::{BackendPool, PooledCanvas};
+pub use postprocess::PostEffect;
+pub use prerender::{PrerenderSteps, Prerenderer, ViewportRenderer};
+pub use progressive::{decimated, ProgressiveRender};
+pub use queue::{RenderPriority, RenderQueue};
+pub use raster::{BandedUpload, DEFAULT_BAND_HEIGHT};
+pub use readback::{migrate_to_read_frequently, READBACK_HINT_THRESHOLD};
+#[cfg(feature = "remote-backend")]
+pub use remote::{execute_remote_message, serve_remote_port, RemoteBackend};
+pub use recorder::{FrameRecorder, RecordedFrame};
+pub use render_cache::{fingerprint_of, RenderCache};
+pub use render_loop::{
+    vsync_message, AnimationLoop, FrameCallback, FrameTime, LoopControl, ManualLoop, VsyncLoop,
+    VsyncSource,
+};
+pub use replay::ReplayTimeline;
+pub use report::FrameReport;
+pub use scheduler::{ChartRenderer, RefreshScheduler, RefreshSlice};
+pub use scratch::{clear_scratch_canvases, with_scratch_canvas};
+pub use series::SeriesHint;
+pub use shared::SharedBackend;
+pub use sizing::physical_size_for;
+pub use sliding::{RingBuffer, SlidingWindow};
+pub use snap::{GuideStyle, Selection, SnapHit, SnapIndex, SnapMode};
+pub use sprite::{SpriteCache, SpriteFrame};
+pub use stats::{
+    BudgetWarning, FrameBudget, FrameHistogram, FrameStats, FrameTimingSummary, PrimitiveKind,
+    SeriesStats,
+};
+pub use stream::{FrameSink, RegionSink};
+pub use stroke_align::StrokeAlignment;
+pub use style_ext::{
+    CanvasShapeStyle, LineCap, LineJoin, Shadow, StyleExtras, MAX_REGISTERED_STYLES,
+};
+#[cfg(feature = "tessellation")]
+pub use tessellate::{Mesh, Tessellator};
+pub use text_effect::TextEffect;
+pub use text_rotation::TextRotation;
+pub use time::{MockClock, PerformanceClock, SharedTimeSource, TimeSource};
+pub use tween::{Easing, Lerp, Tween};
+pub use viewport::{Axis, Viewport};
+pub use warn::{reset_warnings, warn_once};
+pub use watchdog::{Watchdog, WatchdogReport};
+#[cfg(feature = "worker-pool")]
+pub use worker_pool::{serve_pool_jobs, ChartErrorCallback, JobCallback, WorkerPool};
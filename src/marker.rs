@@ -0,0 +1,403 @@
+use plotters::element::{Drawable, PointCollection};
+use plotters::style::ShapeStyle;
+use plotters_backend::{BackendCoord, BackendStyle, DrawingBackend, DrawingErrorKind};
+use web_sys::{OffscreenCanvasRenderingContext2d, Path2d};
+
+use crate::canvas::{error_cast, CanvasError, OffscreenCanvasBackend};
+use crate::coord::pixel_to_coord;
+use crate::geometry::Rect;
+use crate::stats::PrimitiveKind;
+
+/// The common marker symbols which can be rendered natively by the backend
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MarkerKind {
+    Triangle,
+    Cross,
+    Plus,
+    Diamond,
+    Star,
+}
+
+/// A sub path of a marker outline, relative to the marker center
+pub(crate) struct MarkerPath {
+    pub points: Vec<(f64, f64)>,
+    pub closed: bool,
+}
+
+impl MarkerKind {
+    /// Cross and plus are made of line segments and can only be stroked
+    pub fn is_fillable(&self) -> bool {
+        !matches!(self, MarkerKind::Cross | MarkerKind::Plus)
+    }
+
+    /// The outline of the marker centered at the origin, `size` is the radius of the bounding circle
+    pub(crate) fn paths(&self, size: f64) -> Vec<MarkerPath> {
+        let polar = |r: f64, deg: f64| {
+            let rad = deg.to_radians();
+            (r * rad.sin(), -r * rad.cos())
+        };
+        match self {
+            MarkerKind::Triangle => vec![MarkerPath {
+                points: vec![polar(size, 0.0), polar(size, 120.0), polar(size, 240.0)],
+                closed: true,
+            }],
+            MarkerKind::Diamond => vec![MarkerPath {
+                points: vec![(0.0, -size), (size, 0.0), (0.0, size), (-size, 0.0)],
+                closed: true,
+            }],
+            MarkerKind::Star => vec![MarkerPath {
+                points: (0..10)
+                    .map(|i| {
+                        let r = if i % 2 == 0 { size } else { size * 0.4 };
+                        polar(r, i as f64 * 36.0)
+                    })
+                    .collect(),
+                closed: true,
+            }],
+            MarkerKind::Plus => vec![
+                MarkerPath {
+                    points: vec![(-size, 0.0), (size, 0.0)],
+                    closed: false,
+                },
+                MarkerPath {
+                    points: vec![(0.0, -size), (0.0, size)],
+                    closed: false,
+                },
+            ],
+            MarkerKind::Cross => {
+                let d = size * std::f64::consts::FRAC_1_SQRT_2;
+                vec![
+                    MarkerPath {
+                        points: vec![(-d, -d), (d, d)],
+                        closed: false,
+                    },
+                    MarkerPath {
+                        points: vec![(-d, d), (d, -d)],
+                        closed: false,
+                    },
+                ]
+            }
+        }
+    }
+}
+
+/// Backends which are able to draw marker symbols
+///  - The default implementation builds the marker out of the regular backend primitives,
+///    backends with a faster way to render them (like `OffscreenCanvasBackend`) override it
+pub trait MarkerBackend: DrawingBackend {
+    fn draw_marker<S: BackendStyle>(
+        &mut self,
+        center: BackendCoord,
+        kind: MarkerKind,
+        size: u32,
+        style: &S,
+        filled: bool,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        for path in kind.paths(size as f64) {
+            let mut points: Vec<BackendCoord> = path
+                .points
+                .iter()
+                .map(|(x, y)| {
+                    let (dx, dy) = pixel_to_coord(*x, *y);
+                    (center.0.saturating_add(dx), center.1.saturating_add(dy))
+                })
+                .collect();
+            if path.closed && filled && kind.is_fillable() {
+                self.fill_polygon(points, style)?;
+            } else {
+                if path.closed {
+                    points.push(points[0]);
+                }
+                self.draw_path(points, style)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Draw a marker filled with `fill` and outlined with `stroke`
+    ///  - The fill and the outline have their own color and alpha, so a translucent marker keeps
+    ///    an opaque border. Markers which can't be filled are only stroked
+    fn draw_outlined_marker<F: BackendStyle, S: BackendStyle>(
+        &mut self,
+        center: BackendCoord,
+        kind: MarkerKind,
+        size: u32,
+        fill: &F,
+        stroke: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        if kind.is_fillable() {
+            self.draw_marker(center, kind, size, fill, true)?;
+        }
+        self.draw_marker(center, kind, size, stroke, false)
+    }
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Build the path of the marker once, it is then reused for every marker of that size
+    fn cache_marker_path(
+        &mut self,
+        kind: MarkerKind,
+        size: u32,
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        if !self.marker_cache.contains_key(&(kind, size)) {
+            let path2d = Path2d::new().map_err(error_cast)?;
+            for path in kind.paths(size as f64) {
+                let mut points = path.points.iter();
+                if let Some(&(x, y)) = points.next() {
+                    path2d.move_to(x, y);
+                }
+                for &(x, y) in points {
+                    path2d.line_to(x, y);
+                }
+                if path.closed {
+                    path2d.close_path();
+                }
+            }
+            self.marker_cache.insert((kind, size), path2d);
+        }
+        Ok(())
+    }
+
+    /// Run `paint` with the origin on the center of a marker
+    ///  - The styles are set before, out of the save and restore, so that the style cache still
+    ///    matches the context after it
+    fn paint_at_center(
+        &self,
+        (x, y): (f64, f64),
+        paint: impl FnOnce(&OffscreenCanvasRenderingContext2d),
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        self.context.save();
+        let result = self
+            .context
+            .translate(x, y)
+            .map(|_| paint(&self.context))
+            .map_err(error_cast);
+        self.context.restore();
+        result
+    }
+}
+
+impl<'a> MarkerBackend for OffscreenCanvasBackend<'a> {
+    fn draw_marker<S: BackendStyle>(
+        &mut self,
+        center: BackendCoord,
+        kind: MarkerKind,
+        size: u32,
+        style: &S,
+        filled: bool,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        if style.color().alpha == 0.0 {
+            return Ok(());
+        }
+
+        let stroked = !(filled && kind.is_fillable());
+        let center = if stroked {
+            self.stroke_coord(center)?
+        } else {
+            self.fill_coord(center)?
+        };
+        let (x, y) = match center {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+        let width = self.line_width(style);
+        if stroked && width.is_none() {
+            return Ok(());
+        }
+        let extent = f64::from(size) + width.unwrap_or(0.0) / 2.0;
+        if !self.begin_primitive(PrimitiveKind::Marker, Rect::new(x, y, x, y).inflate(extent)) {
+            return Ok(());
+        }
+        self.cache_marker_path(kind, size)?;
+
+        let key = (kind, size);
+        if !stroked {
+            self.set_fill_style(style);
+            self.paint_at_center((x, y), |context| {
+                context.fill_with_path_2d(&self.marker_cache[&key])
+            })
+        } else {
+            self.set_line_style(style);
+            self.paint_at_center((x, y), |context| {
+                context.stroke_with_path(&self.marker_cache[&key])
+            })
+        }
+    }
+
+    fn draw_outlined_marker<F: BackendStyle, S: BackendStyle>(
+        &mut self,
+        center: BackendCoord,
+        kind: MarkerKind,
+        size: u32,
+        fill: &F,
+        stroke: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let filled = kind.is_fillable() && fill.color().alpha != 0.0;
+        let width = if stroke.color().alpha != 0.0 {
+            self.line_width(stroke)
+        } else {
+            None
+        };
+        if !filled && width.is_none() {
+            return Ok(());
+        }
+
+        let (x, y) = match self.stroke_coord(center)? {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+        let extent = f64::from(size) + width.unwrap_or(0.0) / 2.0;
+        if !self.begin_primitive(PrimitiveKind::Marker, Rect::new(x, y, x, y).inflate(extent)) {
+            return Ok(());
+        }
+        self.cache_marker_path(kind, size)?;
+
+        // One path, filled then stroked with their own colors
+        let key = (kind, size);
+        if filled {
+            self.set_fill_style(fill);
+            self.paint_at_center((x, y), |context| {
+                context.fill_with_path_2d(&self.marker_cache[&key])
+            })?;
+        }
+        if width.is_some() {
+            self.set_line_style(stroke);
+            self.paint_at_center((x, y), |context| {
+                context.stroke_with_path(&self.marker_cache[&key])
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// A marker element, which is drawn with the native marker support of the backend
+pub struct Marker<Coord> {
+    center: Coord,
+    kind: MarkerKind,
+    size: u32,
+    style: ShapeStyle,
+    outline: Option<ShapeStyle>,
+}
+
+impl<Coord> Marker<Coord> {
+    /// Create a new marker
+    ///  - `size` is the radius of the circle the marker fits in
+    pub fn new<S: Into<ShapeStyle>>(center: Coord, kind: MarkerKind, size: u32, style: S) -> Self {
+        Self {
+            center,
+            kind,
+            size,
+            style: style.into(),
+            outline: None,
+        }
+    }
+
+    /// Outline the marker with `stroke`, the style given to [Marker::new] is then used as fill
+    pub fn outlined<S: Into<ShapeStyle>>(mut self, stroke: S) -> Self {
+        self.outline = Some(stroke.into());
+        self
+    }
+}
+
+impl<'a, Coord> PointCollection<'a, Coord> for &'a Marker<Coord> {
+    type Point = &'a Coord;
+    type IntoIter = std::iter::Once<&'a Coord>;
+    fn point_iter(self) -> Self::IntoIter {
+        std::iter::once(&self.center)
+    }
+}
+
+impl<Coord, DB: MarkerBackend> Drawable<DB> for Marker<Coord> {
+    fn draw<I: Iterator<Item = BackendCoord>>(
+        &self,
+        mut points: I,
+        backend: &mut DB,
+        _: (u32, u32),
+    ) -> Result<(), DrawingErrorKind<DB::ErrorType>> {
+        if let Some(center) = points.next() {
+            match &self.outline {
+                Some(outline) => backend.draw_outlined_marker(
+                    center,
+                    self.kind,
+                    self.size,
+                    &self.style,
+                    outline,
+                )?,
+                None => backend.draw_marker(
+                    center,
+                    self.kind,
+                    self.size,
+                    &self.style,
+                    self.style.filled,
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use plotters::prelude::*;
+    use wasm_bindgen_test::*;
+    use web_sys::OffscreenCanvas;
+
+    #[wasm_bindgen_test]
+    fn test_draw_all_markers() {
+        let canvas = OffscreenCanvas::new(100, 100).unwrap();
+        let backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        let root = backend.into_drawing_area();
+
+        let kinds = [
+            MarkerKind::Triangle,
+            MarkerKind::Cross,
+            MarkerKind::Plus,
+            MarkerKind::Diamond,
+            MarkerKind::Star,
+        ];
+        for (i, kind) in kinds.iter().enumerate() {
+            let center = (10 + i as i32 * 20, 50);
+            root.draw(&Marker::new(center, *kind, 6, BLACK.filled()))
+                .unwrap();
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_outlined_marker_is_one_primitive() {
+        let canvas = OffscreenCanvas::new(100, 100).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+
+        backend
+            .draw_outlined_marker(
+                (50, 50),
+                MarkerKind::Diamond,
+                6,
+                &RED.mix(0.3).filled(),
+                &BLACK.stroke_width(1),
+            )
+            .unwrap();
+
+        let stats = backend.frame_stats().unwrap();
+        assert_eq!(stats.count(PrimitiveKind::Marker), 1);
+        assert_eq!(stats.total(), 1);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_marker_restores_the_origin() {
+        let canvas = OffscreenCanvas::new(100, 100).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+
+        backend
+            .draw_marker((50, 50), MarkerKind::Cross, 6, &BLACK, false)
+            .unwrap();
+        backend
+            .draw_outlined_marker((50, 50), MarkerKind::Star, 6, &RED.filled(), &BLACK)
+            .unwrap();
+        backend.draw_rect((0, 0), (4, 4), &BLUE, true).unwrap();
+
+        // The rect is drawn from the origin, not from the center of the markers
+        assert_eq!(backend.pick_color(2, 2).unwrap().rgb, (0, 0, 255));
+        assert_ne!(backend.pick_color(52, 52).unwrap().rgb, (0, 0, 255));
+    }
+}
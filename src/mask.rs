@@ -0,0 +1,41 @@
+use web_sys::{ImageBitmap, OffscreenCanvasRenderingContext2d};
+
+use crate::canvas::{context_2d, js_error, CanvasError};
+use crate::layers::LayeredCanvas;
+
+impl<'a> LayeredCanvas<'a> {
+    /// Keep the content of a layer only where the mask is opaque, scaled by its alpha
+    ///  - The bitmap is stretched over the whole layer
+    pub fn apply_mask_bitmap(&self, name: &str, mask: &ImageBitmap) -> Result<(), CanvasError> {
+        self.apply_mask_with(name, |context, width, height| {
+            context
+                .draw_image_with_image_bitmap_and_dw_and_dh(mask, 0.0, 0.0, width, height)
+                .map_err(js_error)
+        })
+    }
+
+    /// Mask a layer with shapes drawn by `draw`, which gets the context of the layer, and its
+    /// width and height
+    ///  - Everything `draw` paints acts as the mask through destination-in compositing, e.g. a
+    ///    gradient going from opaque to transparent fades the edge of the layer out
+    pub fn apply_mask_with<F>(&self, name: &str, draw: F) -> Result<(), CanvasError>
+    where
+        F: FnOnce(&OffscreenCanvasRenderingContext2d, f64, f64) -> Result<(), CanvasError>,
+    {
+        let canvas = self
+            .layer_canvas(name)
+            .ok_or_else(|| CanvasError::InvalidInput(format!("no layer named {}", name)))?;
+        let context = context_2d(canvas)?;
+        context.save();
+        context
+            .set_global_composite_operation("destination-in")
+            .map_err(js_error)?;
+        let result = draw(
+            &context,
+            f64::from(canvas.width()),
+            f64::from(canvas.height()),
+        );
+        context.restore();
+        result
+    }
+}
@@ -0,0 +1,129 @@
+use plotters_backend::DrawingErrorKind;
+
+use crate::canvas::{CanvasError, OffscreenCanvasBackend};
+use crate::command::DrawCommand;
+
+/// What happens to a command after a middleware saw it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MiddlewareAction {
+    /// Hand the (possibly modified) command to the next middleware, then execute it
+    Continue,
+    /// Drop the command
+    Skip,
+}
+
+/// A hook seeing every primitive before the backend executes it
+///  - Middlewares run in the order they were added and can inspect (logging), modify
+///    (e.g. a global offset), filter, or copy the command to another sink
+pub trait Middleware {
+    fn handle(&mut self, command: &mut DrawCommand) -> MiddlewareAction;
+}
+
+impl<F: FnMut(&mut DrawCommand) -> MiddlewareAction> Middleware for F {
+    fn handle(&mut self, command: &mut DrawCommand) -> MiddlewareAction {
+        self(command)
+    }
+}
+
+/// Middleware moving every primitive by a fixed offset
+pub struct Offset(pub i32, pub i32);
+
+impl Middleware for Offset {
+    fn handle(&mut self, command: &mut DrawCommand) -> MiddlewareAction {
+        command.translate(self.0, self.1);
+        MiddlewareAction::Continue
+    }
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Append a middleware to the chain
+    pub fn add_middleware<M: Middleware + 'static>(&mut self, middleware: M) {
+        self.middleware.push(Box::new(middleware));
+    }
+
+    pub fn clear_middleware(&mut self) {
+        self.middleware.clear();
+    }
+
+    /// Run the command through the middleware chain, then execute it
+    pub(crate) fn submit(
+        &mut self,
+        mut command: DrawCommand,
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        for middleware in self.middleware.iter_mut() {
+            if middleware.handle(&mut command) == MiddlewareAction::Skip {
+                return Ok(());
+            }
+        }
+        self.execute(&command)
+    }
+
+    /// Draw a command on the canvas, bypassing the middleware chain
+    pub fn execute(&mut self, command: &DrawCommand) -> Result<(), DrawingErrorKind<CanvasError>> {
+        match command {
+            DrawCommand::Pixel { point, color } => {
+                self.execute_pixel(*point, color.to_backend_color())
+            }
+            DrawCommand::Line { from, to, style } => self.execute_line(*from, *to, style),
+            DrawCommand::Text { text, pos, style } => self.execute_text(text, style, *pos),
+            DrawCommand::Rect {
+                upper_left,
+                bottom_right,
+                style,
+                fill,
+            } => self.execute_rect(*upper_left, *bottom_right, style, *fill),
+            DrawCommand::Circle {
+                center,
+                radius,
+                style,
+                fill,
+            } => self.execute_circle(*center, *radius, style, *fill),
+            DrawCommand::Path { points, style } => self.execute_path(points, style),
+            DrawCommand::Polygon { points, style } => self.execute_polygon(points, style),
+            DrawCommand::Bitmap { pos, size, data } => self.execute_bitmap(*pos, *size, data),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use plotters::prelude::*;
+    use plotters_backend::DrawingBackend;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use wasm_bindgen_test::*;
+    use web_sys::OffscreenCanvas;
+
+    #[wasm_bindgen_test]
+    fn test_middleware_chain() {
+        let canvas = OffscreenCanvas::new(100, 100).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+
+        let seen = Rc::new(RefCell::new(vec![]));
+        let log = seen.clone();
+        backend.add_middleware(Offset(10, 0));
+        backend.add_middleware(move |command: &mut DrawCommand| {
+            log.borrow_mut().push(command.clone());
+            match command {
+                DrawCommand::Line { .. } => MiddlewareAction::Skip,
+                _ => MiddlewareAction::Continue,
+            }
+        });
+
+        backend
+            .draw_pixel((1, 2), BLACK.to_backend_color())
+            .unwrap();
+        backend
+            .draw_line((0, 0), (5, 5), &BLACK.to_backend_color())
+            .unwrap();
+
+        let seen = seen.borrow();
+        assert_eq!(seen.len(), 2);
+        match &seen[0] {
+            DrawCommand::Pixel { point, .. } => assert_eq!(*point, (11, 2)),
+            other => panic!("unexpected command {:?}", other),
+        }
+        assert_eq!(backend.frame_stats().unwrap().total(), 1);
+    }
+}
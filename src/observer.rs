@@ -0,0 +1,89 @@
+use crate::canvas::OffscreenCanvasBackend;
+use crate::geometry::Rect;
+use crate::stats::{FrameStats, PrimitiveKind};
+
+/// Callbacks following the rendering, e.g. to drive a progress bar or feed a profiler
+///  - Unlike a middleware, an observer can't change what is drawn
+///  - Every method does nothing by default
+pub trait DrawObserver {
+    /// The first primitive of a frame is about to be drawn
+    fn on_frame_start(&mut self) {}
+
+    /// A primitive passed culling and is about to be drawn
+    fn on_primitive(&mut self, _kind: PrimitiveKind, _bounds: &Rect) {}
+
+    /// The frame was presented, after `elapsed_ms` milliseconds
+    ///  - Only called if something was drawn since the last present
+    fn on_present(&mut self, _stats: &FrameStats, _elapsed_ms: f64) {}
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    pub fn add_observer<O: DrawObserver + 'static>(&mut self, observer: O) {
+        self.observers.push(Box::new(observer));
+    }
+
+    pub fn clear_observers(&mut self) {
+        self.observers.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use plotters::prelude::*;
+    use plotters_backend::DrawingBackend;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use wasm_bindgen_test::*;
+    use web_sys::OffscreenCanvas;
+
+    #[derive(Default)]
+    struct Counts {
+        starts: usize,
+        primitives: usize,
+        presents: usize,
+    }
+
+    struct Counter(Rc<RefCell<Counts>>);
+
+    impl DrawObserver for Counter {
+        fn on_frame_start(&mut self) {
+            self.0.borrow_mut().starts += 1;
+        }
+
+        fn on_primitive(&mut self, _kind: PrimitiveKind, _bounds: &Rect) {
+            self.0.borrow_mut().primitives += 1;
+        }
+
+        fn on_present(&mut self, _stats: &FrameStats, _elapsed_ms: f64) {
+            self.0.borrow_mut().presents += 1;
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_observer_callbacks() {
+        let canvas = OffscreenCanvas::new(100, 100).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        let counts = Rc::new(RefCell::new(Counts::default()));
+        backend.add_observer(Counter(counts.clone()));
+
+        backend
+            .draw_pixel((1, 1), BLACK.to_backend_color())
+            .unwrap();
+        backend
+            .draw_pixel((2, 2), BLACK.to_backend_color())
+            .unwrap();
+        // Culled, not reported
+        backend
+            .draw_pixel((500, 2), BLACK.to_backend_color())
+            .unwrap();
+        backend.present().unwrap();
+        // Nothing drawn
+        backend.present().unwrap();
+
+        let counts = counts.borrow();
+        assert_eq!(counts.starts, 1);
+        assert_eq!(counts.primitives, 2);
+        assert_eq!(counts.presents, 1);
+    }
+}
@@ -0,0 +1,301 @@
+use plotters_backend::{BackendCoord, BackendStyle, DrawingBackend, DrawingErrorKind};
+
+use crate::canvas::{error_cast, CanvasError, OffscreenCanvasBackend};
+use crate::command::CommandStyle;
+use crate::geometry::Rect;
+use crate::placement::PixelPlacement;
+use crate::stats::PrimitiveKind;
+use crate::stroke_align::{offset_polygon, offset_rect};
+
+/// Backends which can fill and outline a shape in one call
+///  - plotters draws outlined shapes as a fill pass then a stroke pass, which builds the path
+///    twice. The default implementations do just that, `OffscreenCanvasBackend` builds the path
+///    once and fills then strokes it
+pub trait OutlineBackend: DrawingBackend {
+    fn draw_outlined_rect<F: BackendStyle, S: BackendStyle>(
+        &mut self,
+        upper_left: BackendCoord,
+        bottom_right: BackendCoord,
+        fill: &F,
+        stroke: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.draw_rect(upper_left, bottom_right, fill, true)?;
+        self.draw_rect(upper_left, bottom_right, stroke, false)
+    }
+
+    fn draw_outlined_circle<F: BackendStyle, S: BackendStyle>(
+        &mut self,
+        center: BackendCoord,
+        radius: u32,
+        fill: &F,
+        stroke: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.draw_circle(center, radius, fill, true)?;
+        self.draw_circle(center, radius, stroke, false)
+    }
+
+    fn draw_outlined_polygon<
+        F: BackendStyle,
+        S: BackendStyle,
+        I: IntoIterator<Item = BackendCoord>,
+    >(
+        &mut self,
+        vert: I,
+        fill: &F,
+        stroke: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let mut points: Vec<_> = vert.into_iter().collect();
+        self.fill_polygon(points.iter().copied(), fill)?;
+        if let Some(&first) = points.first() {
+            points.push(first);
+        }
+        self.draw_path(points, stroke)
+    }
+}
+
+/// A closed shape in canvas space
+#[derive(Clone, Debug)]
+pub(crate) enum Shape {
+    Rect(Rect),
+    Circle((f64, f64), f64),
+    Polygon(Vec<(f64, f64)>),
+}
+
+impl Shape {
+    fn bounds(&self) -> Option<Rect> {
+        match self {
+            Shape::Rect(rect) => Some(*rect),
+            Shape::Circle((x, y), r) => Some(Rect::new(x - r, y - r, x + r, y + r)),
+            Shape::Polygon(points) => Rect::bounding(points),
+        }
+    }
+
+    /// The shape moved outward by `d` (inward if negative)
+    fn offset(&self, d: f64) -> Shape {
+        match self {
+            Shape::Rect(rect) => Shape::Rect(offset_rect(*rect, d)),
+            Shape::Circle(center, r) => Shape::Circle(*center, (r + d).max(0.0)),
+            Shape::Polygon(points) => Shape::Polygon(offset_polygon(points, d)),
+        }
+    }
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    fn trace_shape(&self, shape: &Shape) -> Result<(), DrawingErrorKind<CanvasError>> {
+        self.context.begin_path();
+        match shape {
+            Shape::Rect(r) => self.context.rect(r.left, r.top, r.width(), r.height()),
+            Shape::Circle((x, y), r) => self
+                .context
+                .arc(*x, *y, *r, 0.0, std::f64::consts::PI * 2.0)
+                .map_err(error_cast)?,
+            Shape::Polygon(points) => {
+                for (i, &(x, y)) in points.iter().enumerate() {
+                    if i == 0 {
+                        self.context.move_to(x, y);
+                    } else {
+                        self.context.line_to(x, y);
+                    }
+                }
+                self.context.close_path();
+            }
+        }
+        Ok(())
+    }
+
+    /// Fill and/or stroke a shape, building its path once when the stroke is centered
+    ///  - This is the primitive the shape overrides of the backend are built on
+    pub(crate) fn draw_shape(
+        &mut self,
+        kind: PrimitiveKind,
+        shape: Shape,
+        fill: Option<CommandStyle>,
+        stroke: Option<CommandStyle>,
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        let fill = fill.filter(|f| f.color.alpha != 0.0);
+        let stroke = stroke
+            .filter(|s| s.color.alpha != 0.0)
+            .and_then(|s| self.line_width(&s).map(|w| (s, w)));
+        if fill.is_none() && stroke.is_none() {
+            return Ok(());
+        }
+
+        let offset = stroke.map_or(0.0, |(_, w)| self.stroke_alignment.offset(w));
+        let stroked = if offset != 0.0 {
+            shape.offset(offset)
+        } else {
+            shape.clone()
+        };
+        let bounds = match (shape.bounds(), stroked.bounds()) {
+            (Some(a), Some(b)) => a.union(b),
+            _ => return Ok(()),
+        };
+        let margin = stroke.map_or(0.0, |(_, w)| w / 2.0);
+        if !self.begin_primitive(kind, bounds.inflate(margin)) {
+            return Ok(());
+        }
+
+        self.trace_shape(&shape)?;
+        if let Some(fill) = fill {
+            self.set_fill_style(&fill);
+            self.context.fill();
+        }
+        if let Some((style, _)) = stroke {
+            // The fill path is reused as is when the stroke is centered
+            if offset != 0.0 {
+                self.trace_shape(&stroked)?;
+            }
+            self.set_line_style(&style);
+            if let Shape::Polygon(points) = &stroked {
+                self.apply_line_join(points, true);
+            }
+            self.stroke_current_path();
+        }
+        Ok(())
+    }
+
+    /// Fill and stroke the shape which `place` builds with the placement of the fills, then
+    /// with the one of the strokes
+    ///  - The path is built once when both placements are the same
+    fn draw_placed_shape<P>(
+        &mut self,
+        kind: PrimitiveKind,
+        place: P,
+        fill: CommandStyle,
+        stroke: CommandStyle,
+    ) -> Result<(), DrawingErrorKind<CanvasError>>
+    where
+        P: Fn(&Self, PixelPlacement) -> Result<Option<Shape>, DrawingErrorKind<CanvasError>>,
+    {
+        if self.fill_placement == self.stroke_placement {
+            return match place(self, self.fill_placement)? {
+                Some(shape) => self.draw_shape(kind, shape, Some(fill), Some(stroke)),
+                None => Ok(()),
+            };
+        }
+        if let Some(shape) = place(self, self.fill_placement)? {
+            self.draw_shape(kind, shape, Some(fill), None)?;
+        }
+        match place(self, self.stroke_placement)? {
+            Some(shape) => self.draw_shape(kind, shape, None, Some(stroke)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<'a> OutlineBackend for OffscreenCanvasBackend<'a> {
+    fn draw_outlined_rect<F: BackendStyle, S: BackendStyle>(
+        &mut self,
+        upper_left: BackendCoord,
+        bottom_right: BackendCoord,
+        fill: &F,
+        stroke: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.draw_placed_shape(
+            PrimitiveKind::Rect,
+            |backend, placement| {
+                let a = backend.placed_coord(upper_left, placement)?;
+                let b = backend.placed_coord(bottom_right, placement)?;
+                Ok(match (a, b) {
+                    (Some(a), Some(b)) => Some(Shape::Rect(Rect::new(a.0, a.1, b.0, b.1))),
+                    _ => None,
+                })
+            },
+            CommandStyle::from_style(fill),
+            CommandStyle::from_style(stroke),
+        )
+    }
+
+    fn draw_outlined_circle<F: BackendStyle, S: BackendStyle>(
+        &mut self,
+        center: BackendCoord,
+        radius: u32,
+        fill: &F,
+        stroke: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.draw_placed_shape(
+            PrimitiveKind::Circle,
+            |backend, placement| {
+                let center = backend.placed_coord(center, placement)?;
+                Ok(center.map(|c| Shape::Circle(c, f64::from(radius))))
+            },
+            CommandStyle::from_style(fill),
+            CommandStyle::from_style(stroke),
+        )
+    }
+
+    fn draw_outlined_polygon<
+        F: BackendStyle,
+        S: BackendStyle,
+        I: IntoIterator<Item = BackendCoord>,
+    >(
+        &mut self,
+        vert: I,
+        fill: &F,
+        stroke: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let vert: Vec<_> = vert.into_iter().collect();
+        self.draw_placed_shape(
+            PrimitiveKind::Polygon,
+            |backend, placement| {
+                let mut points = vec![];
+                for &p in &vert {
+                    match backend.placed_coord(p, placement)? {
+                        Some(p) => points.push(p),
+                        None => return Ok(None),
+                    }
+                }
+                Ok(Some(Shape::Polygon(points)))
+            },
+            CommandStyle::from_style(fill),
+            CommandStyle::from_style(stroke),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use plotters::prelude::*;
+    use wasm_bindgen_test::*;
+    use web_sys::OffscreenCanvas;
+
+    #[wasm_bindgen_test]
+    fn test_outlined_shapes_are_one_primitive() {
+        let canvas = OffscreenCanvas::new(100, 100).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        let fill = RED.filled();
+        let stroke = BLACK.stroke_width(2);
+
+        backend
+            .draw_outlined_rect((10, 10), (40, 40), &fill, &stroke)
+            .unwrap();
+        backend
+            .draw_outlined_circle((60, 60), 10, &fill, &stroke)
+            .unwrap();
+        backend
+            .draw_outlined_polygon(vec![(0, 90), (20, 70), (40, 90)], &fill, &stroke)
+            .unwrap();
+
+        let stats = backend.frame_stats().unwrap();
+        assert_eq!(stats.count(PrimitiveKind::Rect), 1);
+        assert_eq!(stats.count(PrimitiveKind::Circle), 1);
+        assert_eq!(stats.count(PrimitiveKind::Polygon), 1);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_outlined_shapes_are_placed() {
+        let canvas = OffscreenCanvas::new(20, 20).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        backend.set_pixel_placement(PixelPlacement::PixelCenter, PixelPlacement::Exact);
+        backend
+            .draw_outlined_rect((5, 5), (15, 15), &RED.filled(), &BLACK)
+            .unwrap();
+
+        // The 1px outline covers the column 5 only, instead of half of the columns 4 and 5
+        assert_eq!(backend.pick_color(5, 10).unwrap().rgb, (0, 0, 0));
+        assert_eq!(backend.pick_color(5, 10).unwrap().alpha, 1.0);
+        assert_eq!(backend.pick_color(4, 10).unwrap().alpha, 0.0);
+        assert_eq!(backend.pick_color(10, 10).unwrap().rgb, (255, 0, 0));
+    }
+}
@@ -0,0 +1,199 @@
+use web_sys::{CanvasPattern, OffscreenCanvas};
+
+use crate::canvas::{context_2d, js_error_in, CanvasError, OffscreenCanvasBackend};
+use crate::color::{make_canvas_color, set_fill_css, set_fill_pattern, set_stroke_css};
+use crate::command::CommandColor;
+
+/// Patterns kept per backend, charts rarely use more series
+const MAX_PATTERNS: usize = 16;
+
+/// The motif of a pattern fill
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HatchKind {
+    Horizontal,
+    Vertical,
+    /// Stripes going up to the right
+    Diagonal,
+    /// Stripes going down to the right
+    AntiDiagonal,
+    Cross,
+    DiagonalCross,
+    Dots,
+}
+
+/// A hatch or stripe fill, to tell the series apart without colors (e.g. on print)
+///
+/// ```ignore
+/// let hatch = PatternFill::new(HatchKind::DiagonalCross, BLACK.to_backend_color().into())
+///     .spacing(6);
+/// chart.draw_series(bars.map(|b| b.style(CanvasShapeStyle::new(BLACK.filled()).pattern(hatch))))?;
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PatternFill {
+    pub kind: HatchKind,
+    pub color: CommandColor,
+    /// Painted under the motif, transparent if None
+    pub background: Option<CommandColor>,
+    /// Distance between the lines or the dots, in pixels
+    pub spacing: u32,
+    pub line_width: f64,
+}
+
+impl PatternFill {
+    pub fn new(kind: HatchKind, color: CommandColor) -> Self {
+        Self {
+            kind,
+            color,
+            background: None,
+            spacing: 8,
+            line_width: 1.0,
+        }
+    }
+
+    pub fn spacing(mut self, spacing: u32) -> Self {
+        self.spacing = spacing.max(2);
+        self
+    }
+
+    pub fn line_width(mut self, width: f64) -> Self {
+        self.line_width = width;
+        self
+    }
+
+    pub fn background(mut self, color: CommandColor) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    /// Draw one tile of the pattern, it repeats seamlessly
+    pub(crate) fn render_tile(&self) -> Result<OffscreenCanvas, CanvasError> {
+        let s = self.spacing.max(2);
+        let canvas = OffscreenCanvas::new(s, s).map_err(js_error_in("OffscreenCanvas::new"))?;
+        let context = context_2d(&canvas)?;
+        let s = f64::from(s);
+
+        if let Some(background) = self.background {
+            set_fill_css(&context, &make_canvas_color(background.to_backend_color()));
+            context.fill_rect(0.0, 0.0, s, s);
+        }
+        let color = make_canvas_color(self.color.to_backend_color());
+        set_fill_css(&context, &color);
+        set_stroke_css(&context, &color);
+        context.set_line_width(self.line_width);
+
+        context.begin_path();
+        let line = |x0: f64, y0: f64, x1: f64, y1: f64| {
+            context.move_to(x0, y0);
+            context.line_to(x1, y1);
+        };
+        let horizontal = matches!(self.kind, HatchKind::Horizontal | HatchKind::Cross);
+        let vertical = matches!(self.kind, HatchKind::Vertical | HatchKind::Cross);
+        let diagonal = matches!(self.kind, HatchKind::Diagonal | HatchKind::DiagonalCross);
+        let anti_diagonal = matches!(
+            self.kind,
+            HatchKind::AntiDiagonal | HatchKind::DiagonalCross
+        );
+        if horizontal {
+            line(0.0, s / 2.0, s, s / 2.0);
+        }
+        if vertical {
+            line(s / 2.0, 0.0, s / 2.0, s);
+        }
+        // The neighbour tiles' stripes cross the corners, so that the stripes join up
+        for &offset in [-s, 0.0, s].iter() {
+            if diagonal {
+                line(offset, s, offset + s, 0.0);
+            }
+            if anti_diagonal {
+                line(offset, 0.0, offset + s, s);
+            }
+        }
+        context.stroke();
+
+        if self.kind == HatchKind::Dots {
+            context.begin_path();
+            let _ = context.arc(
+                s / 2.0,
+                s / 2.0,
+                self.line_width.max(0.5),
+                0.0,
+                std::f64::consts::PI * 2.0,
+            );
+            context.fill();
+        }
+        Ok(canvas)
+    }
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Replace the fill color with the pattern of the applied style, if it has one
+    ///  - The tiles are rendered once per pattern and kept by the backend
+    ///  - A pattern takes precedence over a gradient of the same style
+    pub(crate) fn apply_fill_pattern(&mut self) {
+        let pattern = match self.applied_extras.as_ref().and_then(|e| e.pattern) {
+            Some(pattern) => pattern,
+            None => return,
+        };
+        let compositing = self.compositing;
+        let map = |c: CommandColor| CommandColor::from(compositing.apply(c.to_backend_color()));
+        let pattern = PatternFill {
+            color: map(pattern.color),
+            background: pattern.background.map(map),
+            ..pattern
+        };
+
+        let cached = self.patterns.iter().find(|(p, _)| *p == pattern);
+        let canvas_pattern = match cached {
+            Some((_, canvas_pattern)) => canvas_pattern.clone(),
+            None => {
+                let created = pattern.render_tile().and_then(|tile| {
+                    self.context
+                        .create_pattern_with_offscreen_canvas(&tile, "repeat")
+                        .map_err(js_error_in("createPattern"))
+                });
+                let canvas_pattern = match created {
+                    Ok(Some(canvas_pattern)) => canvas_pattern,
+                    _ => return,
+                };
+                if self.patterns.len() >= MAX_PATTERNS {
+                    self.patterns.remove(0);
+                }
+                self.patterns.push((pattern, canvas_pattern.clone()));
+                canvas_pattern
+            }
+        };
+        set_fill_pattern(&self.context, &canvas_pattern);
+        self.style_cache.forget_fill();
+    }
+
+    /// Drop the pattern tiles kept by the backend
+    pub fn clear_patterns(&mut self) {
+        self.patterns.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::style_ext::CanvasShapeStyle;
+    use plotters::prelude::*;
+    use plotters_backend::DrawingBackend;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_pattern_fill() {
+        let canvas = OffscreenCanvas::new(40, 40).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        let hatch = PatternFill::new(HatchKind::Vertical, BLACK.to_backend_color().into())
+            .spacing(4)
+            .line_width(2.0);
+        let style = CanvasShapeStyle::new(RED.filled()).pattern(hatch);
+        backend.draw_rect((0, 0), (40, 40), &style, true).unwrap();
+        backend.draw_rect((0, 0), (40, 40), &style, true).unwrap();
+        assert_eq!(backend.patterns.len(), 1);
+
+        // Columns 1 and 2 of each tile are painted, 0 and 3 are left transparent
+        assert_eq!(backend.pick_color(1, 10).unwrap().rgb, (0, 0, 0));
+        assert_eq!(backend.pick_color(4, 10).unwrap().alpha, 0.0);
+    }
+}
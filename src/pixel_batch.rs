@@ -0,0 +1,174 @@
+use plotters_backend::{BackendColor, DrawingBackend};
+use wasm_bindgen::Clamped;
+use web_sys::{ImageData, OffscreenCanvasRenderingContext2d};
+
+use crate::canvas::{js_error_in, CanvasError, OffscreenCanvasBackend};
+use crate::scratch::with_scratch_canvas;
+use crate::warn::warn_once;
+
+/// Pixels buffered before they are blitted anyway, so that the buffer stays small
+const MAX_BATCHED_PIXELS: usize = 1 << 16;
+
+/// A pixel drawn with draw_pixel(), in logical pixels, with its composited RGBA color
+pub(crate) type BatchedPixel = (i32, i32, [u8; 4]);
+
+/// Draw `src` over `dst`, both RGBA with straight alpha
+fn blend_over(dst: &mut [u8], src: [u8; 4]) {
+    let sa = f64::from(src[3]) / 255.0;
+    let da = f64::from(dst[3]) / 255.0;
+    let alpha = sa + da * (1.0 - sa);
+    if alpha == 0.0 {
+        return;
+    }
+    for i in 0..3 {
+        let c = (f64::from(src[i]) * sa + f64::from(dst[i]) * da * (1.0 - sa)) / alpha;
+        dst[i] = c.round() as u8;
+    }
+    dst[3] = (alpha * 255.0).round() as u8;
+}
+
+/// Draw the pixels on the context with a single drawImage
+///  - The pixels are put in an image of their bounding box on a scratch canvas, and drawn
+///    through the context: the clip, the transform and the compositing apply as with fillRect
+///  - Only the pixels inside of `size`, the logical size of the canvas, are drawn: without
+///    culling the batch can have pixels far away, which would make a huge image
+fn blit_pixels(
+    context: &OffscreenCanvasRenderingContext2d,
+    pixels: &[BatchedPixel],
+    size: (u32, u32),
+) -> Result<(), CanvasError> {
+    let (w, h) = (i64::from(size.0), i64::from(size.1));
+    let inside = |&&(x, y, _): &&BatchedPixel| {
+        (0..w).contains(&i64::from(x)) && (0..h).contains(&i64::from(y))
+    };
+    let (mut left, mut top) = (i32::MAX, i32::MAX);
+    let (mut right, mut bottom) = (i32::MIN, i32::MIN);
+    for &(x, y, _) in pixels.iter().filter(inside) {
+        left = left.min(x);
+        top = top.min(y);
+        right = right.max(x);
+        bottom = bottom.max(y);
+    }
+    if left > right {
+        return Ok(());
+    }
+    let width = (i64::from(right) - i64::from(left) + 1) as usize;
+    let height = (i64::from(bottom) - i64::from(top) + 1) as usize;
+
+    let mut rgba = vec![0; width * height * 4];
+    for &(x, y, color) in pixels.iter().filter(inside) {
+        let i = ((y - top) as usize * width + (x - left) as usize) * 4;
+        blend_over(&mut rgba[i..i + 4], color);
+    }
+
+    let (width, height) = (width as u32, height as u32);
+    with_scratch_canvas(width, height, |scratch, scratch_context| {
+        let image = ImageData::new_with_u8_clamped_array_and_sh(Clamped(&rgba), width, height)
+            .map_err(js_error_in("ImageData::new"))?;
+        scratch_context
+            .put_image_data(&image, 0.0, 0.0)
+            .map_err(js_error_in("putImageData"))?;
+
+        // Scaled by the pixel ratio, each pixel stays a sharp square as with fillRect
+        let smoothing = context.image_smoothing_enabled();
+        context.set_image_smoothing_enabled(false);
+        let (w, h) = (f64::from(width), f64::from(height));
+        let result = context
+            .draw_image_with_offscreen_canvas_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                scratch,
+                0.0,
+                0.0,
+                w,
+                h,
+                f64::from(left),
+                f64::from(top),
+                w,
+                h,
+            )
+            .map_err(js_error_in("drawImage"));
+        context.set_image_smoothing_enabled(smoothing);
+        result
+    })?
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Buffer a pixel of draw_pixel(), it is drawn with the other buffered pixels by
+    /// flush_batch()
+    pub(crate) fn batch_pixel(&mut self, x: f64, y: f64, color: BackendColor) {
+        let color = self.compositing.apply(color);
+        let (r, g, b) = color.rgb;
+        let alpha = (color.alpha.max(0.0).min(1.0) * 255.0).round() as u8;
+        self.pixel_batch
+            .push((x.floor() as i32, y.floor() as i32, [r, g, b, alpha]));
+        if self.pixel_batch.len() >= MAX_BATCHED_PIXELS {
+            self.flush_pixels();
+        }
+    }
+
+    /// Draw the buffered pixels
+    ///  - If the image can't be blitted, they are filled one by one
+    pub(crate) fn flush_pixels(&mut self) {
+        if self.pixel_batch.is_empty() {
+            return;
+        }
+        let pixels = std::mem::take(&mut self.pixel_batch);
+        if blit_pixels(&self.context, &pixels, self.get_size()).is_err() {
+            warn_once("batched pixels couldn't be blitted, they are filled one by one");
+            for &(x, y, [r, g, b, a]) in pixels.iter() {
+                let color = BackendColor {
+                    alpha: f64::from(a) / 255.0,
+                    rgb: (r, g, b),
+                };
+                self.style_cache.set_fill(&self.context, color);
+                self.context.fill_rect(f64::from(x), f64::from(y), 1.0, 1.0);
+            }
+        }
+        // The buffer keeps its capacity for the next frame
+        self.pixel_batch = pixels;
+        self.pixel_batch.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use plotters::prelude::*;
+    use plotters_backend::DrawingBackend;
+    use wasm_bindgen_test::*;
+    use web_sys::OffscreenCanvas;
+
+    #[wasm_bindgen_test]
+    fn test_pixel_batch() {
+        let canvas = OffscreenCanvas::new(20, 20).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        for x in 0..10 {
+            backend.draw_pixel((x, 5), RED.to_backend_color()).unwrap();
+        }
+        backend
+            .draw_pixel((3, 5), BLUE.mix(0.5).to_backend_color())
+            .unwrap();
+        assert_eq!(backend.pixel_batch.len(), 11);
+
+        // Another primitive draws the pixels first
+        backend.draw_rect((0, 6), (10, 8), &GREEN, true).unwrap();
+        assert!(backend.pixel_batch.is_empty());
+        assert_eq!(backend.pick_color(9, 5).unwrap().rgb, (255, 0, 0));
+        let blended = backend.pick_color(3, 5).unwrap();
+        assert_eq!(blended.alpha, 1.0);
+        assert!(blended.rgb.0 > 100 && blended.rgb.2 > 100);
+
+        // Without culling, far away pixels reach the batch and are dropped
+        backend.set_culling(false);
+        backend
+            .draw_pixel((-100_000, 0), RED.to_backend_color())
+            .unwrap();
+        backend
+            .draw_pixel((100_000, 100_000), RED.to_backend_color())
+            .unwrap();
+        backend
+            .draw_pixel((15, 15), RED.to_backend_color())
+            .unwrap();
+        backend.present().unwrap();
+        assert_eq!(backend.pick_color(15, 15).unwrap().rgb, (255, 0, 0));
+    }
+}
@@ -0,0 +1,105 @@
+use plotters_backend::{BackendCoord, DrawingErrorKind};
+
+use crate::canvas::{CanvasError, OffscreenCanvasBackend};
+
+/// How the coordinates of a primitive are placed on the pixel grid
+///  - The placement is done in physical pixels, so it stays crisp with a pixel ratio
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PixelPlacement {
+    /// The coordinates are used as they are, geometrically accurate
+    Exact,
+    /// On the pixel edge at the top left of the coordinate
+    Floor,
+    /// On the nearest pixel edge
+    Round,
+    /// On the center of the pixel of the coordinate, odd stroke widths cover whole pixels
+    PixelCenter,
+}
+
+impl Default for PixelPlacement {
+    fn default() -> Self {
+        PixelPlacement::Exact
+    }
+}
+
+impl PixelPlacement {
+    /// Place a logical coordinate, `ratio` is the number of physical pixels per logical pixel
+    pub fn place(&self, v: f64, ratio: f64) -> f64 {
+        let p = v * ratio;
+        let placed = match self {
+            PixelPlacement::Exact => return v,
+            PixelPlacement::Floor => p.floor(),
+            PixelPlacement::Round => p.round(),
+            PixelPlacement::PixelCenter => p.floor() + 0.5,
+        };
+        placed / ratio
+    }
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Set the placement of the coordinates of stroked and of filled primitives
+    ///  - Both are Exact by default, PixelCenter strokes and Floor fills give crisp 1px grid
+    ///    lines and bar edges
+    pub fn set_pixel_placement(&mut self, strokes: PixelPlacement, fills: PixelPlacement) {
+        self.stroke_placement = strokes;
+        self.fill_placement = fills;
+    }
+
+    /// The placement of stroked and of filled primitives
+    pub fn pixel_placement(&self) -> (PixelPlacement, PixelPlacement) {
+        (self.stroke_placement, self.fill_placement)
+    }
+
+    /// Convert the coordinate of a stroked primitive, see [coord](Self::coord)
+    pub(crate) fn stroke_coord(
+        &self,
+        p: BackendCoord,
+    ) -> Result<Option<(f64, f64)>, DrawingErrorKind<CanvasError>> {
+        self.placed_coord(p, self.stroke_placement)
+    }
+
+    /// Convert the coordinate of a filled primitive, see [coord](Self::coord)
+    pub(crate) fn fill_coord(
+        &self,
+        p: BackendCoord,
+    ) -> Result<Option<(f64, f64)>, DrawingErrorKind<CanvasError>> {
+        self.placed_coord(p, self.fill_placement)
+    }
+
+    /// Convert a coordinate with the given placement, see [coord](Self::coord)
+    pub(crate) fn placed_coord(
+        &self,
+        p: BackendCoord,
+        placement: PixelPlacement,
+    ) -> Result<Option<(f64, f64)>, DrawingErrorKind<CanvasError>> {
+        let (sx, sy) = self.scale_factors();
+        Ok(self
+            .coord(p)?
+            .map(|(x, y)| (placement.place(x, sx), placement.place(y, sy))))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use plotters::prelude::*;
+    use plotters_backend::DrawingBackend;
+    use wasm_bindgen_test::*;
+    use web_sys::OffscreenCanvas;
+
+    #[wasm_bindgen_test]
+    fn test_pixel_placement() {
+        assert_eq!(PixelPlacement::PixelCenter.place(3.0, 1.0), 3.5);
+        assert_eq!(PixelPlacement::Floor.place(3.3, 2.0), 3.0);
+        assert_eq!(PixelPlacement::Round.place(3.3, 2.0), 3.5);
+
+        let canvas = OffscreenCanvas::new(20, 20).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        backend.set_pixel_placement(PixelPlacement::PixelCenter, PixelPlacement::Exact);
+        backend.draw_line((0, 10), (20, 10), &BLACK).unwrap();
+
+        // The 1px line covers the row 10 only, instead of half of the rows 9 and 10
+        assert_eq!(backend.pick_color(5, 10).unwrap().alpha, 1.0);
+        assert_eq!(backend.pick_color(5, 9).unwrap().alpha, 0.0);
+    }
+}
@@ -0,0 +1,98 @@
+use web_sys::{OffscreenCanvas, OffscreenCanvasRenderingContext2d};
+
+use crate::canvas::{context_2d, js_error, js_error_in, CanvasError, OffscreenCanvasBackend};
+
+/// A canvas with its 2d context, handed out by a [BackendPool]
+pub struct PooledCanvas {
+    canvas: OffscreenCanvas,
+    context: OffscreenCanvasRenderingContext2d,
+}
+
+impl PooledCanvas {
+    pub fn canvas(&self) -> &OffscreenCanvas {
+        &self.canvas
+    }
+
+    /// Create a drawing backend on the pooled canvas, reusing its context
+    pub fn backend(&self) -> OffscreenCanvasBackend<'_> {
+        OffscreenCanvasBackend::with_context(&self.canvas, self.context.clone())
+    }
+
+    fn resize(&self, width: u32, height: u32) {
+        // Changing the size resets the bitmap and the context state
+        if self.canvas.width() != width {
+            self.canvas.set_width(width);
+        }
+        if self.canvas.height() != height {
+            self.canvas.set_height(height);
+        }
+    }
+
+    fn clear(&self) -> Result<(), CanvasError> {
+        self.context
+            .set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+            .map_err(js_error)?;
+        self.context.clear_rect(
+            0.0,
+            0.0,
+            f64::from(self.canvas.width()),
+            f64::from(self.canvas.height()),
+        );
+        Ok(())
+    }
+}
+
+/// Recycle canvases and their contexts for applications creating and destroying charts frequently
+pub struct BackendPool {
+    idle: Vec<PooledCanvas>,
+    max_idle: usize,
+}
+
+impl BackendPool {
+    /// Create a pool keeping at most `max_idle` released canvases around
+    pub fn new(max_idle: usize) -> Self {
+        Self {
+            idle: vec![],
+            max_idle,
+        }
+    }
+
+    /// Get a cleared canvas of the given size, recycling a released one if possible
+    pub fn acquire(&mut self, width: u32, height: u32) -> Result<PooledCanvas, CanvasError> {
+        let exact = self
+            .idle
+            .iter()
+            .position(|c| c.canvas.width() == width && c.canvas.height() == height);
+
+        if let Some(pooled) = exact.map(|i| self.idle.swap_remove(i)).or_else(|| self.idle.pop())
+        {
+            pooled.resize(width, height);
+            return Ok(pooled);
+        }
+
+        let canvas =
+            OffscreenCanvas::new(width, height).map_err(js_error_in("OffscreenCanvas::new"))?;
+        let context = context_2d(&canvas)?;
+        Ok(PooledCanvas { canvas, context })
+    }
+
+    /// Give a canvas back to the pool, it's dropped if the pool is already full
+    pub fn release(&mut self, pooled: PooledCanvas) -> Result<(), CanvasError> {
+        if self.idle.len() >= self.max_idle {
+            return Ok(());
+        }
+        pooled.clear()?;
+        self.idle.push(pooled);
+        Ok(())
+    }
+
+    /// Number of canvases waiting to be reused
+    pub fn idle_count(&self) -> usize {
+        self.idle.len()
+    }
+
+    /// Drop all the idle canvases
+    pub fn clear(&mut self) {
+        self.idle.clear();
+    }
+}
@@ -0,0 +1,182 @@
+use plotters_backend::DrawingErrorKind;
+
+use crate::canvas::{error_cast, CanvasError, OffscreenCanvasBackend};
+use crate::color::{make_canvas_color, set_fill_css, set_fill_gradient, set_stroke_css};
+use crate::command::CommandColor;
+use crate::scratch::with_scratch_canvas;
+
+/// An effect applied to the finished frame on present, see
+/// [OffscreenCanvasBackend::set_post_effects]
+///  - Sizes are in logical pixels
+#[derive(Clone, Debug, PartialEq)]
+pub enum PostEffect {
+    /// Darken the corners, `strength` is the opacity of the black at the corners
+    Vignette { strength: f64 },
+    /// Make the corners outside of a rounded rectangle transparent
+    RoundedCorners { radius: f64 },
+    /// A frame along the edges of the canvas
+    Border { color: CommandColor, width: f64 },
+    /// A text in the bottom right corner
+    Watermark {
+        text: String,
+        color: CommandColor,
+        font_size: f64,
+    },
+    /// A CSS filter applied to the whole frame, e.g. `grayscale(1)` or `contrast(1.2)`
+    ColorFilter(String),
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Set the effects applied, in order, to every frame on present before it is streamed
+    ///  - The effects are drawn on the canvas: a frame drawn over the previous one (dirty
+    ///    regions, incremental series) gets them again, use them with charts redrawn in full
+    ///  - The debug overlays are drawn on top of the effects
+    pub fn set_post_effects(&mut self, effects: Vec<PostEffect>) {
+        self.post_effects = effects;
+    }
+
+    pub fn add_post_effect(&mut self, effect: PostEffect) {
+        self.post_effects.push(effect);
+    }
+
+    pub fn post_effects(&self) -> &[PostEffect] {
+        &self.post_effects
+    }
+
+    pub fn clear_post_effects(&mut self) {
+        self.post_effects.clear();
+    }
+
+    /// Apply the effects to the frame, in physical pixels
+    pub(crate) fn apply_post_effects(&mut self) -> Result<(), DrawingErrorKind<CanvasError>> {
+        if self.post_effects.is_empty() || self.frame_stats().is_none() {
+            return Ok(());
+        }
+        let (w, h) = self.physical_size();
+        let (w, h) = (f64::from(w), f64::from(h));
+        let r = self.pixel_ratio;
+
+        self.context.save();
+        self.context.reset_transform().map_err(error_cast)?;
+        let result = self
+            .post_effects
+            .iter()
+            .try_for_each(|effect| self.apply_post_effect(effect, w, h, r));
+        self.context.restore();
+        result
+    }
+
+    fn apply_post_effect(
+        &self,
+        effect: &PostEffect,
+        w: f64,
+        h: f64,
+        r: f64,
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        let ctx = &self.context;
+        ctx.set_global_alpha(1.0);
+        let _ = ctx.set_global_composite_operation("source-over");
+        match effect {
+            PostEffect::Vignette { strength } => {
+                let radius = (w * w + h * h).sqrt() / 2.0;
+                let gradient = ctx
+                    .create_radial_gradient(
+                        w / 2.0,
+                        h / 2.0,
+                        radius * 0.5,
+                        w / 2.0,
+                        h / 2.0,
+                        radius,
+                    )
+                    .map_err(error_cast)?;
+                let edge = format!("rgba(0,0,0,{})", strength.max(0.0).min(1.0));
+                gradient
+                    .add_color_stop(0.0, "rgba(0,0,0,0)")
+                    .and_then(|_| gradient.add_color_stop(1.0, &edge))
+                    .map_err(error_cast)?;
+                set_fill_gradient(ctx, &gradient);
+                ctx.fill_rect(0.0, 0.0, w, h);
+            }
+            PostEffect::RoundedCorners { radius } => {
+                let radius = (radius * r).max(0.0).min(w.min(h) / 2.0);
+                let _ = ctx.set_global_composite_operation("destination-in");
+                set_fill_css(ctx, "#000");
+                ctx.begin_path();
+                ctx.move_to(radius, 0.0);
+                ctx.arc_to(w, 0.0, w, h, radius)
+                    .and_then(|_| ctx.arc_to(w, h, 0.0, h, radius))
+                    .and_then(|_| ctx.arc_to(0.0, h, 0.0, 0.0, radius))
+                    .and_then(|_| ctx.arc_to(0.0, 0.0, w, 0.0, radius))
+                    .map_err(error_cast)?;
+                ctx.close_path();
+                ctx.fill();
+            }
+            PostEffect::Border { color, width } => {
+                let width = width * r;
+                set_stroke_css(ctx, &make_canvas_color(color.to_backend_color()));
+                ctx.set_line_width(width);
+                let _ = ctx.set_line_dash(&js_sys::Array::new());
+                ctx.stroke_rect(width / 2.0, width / 2.0, w - width, h - width);
+            }
+            PostEffect::Watermark {
+                text,
+                color,
+                font_size,
+            } => {
+                let size = font_size * r;
+                set_fill_css(ctx, &make_canvas_color(color.to_backend_color()));
+                ctx.set_font(&format!("{}px sans-serif", size));
+                ctx.set_text_align("end");
+                ctx.set_text_baseline("bottom");
+                ctx.fill_text(text, w - size / 2.0, h - size / 2.0)
+                    .map_err(error_cast)?;
+            }
+            PostEffect::ColorFilter(filter) => {
+                let canvas = self.canvas();
+                with_scratch_canvas(w as u32, h as u32, |scratch, scratch_ctx| {
+                    scratch_ctx.draw_image_with_offscreen_canvas(canvas, 0.0, 0.0)?;
+                    ctx.clear_rect(0.0, 0.0, w, h);
+                    ctx.set_filter(filter);
+                    let result = ctx
+                        .draw_image_with_offscreen_canvas_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                            scratch, 0.0, 0.0, w, h, 0.0, 0.0, w, h,
+                        );
+                    ctx.set_filter("none");
+                    result
+                })
+                .map_err(DrawingErrorKind::DrawingError)?
+                .map_err(error_cast)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use plotters::prelude::*;
+    use plotters_backend::DrawingBackend;
+    use wasm_bindgen_test::*;
+    use web_sys::OffscreenCanvas;
+
+    #[wasm_bindgen_test]
+    fn test_post_effects() {
+        let canvas = OffscreenCanvas::new(40, 40).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        backend.set_post_effects(vec![
+            PostEffect::RoundedCorners { radius: 20.0 },
+            PostEffect::Border {
+                color: BLUE.to_backend_color().into(),
+                width: 2.0,
+            },
+        ]);
+        backend.draw_rect((0, 0), (40, 40), &RED, true).unwrap();
+        backend.present().unwrap();
+
+        // The border is drawn over the cut corners
+        assert_eq!(backend.pick_color(20, 0).unwrap().rgb, (0, 0, 255));
+        assert_eq!(backend.pick_color(20, 20).unwrap().rgb, (255, 0, 0));
+        assert_eq!(backend.pick_color(4, 4).unwrap().alpha, 0.0);
+    }
+}
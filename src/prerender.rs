@@ -0,0 +1,201 @@
+use web_sys::{ImageBitmap, OffscreenCanvas};
+
+use crate::canvas::{context_2d, js_error_in, CanvasError, OffscreenCanvasBackend};
+use crate::time::{default_time_source, SharedTimeSource};
+use crate::viewport::Viewport;
+
+/// The navigation steps anticipated around the current viewport
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PrerenderSteps {
+    /// Pan distance, as a fraction of the visible x and y ranges
+    pub pan: f64,
+    /// Zoom factor, the ranges are divided (zoom in) and multiplied (zoom out) by it
+    pub zoom: f64,
+    /// Also anticipate panning along the y axis
+    pub pan_y: bool,
+}
+
+impl Default for PrerenderSteps {
+    fn default() -> Self {
+        Self {
+            pan: 0.5,
+            zoom: 2.0,
+            pan_y: false,
+        }
+    }
+}
+
+impl PrerenderSteps {
+    /// The viewports one step away from `viewport`, the most likely first
+    pub fn neighbors(&self, viewport: &Viewport) -> Vec<Viewport> {
+        let shift = |(a, b): (f64, f64), d: f64| {
+            let span = b - a;
+            (a + span * d, b + span * d)
+        };
+        let scale = |(a, b): (f64, f64), k: f64| {
+            let (center, half) = ((a + b) / 2.0, (b - a) / 2.0 * k);
+            (center - half, center + half)
+        };
+        let with_x = |x_range| Viewport {
+            x_range,
+            ..*viewport
+        };
+
+        let mut neighbors = vec![
+            with_x(shift(viewport.x_range, self.pan)),
+            with_x(shift(viewport.x_range, -self.pan)),
+        ];
+        if self.pan_y {
+            for d in [self.pan, -self.pan].iter() {
+                neighbors.push(Viewport {
+                    y_range: shift(viewport.y_range, *d),
+                    ..*viewport
+                });
+            }
+        }
+        if self.zoom > 0.0 && self.zoom != 1.0 {
+            neighbors.push(with_x(scale(viewport.x_range, 1.0 / self.zoom)));
+            neighbors.push(with_x(scale(viewport.x_range, self.zoom)));
+        }
+        neighbors
+    }
+}
+
+/// The function rendering the chart for a viewport
+pub type ViewportRenderer =
+    Box<dyn FnMut(&Viewport, &mut OffscreenCanvasBackend) -> Result<(), CanvasError>>;
+
+/// Speculative renderer filling a cache with the viewports around the current one during idle
+/// time, so that the next pan or zoom step can be shown immediately
+pub struct Prerenderer {
+    canvas: OffscreenCanvas,
+    render: ViewportRenderer,
+    steps: PrerenderSteps,
+    capacity: usize,
+    /// Oldest first
+    tiles: Vec<(Viewport, ImageBitmap)>,
+    pending: Vec<Viewport>,
+    time_source: SharedTimeSource,
+}
+
+impl Prerenderer {
+    /// Create a prerenderer for a `width` x `height` chart keeping up to `capacity` bitmaps
+    pub fn new<F>(width: u32, height: u32, capacity: usize, render: F) -> Result<Self, CanvasError>
+    where
+        F: FnMut(&Viewport, &mut OffscreenCanvasBackend) -> Result<(), CanvasError> + 'static,
+    {
+        let canvas =
+            OffscreenCanvas::new(width, height).map_err(js_error_in("OffscreenCanvas::new"))?;
+        Ok(Self {
+            canvas,
+            render: Box::new(render),
+            steps: PrerenderSteps::default(),
+            capacity: capacity.max(1),
+            tiles: vec![],
+            pending: vec![],
+            time_source: default_time_source(),
+        })
+    }
+
+    pub fn set_steps(&mut self, steps: PrerenderSteps) {
+        self.steps = steps;
+    }
+
+    pub fn set_time_source(&mut self, source: SharedTimeSource) {
+        self.time_source = source;
+    }
+
+    /// The user moved to `viewport`, queue its neighbors which are not cached yet
+    pub fn set_viewport(&mut self, viewport: &Viewport) {
+        self.pending = self
+            .steps
+            .neighbors(viewport)
+            .into_iter()
+            .filter(|v| !self.tiles.iter().any(|(t, _)| t == v))
+            .collect();
+    }
+
+    /// Viewports still to be prerendered
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_cached(&self, viewport: &Viewport) -> bool {
+        self.tiles.iter().any(|(t, _)| t == viewport)
+    }
+
+    /// Take the prerendered bitmap of a viewport, None if it wasn't prerendered
+    pub fn take(&mut self, viewport: &Viewport) -> Option<ImageBitmap> {
+        let index = self.tiles.iter().position(|(t, _)| t == viewport)?;
+        Some(self.tiles.remove(index).1)
+    }
+
+    /// Prerender queued viewports until `budget_ms` is spent, call it when the chart is idle
+    ///  - Return the number of viewports rendered
+    pub fn run_for(&mut self, budget_ms: f64) -> Result<usize, CanvasError> {
+        let start = self.time_source.now_ms();
+        let mut count = 0;
+        while !self.pending.is_empty() && self.time_source.now_ms() - start < budget_ms {
+            let viewport = self.pending.remove(0);
+            self.prerender(viewport)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    fn prerender(&mut self, viewport: Viewport) -> Result<(), CanvasError> {
+        let context = context_2d(&self.canvas)?;
+        context.clear_rect(
+            0.0,
+            0.0,
+            f64::from(self.canvas.width()),
+            f64::from(self.canvas.height()),
+        );
+        let mut backend = OffscreenCanvasBackend::with_context(&self.canvas, context);
+        (self.render)(&viewport, &mut backend)?;
+        drop(backend);
+        let bitmap = self
+            .canvas
+            .transfer_to_image_bitmap()
+            .map_err(js_error_in("transferToImageBitmap"))?;
+
+        if self.tiles.len() >= self.capacity {
+            self.tiles.remove(0).1.close();
+        }
+        self.tiles.push((viewport, bitmap));
+        Ok(())
+    }
+
+    /// Drop the cached bitmaps, releasing their memory, e.g. when the data changed
+    pub fn invalidate(&mut self) {
+        for (_, bitmap) in self.tiles.drain(..) {
+            bitmap.close();
+        }
+        self.pending.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::geometry::Rect;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_prerender_neighbors() {
+        let area = Rect::new(0.0, 0.0, 100.0, 100.0);
+        let viewport = Viewport::new((0.0, 10.0), (0.0, 1.0), area);
+        let neighbors = PrerenderSteps::default().neighbors(&viewport);
+        assert_eq!(neighbors[0].x_range, (5.0, 15.0));
+        assert_eq!(neighbors[1].x_range, (-5.0, 5.0));
+        assert_eq!(neighbors[2].x_range, (2.5, 7.5));
+        assert_eq!(neighbors[3].x_range, (-5.0, 15.0));
+
+        let mut prerenderer = Prerenderer::new(100, 100, 8, |_, _| Ok(())).unwrap();
+        prerenderer.set_viewport(&viewport);
+        assert_eq!(prerenderer.pending(), 4);
+        assert_eq!(prerenderer.run_for(f64::INFINITY).unwrap(), 4);
+        assert!(prerenderer.take(&neighbors[0]).is_some());
+        assert!(!prerenderer.is_cached(&neighbors[0]));
+    }
+}
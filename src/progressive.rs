@@ -0,0 +1,100 @@
+use crate::chunked::{CancellationToken, ChunkStatus};
+
+/// Render a huge dataset coarse first, then refine it over the next idle slices
+///
+/// Every pass redraws the whole chart with a smaller stride over the data (the first pass keeps
+/// about `coarse_points` points, each following pass doubles the detail), until the full
+/// resolution pass is rendered.
+pub struct ProgressiveRender {
+    stride: usize,
+    finished: bool,
+    token: CancellationToken,
+}
+
+impl ProgressiveRender {
+    pub fn new(point_count: usize, coarse_points: usize, token: CancellationToken) -> Self {
+        let coarse_points = coarse_points.max(1);
+        let mut stride = 1;
+        while point_count / stride > coarse_points {
+            stride *= 2;
+        }
+        Self {
+            stride,
+            finished: false,
+            token,
+        }
+    }
+
+    /// The stride the next pass is going to use, None if the render is complete or cancelled
+    pub fn next_stride(&self) -> Option<usize> {
+        if self.finished || self.token.is_cancelled() {
+            None
+        } else {
+            Some(self.stride)
+        }
+    }
+
+    /// Run the next refinement pass, `render` gets the stride to decimate the data with
+    pub fn run_pass<E, F: FnOnce(usize) -> Result<(), E>>(
+        &mut self,
+        render: F,
+    ) -> Result<ChunkStatus, E> {
+        if self.token.is_cancelled() {
+            return Ok(ChunkStatus::Cancelled);
+        }
+        if self.finished {
+            return Ok(ChunkStatus::Done);
+        }
+
+        render(self.stride)?;
+
+        if self.stride == 1 {
+            self.finished = true;
+            Ok(ChunkStatus::Done)
+        } else {
+            self.stride /= 2;
+            Ok(ChunkStatus::Pending)
+        }
+    }
+}
+
+/// Every `stride`-th item of the data, always including the last one so the series keeps its extent
+pub fn decimated<T>(data: &[T], stride: usize) -> impl Iterator<Item = &T> {
+    let stride = stride.max(1);
+    let last = data.len().checked_sub(1);
+    data.iter()
+        .enumerate()
+        .filter(move |(i, _)| i % stride == 0 || Some(*i) == last)
+        .map(|(_, v)| v)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_progressive_strides() {
+        let mut render = ProgressiveRender::new(1000, 100, CancellationToken::new());
+        let mut strides = vec![];
+        loop {
+            let status = render
+                .run_pass(|stride| -> Result<(), ()> {
+                    strides.push(stride);
+                    Ok(())
+                })
+                .unwrap();
+            if status == ChunkStatus::Done {
+                break;
+            }
+        }
+        assert_eq!(strides, vec![16, 8, 4, 2, 1]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_decimated_keeps_last() {
+        let data: Vec<_> = (0..10).collect();
+        let picked: Vec<_> = decimated(&data, 4).cloned().collect();
+        assert_eq!(picked, vec![0, 4, 8, 9]);
+    }
+}
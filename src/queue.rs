@@ -0,0 +1,193 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::time::{default_time_source, SharedTimeSource};
+
+/// Priority of a render request, higher priorities are rendered first
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RenderPriority {
+    /// Speculative renders of charts which are not on the screen yet
+    Prerender,
+    /// Charts which are currently scrolled out of the viewport
+    Offscreen,
+    /// Charts inside the viewport
+    Visible,
+    /// The chart the user is interacting with
+    Interactive,
+}
+
+struct Entry<J> {
+    priority: RenderPriority,
+    seq: u64,
+    key: Option<String>,
+    job: J,
+}
+
+impl<J> PartialEq for Entry<J> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority && self.seq == other.seq
+    }
+}
+
+impl<J> Eq for Entry<J> {}
+
+impl<J> PartialOrd for Entry<J> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<J> Ord for Entry<J> {
+    // Highest priority first, then first-in first-out
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+/// A queue of render jobs ordered by priority
+///
+/// Jobs pushed with a key replace the pending job with the same key, so a chart which is refreshed
+/// several times before it gets rendered is only drawn once, with the latest request.
+pub struct RenderQueue<J> {
+    heap: BinaryHeap<Entry<J>>,
+    next_seq: u64,
+    time_source: SharedTimeSource,
+}
+
+impl<J> Default for RenderQueue<J> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<J> RenderQueue<J> {
+    pub fn new() -> Self {
+        Self {
+            heap: BinaryHeap::new(),
+            next_seq: 0,
+            time_source: default_time_source(),
+        }
+    }
+
+    /// Set the clock run_for() measures its budget with
+    pub fn set_time_source(&mut self, source: SharedTimeSource) {
+        self.time_source = source;
+    }
+
+    /// Queue an anonymous job
+    pub fn push(&mut self, job: J, priority: RenderPriority) {
+        self.push_entry(None, job, priority);
+    }
+
+    /// Queue a job for the chart identified by `key`, superseding its pending job if any
+    pub fn push_keyed(&mut self, key: &str, job: J, priority: RenderPriority) {
+        if self.heap.iter().any(|e| e.key.as_deref() == Some(key)) {
+            self.heap.retain(|e| e.key.as_deref() != Some(key));
+        }
+        self.push_entry(Some(key.to_string()), job, priority);
+    }
+
+    fn push_entry(&mut self, key: Option<String>, job: J, priority: RenderPriority) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.heap.push(Entry {
+            priority,
+            seq,
+            key,
+            job,
+        });
+    }
+
+    /// Change the priority of the pending job of a chart, e.g. when it is scrolled into view
+    ///  - Return false if there's no pending job with the key
+    pub fn set_priority(&mut self, key: &str, priority: RenderPriority) -> bool {
+        let mut entries = std::mem::take(&mut self.heap).into_vec();
+        let mut found = false;
+        for e in entries.iter_mut() {
+            if e.key.as_deref() == Some(key) {
+                e.priority = priority;
+                found = true;
+            }
+        }
+        self.heap = BinaryHeap::from(entries);
+        found
+    }
+
+    /// Drop the pending job of a chart
+    pub fn remove(&mut self, key: &str) {
+        self.heap.retain(|e| e.key.as_deref() != Some(key));
+    }
+
+    /// Take the job which should be rendered next
+    pub fn pop(&mut self) -> Option<J> {
+        self.heap.pop().map(|e| e.job)
+    }
+
+    /// The job which would be rendered next
+    pub fn peek(&self) -> Option<&J> {
+        self.heap.peek().map(|e| &e.job)
+    }
+
+    /// The priority of the job which would be rendered next
+    pub fn peek_priority(&self) -> Option<RenderPriority> {
+        self.heap.peek().map(|e| e.priority)
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Render jobs in priority order until the queue is empty or `budget_ms` is spent
+    ///  - At least one job is rendered per call, so the queue always makes progress
+    ///  - Return the number of jobs rendered
+    pub fn run_for<F: FnMut(J)>(&mut self, budget_ms: f64, mut render: F) -> usize {
+        let start = self.time_source.now_ms();
+        let mut count = 0;
+        while let Some(job) = self.pop() {
+            render(job);
+            count += 1;
+            if self.time_source.now_ms() - start >= budget_ms {
+                break;
+            }
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_priority_order() {
+        let mut queue = RenderQueue::new();
+        queue.push(1, RenderPriority::Prerender);
+        queue.push(2, RenderPriority::Visible);
+        queue.push(3, RenderPriority::Visible);
+        queue.push(4, RenderPriority::Interactive);
+
+        let order: Vec<_> = std::iter::from_fn(|| queue.pop()).collect();
+        assert_eq!(order, vec![4, 2, 3, 1]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_keyed_jobs() {
+        let mut queue = RenderQueue::new();
+        queue.push_keyed("a", 1, RenderPriority::Offscreen);
+        queue.push_keyed("b", 2, RenderPriority::Visible);
+        queue.push_keyed("a", 3, RenderPriority::Offscreen);
+        assert_eq!(queue.len(), 2);
+
+        assert!(queue.set_priority("a", RenderPriority::Interactive));
+        assert_eq!(queue.pop(), Some(3));
+        assert_eq!(queue.pop(), Some(2));
+        assert_eq!(queue.pop(), None);
+    }
+}
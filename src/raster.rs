@@ -0,0 +1,252 @@
+use plotters_backend::{BackendCoord, DrawingErrorKind};
+use wasm_bindgen::Clamped;
+use web_sys::ImageData;
+
+use crate::canvas::{error_cast, CanvasError, OffscreenCanvasBackend};
+use crate::chunked::ChunkStatus;
+use crate::geometry::Rect;
+use crate::scratch::with_scratch_canvas;
+use crate::stats::PrimitiveKind;
+
+/// Band height used by put_rgba(), large enough to keep the number of calls low and small
+/// enough for put_image_data to stay cheap on wide images
+pub const DEFAULT_BAND_HEIGHT: u32 = 64;
+
+/// An RGBA buffer uploaded to the canvas in horizontal bands, which can be spread across
+/// several frames to keep each of them under budget
+pub struct BandedUpload {
+    data: Vec<u8>,
+    width: u32,
+    height: u32,
+    pos: BackendCoord,
+    band_height: u32,
+    next_row: u32,
+}
+
+impl BandedUpload {
+    /// Prepare the upload of `data` (`width` x `height` RGBA pixels) with its upper left corner
+    /// at `pos`, in physical pixels
+    pub fn new(
+        data: Vec<u8>,
+        width: u32,
+        height: u32,
+        pos: BackendCoord,
+        band_height: u32,
+    ) -> Result<Self, CanvasError> {
+        if data.len() != width as usize * height as usize * 4 {
+            return Err(CanvasError::InvalidInput(format!(
+                "{} bytes given for a {}x{} RGBA image",
+                data.len(),
+                width,
+                height
+            )));
+        }
+        Ok(Self {
+            data,
+            width,
+            height,
+            pos,
+            band_height: band_height.max(1),
+            next_row: 0,
+        })
+    }
+
+    pub fn bands_left(&self) -> u32 {
+        let rows = self.height - self.next_row;
+        (rows + self.band_height - 1) / self.band_height
+    }
+
+    /// Upload at most `max_bands` bands
+    pub fn upload(
+        &mut self,
+        backend: &mut OffscreenCanvasBackend,
+        max_bands: usize,
+    ) -> Result<ChunkStatus, DrawingErrorKind<CanvasError>> {
+        self.upload_while(backend, |n| n < max_bands)
+    }
+
+    /// Upload bands as long as `keep_going(bands_uploaded_so_far)` returns true
+    pub fn upload_while<C: FnMut(usize) -> bool>(
+        &mut self,
+        backend: &mut OffscreenCanvasBackend,
+        mut keep_going: C,
+    ) -> Result<ChunkStatus, DrawingErrorKind<CanvasError>> {
+        let mut uploaded = 0;
+        while self.next_row < self.height {
+            if !keep_going(uploaded) {
+                return Ok(ChunkStatus::Pending);
+            }
+            let rows = self.band_height.min(self.height - self.next_row);
+            let row_bytes = self.width as usize * 4;
+            let start = self.next_row as usize * row_bytes;
+            let end = start + rows as usize * row_bytes;
+            backend.put_band(
+                &self.data[start..end],
+                self.width,
+                rows,
+                (self.pos.0, self.pos.1 + self.next_row as i32),
+            )?;
+            self.next_row += rows;
+            uploaded += 1;
+        }
+        Ok(ChunkStatus::Done)
+    }
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Copy an RGBA buffer (`width` x `height` pixels) to the canvas at `pos`, in physical pixels
+    ///  - The upload is split into bands of `DEFAULT_BAND_HEIGHT` rows, use `BandedUpload` to
+    ///    choose the band height or to spread the bands across frames
+    pub fn put_rgba(
+        &mut self,
+        data: Vec<u8>,
+        width: u32,
+        height: u32,
+        pos: BackendCoord,
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        let mut upload = BandedUpload::new(data, width, height, pos, DEFAULT_BAND_HEIGHT)
+            .map_err(DrawingErrorKind::DrawingError)?;
+        upload.upload_while(self, |_| true)?;
+        Ok(())
+    }
+
+    /// Draw an RGB or RGBA bitmap with its upper left corner at `pos`, as plotters' blit_bitmap
+    pub(crate) fn execute_bitmap(
+        &mut self,
+        pos: BackendCoord,
+        (width, height): (u32, u32),
+        src: &[u8],
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        let pixels = width as usize * height as usize;
+        let rgba = if src.len() == pixels * 4 {
+            src.to_vec()
+        } else if src.len() == pixels * 3 {
+            let mut rgba = Vec::with_capacity(pixels * 4);
+            for rgb in src.chunks_exact(3) {
+                rgba.extend_from_slice(rgb);
+                rgba.push(255);
+            }
+            rgba
+        } else {
+            return Err(DrawingErrorKind::DrawingError(CanvasError::InvalidInput(
+                format!(
+                    "{} bytes given for a {}x{} bitmap",
+                    src.len(),
+                    width,
+                    height
+                ),
+            )));
+        };
+        if pixels == 0 {
+            return Ok(());
+        }
+
+        let (x, y) = match self.coord(pos)? {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+        let bounds = Rect::new(x, y, x + f64::from(width), y + f64::from(height));
+        if !self.begin_primitive(PrimitiveKind::Bitmap, bounds) {
+            return Ok(());
+        }
+        let image = ImageData::new_with_u8_clamped_array_and_sh(Clamped(&rgba), width, height)
+            .map_err(error_cast)?;
+
+        // put_image_data ignores the transform, go through a scratch canvas when the backend
+        // is scaled so that the bitmap keeps its logical size
+        if self.scale_factors() == (1.0, 1.0) {
+            return self
+                .context
+                .put_image_data(&image, x, y)
+                .map_err(error_cast);
+        }
+        let context = &self.context;
+        with_scratch_canvas(width, height, |scratch, scratch_context| {
+            scratch_context.put_image_data(&image, 0.0, 0.0)?;
+            context.draw_image_with_offscreen_canvas_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                scratch,
+                0.0,
+                0.0,
+                f64::from(width),
+                f64::from(height),
+                x,
+                y,
+                f64::from(width),
+                f64::from(height),
+            )
+        })
+        .map_err(DrawingErrorKind::DrawingError)?
+        .map_err(error_cast)
+    }
+
+    fn put_band(
+        &mut self,
+        data: &[u8],
+        width: u32,
+        rows: u32,
+        pos: BackendCoord,
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        // put_image_data ignores the transform, the bounds are converted back to logical pixels
+        let (sx, sy) = self.scale_factors();
+        let (x, y) = match self.coord(pos)? {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+        let bounds = Rect::new(
+            x / sx,
+            y / sy,
+            (x + f64::from(width)) / sx,
+            (y + f64::from(rows)) / sy,
+        );
+        if !self.begin_primitive(PrimitiveKind::Image, bounds) {
+            return Ok(());
+        }
+        let image = ImageData::new_with_u8_clamped_array_and_sh(Clamped(data), width, rows)
+            .map_err(error_cast)?;
+        self.context
+            .put_image_data(&image, x, y)
+            .map_err(error_cast)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+    use web_sys::OffscreenCanvas;
+
+    #[wasm_bindgen_test]
+    fn test_banded_upload() {
+        let canvas = OffscreenCanvas::new(100, 100).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+
+        let mut upload = BandedUpload::new(vec![255; 100 * 50 * 4], 100, 50, (0, 0), 16).unwrap();
+        assert_eq!(upload.bands_left(), 4);
+        assert_eq!(
+            upload.upload(&mut backend, 3).unwrap(),
+            ChunkStatus::Pending
+        );
+        assert_eq!(upload.bands_left(), 1);
+        assert_eq!(upload.upload(&mut backend, 3).unwrap(), ChunkStatus::Done);
+
+        assert!(BandedUpload::new(vec![0; 10], 100, 50, (0, 0), 16).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_blit_rgb_bitmap() {
+        use plotters_backend::DrawingBackend;
+
+        let canvas = OffscreenCanvas::new(100, 100).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+
+        backend
+            .blit_bitmap((10, 10), (4, 2), &[128; 4 * 2 * 3])
+            .unwrap();
+        assert!(backend.blit_bitmap((10, 10), (4, 2), &[0; 5]).is_err());
+        assert_eq!(
+            backend.frame_stats().unwrap().count(PrimitiveKind::Bitmap),
+            1
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use web_sys::{OffscreenCanvas, OffscreenCanvasRenderingContext2d};
+
+use crate::canvas::{js_error, js_error_in, CanvasError, OffscreenCanvasBackend};
+use crate::command::CommandColor;
+use crate::context::{context_with_options, ContextOptions};
+use crate::geometry::Rect;
+use crate::warn::warn_once;
+
+/// Readbacks after which a context without the willReadFrequently hint is reported
+pub const READBACK_HINT_THRESHOLD: u32 = 3;
+
+/// Get the 2d context of a canvas with the willReadFrequently hint, which keeps the bitmap in
+/// memory the CPU can read
+///  - The hint is only honored by the first getContext call of a canvas
+fn read_frequently_context(
+    canvas: &OffscreenCanvas,
+) -> Result<OffscreenCanvasRenderingContext2d, CanvasError> {
+    context_with_options(canvas, &ContextOptions::new().will_read_frequently(true))
+}
+
+/// Copy a canvas into a new one of the same size, whose context is optimized for readbacks
+///  - Use it when a backend reports it should read frequently: the canvas already has a
+///    context, which can't get the hint anymore. Create the backend on the returned canvas
+///    with `OffscreenCanvasBackend::new_read_frequently`
+pub fn migrate_to_read_frequently(
+    canvas: &OffscreenCanvas,
+) -> Result<OffscreenCanvas, CanvasError> {
+    let target = OffscreenCanvas::new(canvas.width(), canvas.height())
+        .map_err(js_error_in("OffscreenCanvas::new"))?;
+    read_frequently_context(&target)?
+        .draw_image_with_offscreen_canvas(canvas, 0.0, 0.0)
+        .map_err(js_error)?;
+    Ok(target)
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Create a backend whose context is optimized for pixel readbacks (pick_color, ...)
+    ///  - Drawing may be slower, as the canvas is not GPU accelerated anymore
+    pub fn new_read_frequently(canvas: &'a OffscreenCanvas) -> Option<Self> {
+        Self::new_with_options(canvas, &ContextOptions::new().will_read_frequently(true))
+    }
+
+    /// The color of the pixel at (x, y), in logical pixels
+    pub fn pick_color(&mut self, x: i32, y: i32) -> Result<CommandColor, CanvasError> {
+        self.flush_batch();
+        let (sx, sy) = self.scale_factors();
+        let data = self
+            .context
+            .get_image_data(
+                (f64::from(x) * sx).floor(),
+                (f64::from(y) * sy).floor(),
+                1.0,
+                1.0,
+            )
+            .map_err(|e| CanvasError::Readback(js_error(e).message().to_string()))?
+            .data();
+        self.note_readback();
+        Ok(CommandColor {
+            rgb: (data[0], data[1], data[2]),
+            alpha: f64::from(data[3]) / 255.0,
+        })
+    }
+
+    /// The RGBA bytes of an area, row by row, e.g. for assertions or to upload the chart
+    ///  - `area` is in logical pixels, None reads the whole canvas
+    ///  - The bytes are in physical pixels: the area is expanded to whole physical pixels,
+    ///    `ceil(right * sx) - floor(left * sx)` per row with `sx` the horizontal scale. Pixels
+    ///    outside of the canvas are transparent
+    pub fn read_pixels(&mut self, area: Option<Rect>) -> Result<Vec<u8>, CanvasError> {
+        self.flush_batch();
+        let (x, y, width, height) = match area {
+            Some(area) => {
+                let (sx, sy) = self.scale_factors();
+                let (left, top) = ((area.left * sx).floor(), (area.top * sy).floor());
+                let (right, bottom) = ((area.right * sx).ceil(), (area.bottom * sy).ceil());
+                (left, top, right - left, bottom - top)
+            }
+            None => {
+                let (width, height) = self.physical_size();
+                (0.0, 0.0, f64::from(width), f64::from(height))
+            }
+        };
+        if !(width >= 1.0 && height >= 1.0) {
+            return Err(CanvasError::InvalidInput(format!(
+                "cannot read an area of {}x{} pixels",
+                width, height
+            )));
+        }
+
+        let data = self
+            .context
+            .get_image_data(x, y, width, height)
+            .map_err(|e| CanvasError::Readback(js_error(e).message().to_string()))?
+            .data();
+        self.note_readback();
+        Ok(data.0)
+    }
+
+    /// Count a readback, and warn once when they are frequent on a GPU backed context
+    pub(crate) fn note_readback(&mut self) {
+        self.readbacks += 1;
+        if self.should_read_frequently() {
+            warn_once(
+                "frequent pixel readbacks on a canvas without willReadFrequently, \
+                 use migrate_to_read_frequently() to speed them up",
+            );
+        }
+    }
+
+    pub fn readback_count(&self) -> u32 {
+        self.readbacks
+    }
+
+    /// Readbacks are frequent enough that a willReadFrequently context would be faster
+    pub fn should_read_frequently(&self) -> bool {
+        !self.read_frequently && self.readbacks >= READBACK_HINT_THRESHOLD
+    }
+
+    pub fn is_read_frequently(&self) -> bool {
+        self.read_frequently
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use plotters::prelude::*;
+    use plotters_backend::DrawingBackend;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_readback_hint() {
+        let canvas = OffscreenCanvas::new(10, 10).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        backend.draw_pixel((1, 1), RED.to_backend_color()).unwrap();
+
+        assert_eq!(backend.pick_color(1, 1).unwrap().rgb, (255, 0, 0));
+        assert!(!backend.should_read_frequently());
+        backend.pick_color(1, 1).unwrap();
+        backend.pick_color(1, 1).unwrap();
+        assert!(backend.should_read_frequently());
+
+        let migrated = migrate_to_read_frequently(&canvas).unwrap();
+        let mut backend =
+            OffscreenCanvasBackend::new_read_frequently(&migrated).expect("cannot find canvas");
+        assert_eq!(backend.pick_color(1, 1).unwrap().rgb, (255, 0, 0));
+        assert!(backend.is_read_frequently());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_read_pixels() {
+        let canvas = OffscreenCanvas::new(4, 2).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        backend.draw_pixel((1, 0), RED.to_backend_color()).unwrap();
+
+        let all = backend.read_pixels(None).unwrap();
+        assert_eq!(all.len(), 4 * 2 * 4);
+        assert_eq!(all[4..8], [255, 0, 0, 255]);
+
+        let area = backend
+            .read_pixels(Some(Rect::new(1.0, 0.0, 2.0, 1.0)))
+            .unwrap();
+        assert_eq!(area, vec![255, 0, 0, 255]);
+        assert!(backend
+            .read_pixels(Some(Rect::new(1.0, 0.0, 1.0, 1.0)))
+            .is_err());
+    }
+}
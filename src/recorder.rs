@@ -0,0 +1,166 @@
+use std::cell::RefCell;
+use std::fmt::Write;
+use std::rc::Rc;
+
+use crate::canvas::OffscreenCanvasBackend;
+use crate::command::DrawCommand;
+use crate::middleware::{Middleware, MiddlewareAction};
+use crate::observer::DrawObserver;
+use crate::sliding::RingBuffer;
+use crate::stats::FrameStats;
+
+/// The commands of a presented frame
+#[derive(Clone, Debug)]
+pub struct RecordedFrame {
+    /// Number of frames recorded before this one
+    pub index: u64,
+    pub duration_ms: f64,
+    pub commands: Vec<DrawCommand>,
+}
+
+struct RecorderState {
+    frames: RingBuffer<RecordedFrame>,
+    current: Vec<DrawCommand>,
+    next_index: u64,
+    paused: bool,
+}
+
+/// Flight recorder keeping the command streams of the last frames
+///
+/// Installed on a backend, the recorder copies every command going through the middleware
+/// chain and closes a frame on every present. Only the last `capacity` frames are kept, so it
+/// can stay enabled in production and be dumped when a rendering glitch is reported.
+///  - Clones share the same recording
+#[derive(Clone)]
+pub struct FrameRecorder {
+    state: Rc<RefCell<RecorderState>>,
+}
+
+struct RecordCommands(Rc<RefCell<RecorderState>>);
+
+impl Middleware for RecordCommands {
+    fn handle(&mut self, command: &mut DrawCommand) -> MiddlewareAction {
+        let mut state = self.0.borrow_mut();
+        if !state.paused {
+            state.current.push(command.clone());
+        }
+        MiddlewareAction::Continue
+    }
+}
+
+struct CloseFrames(Rc<RefCell<RecorderState>>);
+
+impl DrawObserver for CloseFrames {
+    fn on_present(&mut self, _stats: &FrameStats, elapsed_ms: f64) {
+        let mut state = self.0.borrow_mut();
+        if state.paused {
+            return;
+        }
+        let frame = RecordedFrame {
+            index: state.next_index,
+            duration_ms: elapsed_ms,
+            commands: std::mem::take(&mut state.current),
+        };
+        state.next_index += 1;
+        state.frames.push(frame);
+    }
+}
+
+impl FrameRecorder {
+    /// Create a recorder keeping the last `capacity` frames
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            state: Rc::new(RefCell::new(RecorderState {
+                frames: RingBuffer::new(capacity),
+                current: vec![],
+                next_index: 0,
+                paused: false,
+            })),
+        }
+    }
+
+    /// Start recording the frames of `backend`
+    ///  - The recorder is appended to the middleware chain, so it sees the commands as the
+    ///    middlewares added before it left them
+    pub fn install(&self, backend: &mut OffscreenCanvasBackend) {
+        backend.add_middleware(RecordCommands(self.state.clone()));
+        backend.add_observer(CloseFrames(self.state.clone()));
+    }
+
+    /// Stop or resume recording, the recorded frames are kept
+    pub fn set_paused(&self, paused: bool) {
+        let mut state = self.state.borrow_mut();
+        state.paused = paused;
+        state.current.clear();
+    }
+
+    /// The recorded frames, oldest first
+    pub fn frames(&self) -> Vec<RecordedFrame> {
+        self.state.borrow().frames.iter().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.borrow().frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.state.borrow().frames.is_empty()
+    }
+
+    /// A text dump of the recorded frames, one command per line
+    pub fn dump(&self) -> String {
+        let state = self.state.borrow();
+        let mut out = String::new();
+        for frame in state.frames.iter() {
+            let _ = writeln!(
+                out,
+                "frame {} ({:.1}ms, {} commands)",
+                frame.index,
+                frame.duration_ms,
+                frame.commands.len()
+            );
+            for command in frame.commands.iter() {
+                let _ = writeln!(out, "  {:?}", command);
+            }
+        }
+        out
+    }
+
+    pub fn clear(&self) {
+        let mut state = self.state.borrow_mut();
+        state.frames.clear();
+        state.current.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use plotters::prelude::*;
+    use plotters_backend::DrawingBackend;
+    use wasm_bindgen_test::*;
+    use web_sys::OffscreenCanvas;
+
+    #[wasm_bindgen_test]
+    fn test_recorder_keeps_last_frames() {
+        let canvas = OffscreenCanvas::new(100, 100).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        let recorder = FrameRecorder::new(2);
+        recorder.install(&mut backend);
+
+        for i in 0..3 {
+            for _ in 0..=i {
+                backend
+                    .draw_pixel((i, i), BLACK.to_backend_color())
+                    .unwrap();
+            }
+            backend.present().unwrap();
+        }
+
+        let frames = recorder.frames();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].index, 1);
+        assert_eq!(frames[1].commands.len(), 3);
+        assert!(recorder.dump().starts_with("frame 1"));
+    }
+}
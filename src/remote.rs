@@ -0,0 +1,557 @@
+use js_sys::{Array, Int32Array, Object, Reflect, Uint8Array};
+use plotters_backend::text_anchor::{HPos, Pos, VPos};
+use plotters_backend::{
+    BackendColor, BackendCoord, BackendStyle, BackendTextStyle, DrawingBackend, DrawingErrorKind,
+    FontStyle, FontTransform,
+};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{MessageEvent, MessagePort, OffscreenCanvas, OffscreenCanvasRenderingContext2d};
+
+use crate::canvas::{context_2d, font_string, js_error_in, CanvasError, OffscreenCanvasBackend};
+use crate::command::{CommandColor, CommandStyle, DrawCommand, TextCommandStyle};
+use crate::replay::drawing_error;
+use crate::shared::SharedBackend;
+
+/// Append the fields of the commands to a flat array
+struct Writer(Array);
+
+impl Writer {
+    fn num(&self, v: f64) {
+        self.0.push(&JsValue::from(v));
+    }
+
+    fn coord(&self, (x, y): BackendCoord) {
+        self.num(f64::from(x));
+        self.num(f64::from(y));
+    }
+
+    fn color(&self, color: &CommandColor) {
+        let (r, g, b) = color.rgb;
+        self.num(f64::from(r));
+        self.num(f64::from(g));
+        self.num(f64::from(b));
+        self.num(color.alpha);
+    }
+
+    fn style(&self, style: &CommandStyle) {
+        self.color(&style.color);
+        self.num(f64::from(style.stroke_width));
+    }
+
+    fn points(&self, points: &[BackendCoord]) {
+        let mut flat = Vec::with_capacity(points.len() * 2);
+        for &(x, y) in points {
+            flat.push(x);
+            flat.push(y);
+        }
+        self.0.push(&Int32Array::from(flat.as_slice()));
+    }
+}
+
+/// Read the fields of the commands back, in the order they were written
+struct Reader {
+    items: Array,
+    next: u32,
+}
+
+impl Reader {
+    fn value(&mut self) -> Result<JsValue, CanvasError> {
+        if self.next >= self.items.length() {
+            return Err(CanvasError::InvalidInput(
+                "truncated remote command".to_string(),
+            ));
+        }
+        self.next += 1;
+        Ok(self.items.get(self.next - 1))
+    }
+
+    fn num(&mut self) -> Result<f64, CanvasError> {
+        self.value()?.as_f64().ok_or_else(|| {
+            CanvasError::InvalidInput("expected a number in a remote command".to_string())
+        })
+    }
+
+    fn string(&mut self) -> Result<String, CanvasError> {
+        self.value()?.as_string().ok_or_else(|| {
+            CanvasError::InvalidInput("expected a string in a remote command".to_string())
+        })
+    }
+
+    fn coord(&mut self) -> Result<BackendCoord, CanvasError> {
+        Ok((self.num()? as i32, self.num()? as i32))
+    }
+
+    fn color(&mut self) -> Result<CommandColor, CanvasError> {
+        Ok(CommandColor {
+            rgb: (self.num()? as u8, self.num()? as u8, self.num()? as u8),
+            alpha: self.num()?,
+        })
+    }
+
+    fn style(&mut self) -> Result<CommandStyle, CanvasError> {
+        Ok(CommandStyle {
+            color: self.color()?,
+            stroke_width: self.num()? as u32,
+        })
+    }
+
+    fn points(&mut self) -> Result<Vec<BackendCoord>, CanvasError> {
+        let flat: Int32Array = self.value()?.dyn_into().map_err(|_| {
+            CanvasError::InvalidInput("expected points in a remote command".to_string())
+        })?;
+        Ok(flat
+            .to_vec()
+            .chunks_exact(2)
+            .map(|p| (p[0], p[1]))
+            .collect())
+    }
+}
+
+fn h_pos_index(pos: HPos) -> f64 {
+    match pos {
+        HPos::Left => 0.0,
+        HPos::Center => 1.0,
+        HPos::Right => 2.0,
+    }
+}
+
+fn v_pos_index(pos: VPos) -> f64 {
+    match pos {
+        VPos::Top => 0.0,
+        VPos::Center => 1.0,
+        VPos::Bottom => 2.0,
+    }
+}
+
+fn transform_index(transform: &FontTransform) -> f64 {
+    match transform {
+        FontTransform::None => 0.0,
+        FontTransform::Rotate90 => 1.0,
+        FontTransform::Rotate180 => 2.0,
+        FontTransform::Rotate270 => 3.0,
+    }
+}
+
+/// Encode commands as a flat array which can be posted to another worker
+pub(crate) fn encode_commands(commands: &[DrawCommand]) -> Array {
+    let w = Writer(Array::new());
+    for command in commands {
+        w.0.push(&command.name().into());
+        match command {
+            DrawCommand::Pixel { point, color } => {
+                w.coord(*point);
+                w.color(color);
+            }
+            DrawCommand::Line { from, to, style } => {
+                w.coord(*from);
+                w.coord(*to);
+                w.style(style);
+            }
+            DrawCommand::Text { text, pos, style } => {
+                w.0.push(&text.as_str().into());
+                w.coord(*pos);
+                w.0.push(&style.family.as_str().into());
+                w.0.push(&style.style.as_str().into());
+                w.num(style.size);
+                w.color(&style.color);
+                w.num(h_pos_index(style.anchor.h_pos));
+                w.num(v_pos_index(style.anchor.v_pos));
+                w.num(transform_index(&style.transform));
+            }
+            DrawCommand::Rect {
+                upper_left,
+                bottom_right,
+                style,
+                fill,
+            } => {
+                w.coord(*upper_left);
+                w.coord(*bottom_right);
+                w.style(style);
+                w.0.push(&JsValue::from(*fill));
+            }
+            DrawCommand::Circle {
+                center,
+                radius,
+                style,
+                fill,
+            } => {
+                w.coord(*center);
+                w.num(f64::from(*radius));
+                w.style(style);
+                w.0.push(&JsValue::from(*fill));
+            }
+            DrawCommand::Path { points, style } | DrawCommand::Polygon { points, style } => {
+                w.points(points);
+                w.style(style);
+            }
+            DrawCommand::Bitmap { pos, size, data } => {
+                w.coord(*pos);
+                w.num(f64::from(size.0));
+                w.num(f64::from(size.1));
+                w.0.push(&Uint8Array::from(data.as_slice()));
+            }
+        }
+    }
+    w.0
+}
+
+/// Decode the commands of [encode_commands]
+pub(crate) fn decode_commands(items: Array) -> Result<Vec<DrawCommand>, CanvasError> {
+    let mut r = Reader { items, next: 0 };
+    let mut commands = vec![];
+    while r.next < r.items.length() {
+        let command = match r.string()?.as_str() {
+            "pixel" => DrawCommand::Pixel {
+                point: r.coord()?,
+                color: r.color()?,
+            },
+            "line" => DrawCommand::Line {
+                from: r.coord()?,
+                to: r.coord()?,
+                style: r.style()?,
+            },
+            "text" => {
+                let text = r.string()?;
+                let pos = r.coord()?;
+                let family = r.string()?;
+                let style = FontStyle::from(r.string()?.as_str());
+                let size = r.num()?;
+                let color = r.color()?;
+                let h_pos = match r.num()? as u8 {
+                    0 => HPos::Left,
+                    1 => HPos::Center,
+                    _ => HPos::Right,
+                };
+                let v_pos = match r.num()? as u8 {
+                    0 => VPos::Top,
+                    1 => VPos::Center,
+                    _ => VPos::Bottom,
+                };
+                let transform = match r.num()? as u8 {
+                    0 => FontTransform::None,
+                    1 => FontTransform::Rotate90,
+                    2 => FontTransform::Rotate180,
+                    _ => FontTransform::Rotate270,
+                };
+                DrawCommand::Text {
+                    text,
+                    pos,
+                    style: TextCommandStyle {
+                        family,
+                        style,
+                        size,
+                        color,
+                        anchor: Pos::new(h_pos, v_pos),
+                        transform,
+                    },
+                }
+            }
+            "rect" => DrawCommand::Rect {
+                upper_left: r.coord()?,
+                bottom_right: r.coord()?,
+                style: r.style()?,
+                fill: r.value()?.is_truthy(),
+            },
+            "circle" => DrawCommand::Circle {
+                center: r.coord()?,
+                radius: r.num()? as u32,
+                style: r.style()?,
+                fill: r.value()?.is_truthy(),
+            },
+            "path" => DrawCommand::Path {
+                points: r.points()?,
+                style: r.style()?,
+            },
+            "polygon" => DrawCommand::Polygon {
+                points: r.points()?,
+                style: r.style()?,
+            },
+            "bitmap" => DrawCommand::Bitmap {
+                pos: r.coord()?,
+                size: (r.num()? as u32, r.num()? as u32),
+                data: r
+                    .value()?
+                    .dyn_into::<Uint8Array>()
+                    .map_err(|_| CanvasError::InvalidInput("expected bitmap pixels".to_string()))?
+                    .to_vec(),
+            },
+            other => {
+                return Err(CanvasError::InvalidInput(format!(
+                    "unknown remote command {:?}",
+                    other
+                )))
+            }
+        };
+        commands.push(command);
+    }
+    Ok(commands)
+}
+
+/// A drawing backend rasterizing in another worker
+///
+/// The primitives are queued as commands and posted as one `{ commands }` message over the
+/// port on every present; the worker owning the canvas passes the port to
+/// [serve_remote_port], so the chart logic can live in one worker and the rasterization in
+/// another.
+///  - Only the color and width of the styles cross the port, the side channel of
+///    CanvasShapeStyle is local to a worker
+///  - Texts are measured on a local scratch canvas, with the fonts loaded in this worker
+pub struct RemoteBackend {
+    port: MessagePort,
+    size: (u32, u32),
+    pending: Vec<DrawCommand>,
+    measure: OffscreenCanvasRenderingContext2d,
+}
+
+impl RemoteBackend {
+    /// Draw on the `size` canvas at the other end of the port, in logical pixels
+    pub fn new(port: MessagePort, size: (u32, u32)) -> Result<Self, CanvasError> {
+        let scratch = OffscreenCanvas::new(1, 1).map_err(js_error_in("OffscreenCanvas::new"))?;
+        Ok(Self {
+            port,
+            size,
+            pending: vec![],
+            measure: context_2d(&scratch)?,
+        })
+    }
+
+    /// Follow a resize of the remote canvas
+    pub fn set_size(&mut self, size: (u32, u32)) {
+        self.size = size;
+    }
+
+    /// Number of commands waiting for the next present
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Post the queued commands
+    pub fn flush(&mut self) -> Result<(), CanvasError> {
+        let message = Object::new();
+        let commands = encode_commands(&self.pending);
+        let _ = Reflect::set(&message, &"commands".into(), &commands);
+        self.port
+            .post_message(&message)
+            .map_err(js_error_in("postMessage"))?;
+        self.pending.clear();
+        Ok(())
+    }
+
+    fn push(&mut self, command: DrawCommand) -> Result<(), DrawingErrorKind<CanvasError>> {
+        self.pending.push(command);
+        Ok(())
+    }
+}
+
+impl DrawingBackend for RemoteBackend {
+    type ErrorType = CanvasError;
+
+    fn get_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn ensure_prepared(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        Ok(())
+    }
+
+    fn present(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.flush().map_err(DrawingErrorKind::DrawingError)
+    }
+
+    fn draw_pixel(
+        &mut self,
+        point: BackendCoord,
+        color: BackendColor,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.push(DrawCommand::Pixel {
+            point,
+            color: color.into(),
+        })
+    }
+
+    fn draw_line<S: BackendStyle>(
+        &mut self,
+        from: BackendCoord,
+        to: BackendCoord,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.push(DrawCommand::Line {
+            from,
+            to,
+            style: CommandStyle::from_style(style),
+        })
+    }
+
+    fn draw_rect<S: BackendStyle>(
+        &mut self,
+        upper_left: BackendCoord,
+        bottom_right: BackendCoord,
+        style: &S,
+        fill: bool,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.push(DrawCommand::Rect {
+            upper_left,
+            bottom_right,
+            style: CommandStyle::from_style(style),
+            fill,
+        })
+    }
+
+    fn draw_path<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        path: I,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.push(DrawCommand::Path {
+            points: path.into_iter().collect(),
+            style: CommandStyle::from_style(style),
+        })
+    }
+
+    fn draw_circle<S: BackendStyle>(
+        &mut self,
+        center: BackendCoord,
+        radius: u32,
+        style: &S,
+        fill: bool,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.push(DrawCommand::Circle {
+            center,
+            radius,
+            style: CommandStyle::from_style(style),
+            fill,
+        })
+    }
+
+    fn fill_polygon<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        vert: I,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.push(DrawCommand::Polygon {
+            points: vert.into_iter().collect(),
+            style: CommandStyle::from_style(style),
+        })
+    }
+
+    fn draw_text<TStyle: BackendTextStyle>(
+        &mut self,
+        text: &str,
+        style: &TStyle,
+        pos: BackendCoord,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.push(DrawCommand::Text {
+            text: text.to_string(),
+            pos,
+            style: TextCommandStyle::from_style(style),
+        })
+    }
+
+    fn estimate_text_size<TStyle: BackendTextStyle>(
+        &self,
+        text: &str,
+        style: &TStyle,
+    ) -> Result<(u32, u32), DrawingErrorKind<Self::ErrorType>> {
+        self.measure.set_font(&font_string(style));
+        let metrics = self
+            .measure
+            .measure_text(text)
+            .map_err(|e| DrawingErrorKind::DrawingError(js_error_in("measureText")(e)))?;
+        Ok((metrics.width().ceil() as u32, style.size().ceil() as u32))
+    }
+
+    fn blit_bitmap(
+        &mut self,
+        pos: BackendCoord,
+        size: (u32, u32),
+        src: &[u8],
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.push(DrawCommand::Bitmap {
+            pos,
+            size,
+            data: src.to_vec(),
+        })
+    }
+}
+
+/// Draw a `{ commands }` message posted by a [RemoteBackend], then present the frame
+///  - The commands go through the middleware chain of the backend
+///  - Return the number of commands drawn
+pub fn execute_remote_message(
+    backend: &mut OffscreenCanvasBackend,
+    data: &JsValue,
+) -> Result<usize, CanvasError> {
+    let commands = Reflect::get(data, &"commands".into())
+        .ok()
+        .and_then(|v| v.dyn_into::<Array>().ok())
+        .ok_or_else(|| CanvasError::InvalidInput("not a remote frame message".to_string()))?;
+    let commands = decode_commands(commands)?;
+    for command in commands.iter() {
+        backend.submit(command.clone()).map_err(drawing_error)?;
+    }
+    backend.present().map_err(drawing_error)?;
+    Ok(commands.len())
+}
+
+/// Canvas side of a [RemoteBackend]: draw the frames posted on the port on `backend`
+///  - A frame failing is reported to `on_error`, the port keeps being served
+pub fn serve_remote_port<F: FnMut(CanvasError) + 'static>(
+    port: &MessagePort,
+    backend: SharedBackend,
+    mut on_error: F,
+) {
+    let onmessage = Closure::wrap(Box::new(move |event: MessageEvent| {
+        let result = execute_remote_message(&mut backend.borrow_mut(), &event.data());
+        if let Err(e) = result {
+            on_error(e);
+        }
+    }) as Box<dyn FnMut(MessageEvent)>);
+
+    port.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+    onmessage.forget();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_commands_round_trip() {
+        let style = CommandStyle {
+            color: CommandColor {
+                rgb: (1, 2, 3),
+                alpha: 0.5,
+            },
+            stroke_width: 2,
+        };
+        let commands = vec![
+            DrawCommand::Line {
+                from: (0, 1),
+                to: (-5, 7),
+                style,
+            },
+            DrawCommand::Polygon {
+                points: vec![(0, 0), (10, 0), (5, 5)],
+                style,
+            },
+            DrawCommand::Circle {
+                center: (4, 4),
+                radius: 3,
+                style,
+                fill: true,
+            },
+            DrawCommand::Bitmap {
+                pos: (1, 1),
+                size: (1, 1),
+                data: vec![9, 8, 7, 6],
+            },
+        ];
+        let decoded = decode_commands(encode_commands(&commands)).unwrap();
+        assert_eq!(format!("{:?}", decoded), format!("{:?}", commands));
+
+        let broken = Array::of2(&"line".into(), &JsValue::from(1.0));
+        assert!(decode_commands(broken).is_err());
+    }
+}
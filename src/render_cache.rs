@@ -0,0 +1,195 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use web_sys::{ImageBitmap, OffscreenCanvas};
+
+use crate::canvas::{context_2d, js_error, js_error_in, CanvasError, OffscreenCanvasBackend};
+
+/// Fingerprint of the data and options a chart is rendered from
+///  - Only stable within one build, don't persist it
+pub fn fingerprint_of<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Cache of whole rendered charts, keyed by a fingerprint of what they were rendered from
+///
+/// A dashboard refreshing on a timer can pass the fingerprint of the data it just fetched:
+/// when the chart was already rendered from the same data the cached bitmap is drawn instead
+/// of running the renderer again.
+pub struct RenderCache {
+    capacity: usize,
+    /// Least recently used first
+    entries: Vec<(u64, ImageBitmap)>,
+    hits: u64,
+    misses: u64,
+}
+
+impl RenderCache {
+    /// Create a cache keeping up to `capacity` bitmaps
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: vec![],
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Show the chart with the given fingerprint on the backend's canvas
+    ///  - On a hit the canvas is replaced by the cached bitmap, otherwise `render` draws the
+    ///    chart and a copy of the canvas is cached
+    ///  - Return true on a hit
+    pub fn render<'a, F>(
+        &mut self,
+        fingerprint: u64,
+        backend: &mut OffscreenCanvasBackend<'a>,
+        render: F,
+    ) -> Result<bool, CanvasError>
+    where
+        F: FnOnce(&mut OffscreenCanvasBackend<'a>) -> Result<(), CanvasError>,
+    {
+        let (width, height) = backend.physical_size();
+        if let Some(index) = self.position(fingerprint, width, height) {
+            let entry = self.entries.remove(index);
+            backend.present_bitmap(&entry.1)?;
+            self.entries.push(entry);
+            self.hits += 1;
+            return Ok(true);
+        }
+
+        self.misses += 1;
+        render(backend)?;
+        let bitmap = backend.snapshot()?;
+        if self.entries.len() >= self.capacity {
+            self.entries.remove(0).1.close();
+        }
+        self.entries.push((fingerprint, bitmap));
+        Ok(false)
+    }
+
+    /// The cached bitmap for the fingerprint, if it has the given size
+    fn position(&self, fingerprint: u64, width: u32, height: u32) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|(f, b)| *f == fingerprint && b.width() == width && b.height() == height)
+    }
+
+    pub fn contains(&self, fingerprint: u64) -> bool {
+        self.entries.iter().any(|(f, _)| *f == fingerprint)
+    }
+
+    /// Number of cached bitmaps
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of renders served from the cache, and rendered
+    pub fn hit_counts(&self) -> (u64, u64) {
+        (self.hits, self.misses)
+    }
+
+    /// Drop the bitmap of a fingerprint
+    pub fn invalidate(&mut self, fingerprint: u64) {
+        self.entries.retain(|(f, b)| {
+            let keep = *f != fingerprint;
+            if !keep {
+                b.close();
+            }
+            keep
+        });
+    }
+
+    /// Drop all the bitmaps, releasing their memory
+    pub fn clear(&mut self) {
+        for (_, bitmap) in self.entries.drain(..) {
+            bitmap.close();
+        }
+    }
+}
+
+/// Copy a canvas into a bitmap, leaving the canvas untouched
+fn snapshot(canvas: &OffscreenCanvas) -> Result<ImageBitmap, CanvasError> {
+    let copy = OffscreenCanvas::new(canvas.width(), canvas.height())
+        .map_err(js_error_in("OffscreenCanvas::new"))?;
+    context_2d(&copy)?
+        .draw_image_with_offscreen_canvas(canvas, 0.0, 0.0)
+        .map_err(js_error)?;
+    copy.transfer_to_image_bitmap()
+        .map_err(js_error_in("transferToImageBitmap"))
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Copy the current frame into a bitmap, e.g. as the background of interactive overlays
+    ///  - Drawing continues on the canvas as usual, the bitmap is owned by the caller who
+    ///    should close() it once done
+    pub fn snapshot(&mut self) -> Result<ImageBitmap, CanvasError> {
+        self.flush_batch();
+        snapshot(self.canvas())
+    }
+
+    /// Replace the content of the canvas by a bitmap of its physical size
+    pub(crate) fn present_bitmap(&mut self, bitmap: &ImageBitmap) -> Result<(), CanvasError> {
+        self.flush_batch();
+        let (width, height) = self.physical_size();
+        let _ = self.context.set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+        self.context
+            .clear_rect(0.0, 0.0, f64::from(width), f64::from(height));
+        let result = self
+            .context
+            .draw_image_with_image_bitmap(bitmap, 0.0, 0.0)
+            .map_err(js_error);
+        self.apply_pixel_ratio();
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use plotters::prelude::*;
+    use plotters_backend::DrawingBackend;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_render_cache() {
+        let canvas = OffscreenCanvas::new(20, 20).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        let mut cache = RenderCache::new(2);
+        let mut renders = 0;
+        let mut draw = |b: &mut OffscreenCanvasBackend| {
+            renders += 1;
+            b.draw_rect((0, 0), (10, 10), &RED, true)
+                .map_err(|e| CanvasError::Other(e.to_string()))
+        };
+
+        let data = fingerprint_of(&[1, 2, 3][..]);
+        assert!(!cache.render(data, &mut backend, &mut draw).unwrap());
+        assert!(cache.render(data, &mut backend, &mut draw).unwrap());
+        assert_eq!(renders, 1);
+        assert_eq!(cache.hit_counts(), (1, 1));
+        assert_eq!(backend.pick_color(5, 5).unwrap().rgb, (255, 0, 0));
+
+        cache.invalidate(data);
+        assert!(cache.is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_snapshot() {
+        let canvas = OffscreenCanvas::new(20, 10).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        backend.draw_rect((0, 0), (10, 10), &RED, true).unwrap();
+        let bitmap = backend.snapshot().unwrap();
+        assert_eq!((bitmap.width(), bitmap.height()), (20, 10));
+
+        // The canvas keeps its content and can still be drawn on
+        backend.draw_rect((10, 0), (20, 10), &BLUE, true).unwrap();
+        assert_eq!(backend.pick_color(5, 5).unwrap().rgb, (255, 0, 0));
+        bitmap.close();
+    }
+}
@@ -0,0 +1,367 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use js_sys::{Function, Object, Reflect};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::canvas::{js_error, CanvasError};
+use crate::time::SharedTimeSource;
+
+/// Timing of the frame being rendered
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrameTime {
+    /// Time of the frame, in milliseconds
+    pub now: f64,
+    /// Time elapsed since the previous frame, 0 for the first one
+    pub dt: f64,
+    /// Number of frames rendered before this one
+    pub frame: u64,
+}
+
+/// Returned by the frame callback to keep the loop running or stop it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoopControl {
+    Continue,
+    Stop,
+}
+
+/// The callback rendering a frame, shared by all the loop kinds
+pub type FrameCallback = Box<dyn FnMut(FrameTime) -> LoopControl>;
+
+#[derive(Default)]
+struct Clock {
+    last: Option<f64>,
+    frame: u64,
+}
+
+impl Clock {
+    fn advance(&mut self, now: f64) -> FrameTime {
+        let dt = self.last.map_or(0.0, |last| now - last);
+        let time = FrameTime {
+            now,
+            dt,
+            frame: self.frame,
+        };
+        self.last = Some(now);
+        self.frame += 1;
+        time
+    }
+}
+
+struct AnimationState {
+    /// Taken out while it runs, so that it can stop the loop
+    callback: Option<FrameCallback>,
+    clock: Clock,
+    handle: Option<f64>,
+    running: bool,
+    closure: Option<Closure<dyn FnMut(f64)>>,
+    time_source: Option<SharedTimeSource>,
+}
+
+/// Render loop driven by requestAnimationFrame (available in dedicated workers and windows)
+pub struct AnimationLoop {
+    state: Rc<RefCell<AnimationState>>,
+}
+
+fn global_function(name: &str) -> Result<Function, CanvasError> {
+    Reflect::get(&js_sys::global(), &name.into())
+        .map_err(js_error)?
+        .dyn_into()
+        .map_err(|_| CanvasError::Unsupported(format!("{} is not available in this scope", name)))
+}
+
+fn request_frame(state: &Rc<RefCell<AnimationState>>) -> Result<(), CanvasError> {
+    let raf = global_function("requestAnimationFrame")?;
+    let mut s = state.borrow_mut();
+    let closure = s
+        .closure
+        .as_ref()
+        .ok_or_else(|| CanvasError::Other("animation loop is stopped".to_string()))?;
+    let handle = raf
+        .call1(&js_sys::global(), closure.as_ref().unchecked_ref())
+        .map_err(js_error)?;
+    s.handle = handle.as_f64();
+    Ok(())
+}
+
+impl AnimationLoop {
+    /// Start calling `callback` once per animation frame, with the frame timestamps
+    pub fn start<F: FnMut(FrameTime) -> LoopControl + 'static>(
+        callback: F,
+    ) -> Result<Self, CanvasError> {
+        Self::start_with(None, callback)
+    }
+
+    /// Start the loop with frame times read from `source`, to keep the animations in sync with
+    /// an external clock
+    pub fn start_with_time_source<F: FnMut(FrameTime) -> LoopControl + 'static>(
+        source: SharedTimeSource,
+        callback: F,
+    ) -> Result<Self, CanvasError> {
+        Self::start_with(Some(source), callback)
+    }
+
+    fn start_with<F: FnMut(FrameTime) -> LoopControl + 'static>(
+        time_source: Option<SharedTimeSource>,
+        callback: F,
+    ) -> Result<Self, CanvasError> {
+        let state = Rc::new(RefCell::new(AnimationState {
+            callback: Some(Box::new(callback)),
+            clock: Clock::default(),
+            handle: None,
+            running: true,
+            closure: None,
+            time_source,
+        }));
+
+        let weak = Rc::downgrade(&state);
+        let closure = Closure::wrap(Box::new(move |now: f64| {
+            let state = match weak.upgrade() {
+                Some(state) => state,
+                None => return,
+            };
+            let (mut callback, time) = {
+                let mut s = state.borrow_mut();
+                s.handle = None;
+                if !s.running {
+                    return;
+                }
+                let callback = match s.callback.take() {
+                    Some(callback) => callback,
+                    None => return,
+                };
+                let now = s.time_source.as_ref().map_or(now, |t| t.now_ms());
+                (callback, s.clock.advance(now))
+            };
+            // Called without the state borrowed, the callback may stop the loop through a handle
+            let control = callback(time);
+            let running = {
+                let mut s = state.borrow_mut();
+                s.callback = Some(callback);
+                if control == LoopControl::Stop {
+                    s.running = false;
+                }
+                s.running
+            };
+            if running {
+                let _ = request_frame(&state);
+            }
+        }) as Box<dyn FnMut(f64)>);
+        state.borrow_mut().closure = Some(closure);
+
+        request_frame(&state)?;
+        Ok(Self { state })
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.state.borrow().running
+    }
+
+    /// Stop the loop, the pending frame is cancelled
+    pub fn stop(&self) {
+        let mut s = self.state.borrow_mut();
+        s.running = false;
+        if let Some(handle) = s.handle.take() {
+            if let Ok(cancel) = global_function("cancelAnimationFrame") {
+                let _ = cancel.call1(&js_sys::global(), &handle.into());
+            }
+        }
+    }
+}
+
+impl Drop for AnimationLoop {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Render loop which only advances when `tick` is called
+///  - Frames get a deterministic time, so animations and transitions are reproducible in tests
+///    and screenshot comparisons
+pub struct ManualLoop {
+    callback: FrameCallback,
+    clock: Clock,
+    now: f64,
+    running: bool,
+}
+
+impl ManualLoop {
+    pub fn new<F: FnMut(FrameTime) -> LoopControl + 'static>(callback: F) -> Self {
+        Self {
+            callback: Box::new(callback),
+            clock: Clock::default(),
+            now: 0.0,
+            running: true,
+        }
+    }
+
+    /// Advance the clock by `dt` milliseconds and render one frame
+    ///  - Return false once the callback stopped the loop
+    pub fn tick(&mut self, dt: f64) -> bool {
+        if !self.running {
+            return false;
+        }
+        if self.clock.last.is_some() {
+            self.now += dt;
+        }
+        let time = self.clock.advance(self.now);
+        if (self.callback)(time) == LoopControl::Stop {
+            self.running = false;
+        }
+        self.running
+    }
+
+    /// Current time of the loop, in milliseconds
+    pub fn now(&self) -> f64 {
+        self.now
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+}
+
+/// The message the main thread posts to the worker on every displayed frame, see [VsyncLoop]
+///  - `{ vsync: time, seq }`, `time` is the rAF timestamp of the main thread and `seq` counts
+///    the ticks
+pub fn vsync_message(time: f64, seq: u64) -> JsValue {
+    let message = Object::new();
+    let _ = Reflect::set(&message, &"vsync".into(), &time.into());
+    let _ = Reflect::set(&message, &"seq".into(), &(seq as f64).into());
+    message.into()
+}
+
+/// Main thread side of the vsync pacing: posts a [vsync_message] on every animation frame
+///  - `post` sends the message, usually to the worker rendering the charts
+pub struct VsyncSource {
+    _frames: AnimationLoop,
+}
+
+impl VsyncSource {
+    pub fn start<F: Fn(&JsValue) + 'static>(post: F) -> Result<Self, CanvasError> {
+        let frames = AnimationLoop::start(move |time| {
+            post(&vsync_message(time.now, time.frame));
+            LoopControl::Continue
+        })?;
+        Ok(Self { _frames: frames })
+    }
+}
+
+/// Render loop paced by the vsync ticks the main thread posts
+///
+/// Worker side requestAnimationFrame drifts from the display in some browsers, and can fire
+/// twice or not at all between two displayed frames. Driven by the ticks of a [VsyncSource],
+/// the loop renders exactly once per displayed frame, with the frame times of the main thread.
+///  - Ticks delivered twice or out of order are ignored
+pub struct VsyncLoop {
+    callback: FrameCallback,
+    clock: Clock,
+    last_seq: Option<u64>,
+    missed: u64,
+    running: bool,
+}
+
+impl VsyncLoop {
+    pub fn new<F: FnMut(FrameTime) -> LoopControl + 'static>(callback: F) -> Self {
+        Self {
+            callback: Box::new(callback),
+            clock: Clock::default(),
+            last_seq: None,
+            missed: 0,
+            running: true,
+        }
+    }
+
+    /// Render the frame of a tick
+    ///  - Return false if the tick was ignored, or the loop is stopped
+    pub fn tick(&mut self, time: f64, seq: u64) -> bool {
+        if !self.running {
+            return false;
+        }
+        if let Some(last) = self.last_seq {
+            if seq <= last {
+                return false;
+            }
+            self.missed += seq - last - 1;
+        }
+        self.last_seq = Some(seq);
+        let time = self.clock.advance(time);
+        if (self.callback)(time) == LoopControl::Stop {
+            self.running = false;
+        }
+        true
+    }
+
+    /// Render the frame of a [vsync_message]
+    ///  - Return false if the message isn't a tick, or the tick was ignored
+    pub fn handle_message(&mut self, data: &JsValue) -> bool {
+        let field = |name: &str| {
+            Reflect::get(data, &name.into())
+                .ok()
+                .and_then(|v| v.as_f64())
+        };
+        match (field("vsync"), field("seq")) {
+            (Some(time), Some(seq)) => self.tick(time, seq as u64),
+            _ => false,
+        }
+    }
+
+    /// Number of ticks which never reached the loop, e.g. while the worker was busy
+    pub fn missed_ticks(&self) -> u64 {
+        self.missed
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_manual_loop() {
+        let frames = Rc::new(RefCell::new(vec![]));
+        let seen = frames.clone();
+        let mut frame_loop = ManualLoop::new(move |time| {
+            seen.borrow_mut().push(time);
+            if time.frame == 2 {
+                LoopControl::Stop
+            } else {
+                LoopControl::Continue
+            }
+        });
+
+        assert!(frame_loop.tick(16.0));
+        assert!(frame_loop.tick(16.0));
+        assert!(!frame_loop.tick(20.0));
+        assert!(!frame_loop.tick(16.0));
+
+        let frames = frames.borrow();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[1].dt, 16.0);
+        assert_eq!(frames[2].now, 36.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_vsync_loop() {
+        let count = Rc::new(RefCell::new(0));
+        let seen = count.clone();
+        let mut vsync = VsyncLoop::new(move |_| {
+            *seen.borrow_mut() += 1;
+            LoopControl::Continue
+        });
+
+        assert!(vsync.handle_message(&vsync_message(16.0, 0)));
+        // The same tick delivered twice renders once
+        assert!(!vsync.handle_message(&vsync_message(16.0, 0)));
+        assert!(vsync.tick(64.0, 3));
+        assert!(!vsync.handle_message(&JsValue::from_str("resize")));
+
+        assert_eq!(*count.borrow(), 2);
+        assert_eq!(vsync.missed_ticks(), 2);
+    }
+}
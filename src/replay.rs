@@ -0,0 +1,165 @@
+use plotters_backend::DrawingErrorKind;
+use web_sys::ImageBitmap;
+
+use crate::canvas::{CanvasError, OffscreenCanvasBackend};
+use crate::recorder::{FrameRecorder, RecordedFrame};
+
+pub(crate) fn drawing_error(e: DrawingErrorKind<CanvasError>) -> CanvasError {
+    match e {
+        DrawingErrorKind::DrawingError(e) => e,
+        DrawingErrorKind::FontError(e) => CanvasError::Font(e.to_string()),
+    }
+}
+
+/// Random access into a recorded session, for a debugging UI scrubbing through the frames
+///
+/// The canvas at a frame is what the commands of all the frames up to it drew. Seeking starts
+/// from the closest keyframe, a snapshot of the canvas taken every `keyframe_interval` frames
+/// the first time they are replayed, and only replays the frames after it.
+pub struct ReplayTimeline {
+    frames: Vec<RecordedFrame>,
+    keyframe_interval: usize,
+    /// Position of the frame after which the snapshot was taken, sorted
+    keyframes: Vec<(usize, ImageBitmap)>,
+    size: Option<(u32, u32)>,
+}
+
+impl ReplayTimeline {
+    /// Create a timeline of recorded frames, the first one drawn on a blank canvas
+    pub fn new(frames: Vec<RecordedFrame>, keyframe_interval: usize) -> Self {
+        Self {
+            frames,
+            keyframe_interval: keyframe_interval.max(1),
+            keyframes: vec![],
+            size: None,
+        }
+    }
+
+    /// Create a timeline of the frames a recorder kept
+    ///  - The oldest kept frame is drawn on a blank canvas, what the dropped frames drew before
+    ///    it is missing
+    pub fn from_recorder(recorder: &FrameRecorder, keyframe_interval: usize) -> Self {
+        Self::new(recorder.frames(), keyframe_interval)
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn frames(&self) -> &[RecordedFrame] {
+        &self.frames
+    }
+
+    /// The position of the frame with the given recorder index
+    pub fn position_of(&self, index: u64) -> Option<usize> {
+        self.frames.iter().position(|f| f.index == index)
+    }
+
+    /// Number of keyframes taken so far
+    pub fn keyframe_count(&self) -> usize {
+        self.keyframes.len()
+    }
+
+    /// Draw the canvas as it was once the frame at `position` was rendered
+    ///  - `target` should have the size of the recorded canvas, the keyframes are dropped
+    ///    when it changes
+    pub fn seek(
+        &mut self,
+        position: usize,
+        target: &mut OffscreenCanvasBackend,
+    ) -> Result<(), CanvasError> {
+        if position >= self.frames.len() {
+            return Err(CanvasError::InvalidInput(format!(
+                "frame {} is past the {} recorded frames",
+                position,
+                self.frames.len()
+            )));
+        }
+
+        let size = target.physical_size();
+        if self.size != Some(size) {
+            self.clear_keyframes();
+            self.size = Some(size);
+        }
+
+        let start = match self.keyframes.iter().rev().find(|(p, _)| *p <= position) {
+            Some((p, bitmap)) => {
+                target.present_bitmap(bitmap)?;
+                p + 1
+            }
+            None => {
+                target.reset_state();
+                0
+            }
+        };
+
+        let replayed = self
+            .frames
+            .iter()
+            .enumerate()
+            .take(position + 1)
+            .skip(start);
+        for (p, frame) in replayed {
+            for command in frame.commands.iter() {
+                target.execute(command).map_err(drawing_error)?;
+            }
+            let taken = self.keyframes.iter().any(|(k, _)| *k == p);
+            if (p + 1) % self.keyframe_interval == 0 && !taken {
+                let bitmap = target.snapshot()?;
+                let at = self.keyframes.partition_point(|(k, _)| *k < p);
+                self.keyframes.insert(at, (p, bitmap));
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop the keyframes, releasing their memory
+    pub fn clear_keyframes(&mut self) {
+        for (_, bitmap) in self.keyframes.drain(..) {
+            bitmap.close();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::command::{CommandColor, DrawCommand};
+    use wasm_bindgen_test::*;
+    use web_sys::OffscreenCanvas;
+
+    #[wasm_bindgen_test]
+    fn test_seek_replays_from_keyframes() {
+        let red = CommandColor {
+            rgb: (255, 0, 0),
+            alpha: 1.0,
+        };
+        let frames = (0..5)
+            .map(|i| RecordedFrame {
+                index: i,
+                duration_ms: 1.0,
+                commands: vec![DrawCommand::Pixel {
+                    point: (i as i32, 0),
+                    color: red,
+                }],
+            })
+            .collect();
+        let mut timeline = ReplayTimeline::new(frames, 2);
+
+        let canvas = OffscreenCanvas::new(10, 10).unwrap();
+        let mut target = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        timeline.seek(4, &mut target).unwrap();
+        assert_eq!(timeline.keyframe_count(), 2);
+        assert_eq!(target.pick_color(3, 0).unwrap().rgb, (255, 0, 0));
+
+        // Going back starts from the first keyframe, the later pixels are not drawn yet
+        timeline.seek(1, &mut target).unwrap();
+        assert_eq!(target.pick_color(1, 0).unwrap().rgb, (255, 0, 0));
+        assert_eq!(target.pick_color(3, 0).unwrap().alpha, 0.0);
+        assert!(timeline.seek(5, &mut target).is_err());
+    }
+}
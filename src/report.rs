@@ -0,0 +1,76 @@
+use plotters_backend::DrawingErrorKind;
+
+use crate::canvas::{CanvasError, OffscreenCanvasBackend};
+use crate::geometry::Rect;
+use crate::stats::{BudgetWarning, FrameStats};
+
+/// What happened in a frame, returned by present_with_report()
+#[derive(Clone, Debug, Default)]
+pub struct FrameReport {
+    /// From the first draw call to present, in milliseconds
+    pub duration_ms: f64,
+    /// Draw calls of the frame
+    pub stats: FrameStats,
+    /// The bounding box of what was drawn, None if nothing was
+    pub dirty_rect: Option<Rect>,
+    /// The disjoint regions drawn in the frame, within dirty_rect
+    pub dirty_regions: Vec<Rect>,
+    /// Nothing was drawn since the previous present
+    pub skipped: bool,
+    /// The budget warnings of the frame, also passed to the budget handler
+    pub warnings: Vec<BudgetWarning>,
+}
+
+impl FrameReport {
+    pub(crate) fn skipped() -> Self {
+        Self {
+            skipped: true,
+            ..Default::default()
+        }
+    }
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Present the frame, and report how it went so that a render loop can adapt (lower the
+    /// detail level after slow frames, skip the upload of unchanged frames, ...)
+    pub fn present_with_report(&mut self) -> Result<FrameReport, DrawingErrorKind<CanvasError>> {
+        self.flush_batch();
+        self.apply_post_effects()?;
+        self.draw_debug_bounds()?;
+        self.draw_debug_grid()?;
+        let report = self.end_frame();
+        if !report.skipped {
+            self.copy_to_front()?;
+            self.stream_frame(report.dirty_rect)?;
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use plotters::prelude::*;
+    use plotters_backend::DrawingBackend;
+    use wasm_bindgen_test::*;
+    use web_sys::OffscreenCanvas;
+
+    #[wasm_bindgen_test]
+    fn test_frame_report() {
+        let canvas = OffscreenCanvas::new(100, 100).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+
+        backend
+            .draw_pixel((10, 10), BLACK.to_backend_color())
+            .unwrap();
+        backend
+            .draw_pixel((20, 30), BLACK.to_backend_color())
+            .unwrap();
+        let report = backend.present_with_report().unwrap();
+        assert!(!report.skipped);
+        assert_eq!(report.stats.total(), 2);
+        assert_eq!(report.dirty_rect, Some(Rect::new(10.0, 10.0, 21.0, 31.0)));
+
+        assert!(backend.present_with_report().unwrap().skipped);
+    }
+}
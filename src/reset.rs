@@ -0,0 +1,110 @@
+use js_sys::{Function, Reflect};
+use wasm_bindgen::JsCast;
+
+use crate::canvas::OffscreenCanvasBackend;
+use crate::color::{set_fill_css, set_stroke_css};
+use crate::stats::FrameStats;
+use crate::style_ext::StyleExtras;
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Restore the default context state and clear the canvas
+    ///  - The scaling set up by resize_to_css() is kept
+    ///  - Use the native `reset()` when the browser has it, otherwise set back every piece of
+    ///    state one by one
+    ///  - The clips pushed with push_clip_path() are popped
+    pub fn reset_state(&mut self) {
+        self.flush_batch();
+        // Unwind the clips first, the manual reset can't remove them otherwise
+        while self.pop_clip() {}
+        let reset = Reflect::get(&self.context, &"reset".into())
+            .ok()
+            .and_then(|f| f.dyn_into::<Function>().ok());
+        let done = match reset {
+            Some(reset) => reset.call0(&self.context).is_ok(),
+            None => false,
+        };
+        if !done {
+            self.reset_state_manually();
+        }
+        self.apply_pixel_ratio();
+        self.applied_extras = Some(StyleExtras::default());
+        self.style_cache.forget();
+    }
+
+    fn reset_state_manually(&mut self) {
+        let ctx = &self.context;
+        let _ = ctx.set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+        ctx.set_global_alpha(1.0);
+        let _ = ctx.set_global_composite_operation("source-over");
+        set_fill_css(ctx, "#000000");
+        set_stroke_css(ctx, "#000000");
+        ctx.set_line_width(1.0);
+        ctx.set_line_cap("butt");
+        ctx.set_line_join("miter");
+        ctx.set_miter_limit(10.0);
+        let _ = ctx.set_line_dash(&js_sys::Array::new());
+        ctx.set_line_dash_offset(0.0);
+        ctx.set_shadow_offset_x(0.0);
+        ctx.set_shadow_offset_y(0.0);
+        ctx.set_shadow_blur(0.0);
+        ctx.set_shadow_color("rgba(0,0,0,0)");
+        ctx.set_font("10px sans-serif");
+        ctx.set_text_align("start");
+        ctx.set_text_baseline("alphabetic");
+        ctx.set_filter("none");
+        ctx.set_image_smoothing_enabled(true);
+        ctx.begin_path();
+
+        let (w, h) = self.physical_size();
+        ctx.clear_rect(0.0, 0.0, f64::from(w), f64::from(h));
+    }
+
+    /// Bring the backend back to a drawable state after a render was aborted midway
+    ///  - The batched lines are dropped rather than stroked, the series is ended, the context state
+    ///    is reset and the stats of the unfinished frame are discarded
+    pub fn recover(&mut self) -> Option<FrameStats> {
+        self.line_batch = None;
+        self.context.begin_path();
+        self.end_series();
+        self.reset_state();
+        self.discard_frame()
+    }
+
+    /// Call reset_state() when a frame starts, so that no state leaks from the previous frame
+    /// (off by default)
+    ///  - Call begin_frame() first in frames which push clips before their first draw call
+    pub fn set_reset_on_frame_start(&mut self, enabled: bool) {
+        self.reset_on_frame_start = enabled;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use plotters::prelude::*;
+    use plotters_backend::DrawingBackend;
+    use wasm_bindgen_test::*;
+    use web_sys::OffscreenCanvas;
+
+    #[wasm_bindgen_test]
+    fn test_reset_on_frame_start_keeps_clips() {
+        let canvas = OffscreenCanvas::new(40, 40).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        backend.set_reset_on_frame_start(true);
+
+        // The clip pushed before the first draw call of the frame stays in effect
+        backend.push_clip_rect(0, 0, 20, 20).unwrap();
+        backend.draw_rect((0, 0), (40, 40), &RED, true).unwrap();
+        assert_eq!(backend.clip_depth(), 1);
+        assert_eq!(backend.pick_color(30, 30).unwrap().alpha, 0.0);
+        backend.pop_clip();
+        backend.present().unwrap();
+
+        // begin_frame() resets the state before the clips are pushed
+        backend.begin_frame();
+        backend.push_clip_rect(20, 20, 20, 20).unwrap();
+        backend.draw_rect((0, 0), (40, 40), &BLUE, true).unwrap();
+        assert_eq!(backend.pick_color(10, 10).unwrap().alpha, 0.0);
+        assert_eq!(backend.pick_color(30, 30).unwrap().rgb, (0, 0, 255));
+    }
+}
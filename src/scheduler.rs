@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+
+use crate::canvas::CanvasError;
+use crate::queue::{RenderPriority, RenderQueue};
+use crate::time::{default_time_source, SharedTimeSource};
+
+/// Renders one chart, usually a closure owning the chart's backend and data
+pub type ChartRenderer = Box<dyn FnMut() -> Result<(), CanvasError>>;
+
+struct ScheduledChart {
+    render: ChartRenderer,
+    /// Smoothed duration of the previous renders
+    estimate_ms: Option<f64>,
+}
+
+/// What happened during one frame of a refresh
+#[derive(Debug, Default)]
+pub struct RefreshSlice {
+    /// Number of charts rendered during the frame
+    pub rendered: usize,
+    /// Number of charts left for the next frames
+    pub remaining: usize,
+    /// The charts which failed, they are not retried
+    pub failed: Vec<(String, CanvasError)>,
+}
+
+/// Spread the renders of many charts across frames, within a per-frame time budget
+///
+/// A global refresh marks all the charts stale at once; each call of [RefreshScheduler::run_frame]
+/// (from the render loop) then renders the stale charts in priority order, and stops before a
+/// chart whose previous render wouldn't fit in what's left of the budget, so that the worker
+/// keeps answering messages between the frames.
+pub struct RefreshScheduler {
+    charts: HashMap<String, ScheduledChart>,
+    queue: RenderQueue<String>,
+    time_source: SharedTimeSource,
+}
+
+impl Default for RefreshScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RefreshScheduler {
+    pub fn new() -> Self {
+        Self {
+            charts: HashMap::new(),
+            queue: RenderQueue::new(),
+            time_source: default_time_source(),
+        }
+    }
+
+    /// Set the clock the budget and the render durations are measured with
+    pub fn set_time_source(&mut self, source: SharedTimeSource) {
+        self.queue.set_time_source(source.clone());
+        self.time_source = source;
+    }
+
+    /// Register the renderer of a chart, replacing the previous one with the same key
+    pub fn add_chart<F: FnMut() -> Result<(), CanvasError> + 'static>(
+        &mut self,
+        key: &str,
+        render: F,
+    ) {
+        self.charts.insert(
+            key.to_string(),
+            ScheduledChart {
+                render: Box::new(render),
+                estimate_ms: None,
+            },
+        );
+    }
+
+    /// Forget a chart, and its pending refresh
+    pub fn remove_chart(&mut self, key: &str) {
+        self.charts.remove(key);
+        self.queue.remove(key);
+    }
+
+    /// Mark a chart stale, a chart refreshed twice before it's rendered is only rendered once
+    ///  - Return false if no chart is registered with the key
+    pub fn refresh(&mut self, key: &str, priority: RenderPriority) -> bool {
+        if !self.charts.contains_key(key) {
+            return false;
+        }
+        self.queue.push_keyed(key, key.to_string(), priority);
+        true
+    }
+
+    /// Mark all the charts stale
+    pub fn refresh_all(&mut self, priority: RenderPriority) {
+        let keys: Vec<String> = self.charts.keys().cloned().collect();
+        for key in keys {
+            self.queue.push_keyed(&key, key.clone(), priority);
+        }
+    }
+
+    /// Change the priority of a stale chart, e.g. when it is scrolled into view
+    pub fn set_priority(&mut self, key: &str, priority: RenderPriority) -> bool {
+        self.queue.set_priority(key, priority)
+    }
+
+    /// Number of charts waiting to be rendered
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Render stale charts until `budget_ms` is spent
+    ///  - At least one chart is rendered per frame, so a chart slower than the budget isn't
+    ///    starved
+    pub fn run_frame(&mut self, budget_ms: f64) -> RefreshSlice {
+        let start = self.time_source.now_ms();
+        let mut slice = RefreshSlice::default();
+
+        while let Some(key) = self.queue.peek() {
+            let elapsed = self.time_source.now_ms() - start;
+            let estimate = self
+                .charts
+                .get(key)
+                .and_then(|c| c.estimate_ms)
+                .unwrap_or(0.0);
+            if slice.rendered > 0 && elapsed + estimate > budget_ms {
+                break;
+            }
+
+            let key = match self.queue.pop() {
+                Some(key) => key,
+                None => break,
+            };
+            let chart = match self.charts.get_mut(&key) {
+                Some(chart) => chart,
+                None => continue,
+            };
+
+            let before = self.time_source.now_ms();
+            let result = (chart.render)();
+            let duration = self.time_source.now_ms() - before;
+            chart.estimate_ms = Some(match chart.estimate_ms {
+                Some(previous) => previous * 0.7 + duration * 0.3,
+                None => duration,
+            });
+
+            slice.rendered += 1;
+            if let Err(e) = result {
+                slice.failed.push((key, e));
+            }
+        }
+
+        slice.remaining = self.queue.len();
+        slice
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::time::MockClock;
+    use std::rc::Rc;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_refresh_is_spread_across_frames() {
+        let clock = MockClock::new(0.0);
+        let mut scheduler = RefreshScheduler::new();
+        scheduler.set_time_source(Rc::new(clock.clone()));
+        for key in ["a", "b", "c"].iter() {
+            let clock = clock.clone();
+            scheduler.add_chart(key, move || {
+                clock.advance(6.0);
+                Ok(())
+            });
+        }
+        scheduler.add_chart("broken", || Err(CanvasError::Other("no data".to_string())));
+
+        scheduler.refresh_all(RenderPriority::Offscreen);
+        scheduler.refresh("broken", RenderPriority::Interactive);
+        assert_eq!(scheduler.pending(), 4);
+
+        let first = scheduler.run_frame(10.0);
+        assert_eq!(first.failed.len(), 1);
+        assert_eq!(first.failed[0].0, "broken");
+        // The estimates are unknown yet: "broken" and two charts fit before 10ms are spent
+        assert_eq!((first.rendered, first.remaining), (3, 1));
+
+        let second = scheduler.run_frame(10.0);
+        assert_eq!((second.rendered, second.remaining), (1, 0));
+    }
+}
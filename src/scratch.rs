@@ -0,0 +1,102 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use web_sys::{OffscreenCanvas, OffscreenCanvasRenderingContext2d};
+
+use crate::canvas::{context_2d, js_error_in, CanvasError};
+
+/// Canvases kept per bucket, effects rarely need more at once
+const MAX_PER_BUCKET: usize = 2;
+
+struct ScratchCanvas {
+    canvas: OffscreenCanvas,
+    context: OffscreenCanvasRenderingContext2d,
+}
+
+/// Idle scratch canvases, by bucket size
+#[derive(Default)]
+struct ScratchPool {
+    buckets: HashMap<(u32, u32), Vec<ScratchCanvas>>,
+}
+
+thread_local! {
+    static SCRATCH: RefCell<ScratchPool> = RefCell::new(ScratchPool::default());
+}
+
+/// Sizes are rounded up to the next power of two (64 at least), so that a canvas serves every
+/// request of a similar size
+fn bucket(width: u32, height: u32) -> (u32, u32) {
+    let round = |v: u32| v.max(64).next_power_of_two();
+    (round(width), round(height))
+}
+
+/// Run `draw` with a temporary canvas of at least `width` x `height` pixels
+///  - The canvas comes from a pool shared by the effects (blur, supersampling, masking,
+///    thumbnails, ...) so that they don't allocate and drop large bitmaps on every frame
+///  - The canvas can be larger than requested, only its upper left `width` x `height` pixels
+///    are meant to be used. It is transparent with the default transform when handed out
+pub fn with_scratch_canvas<R, F>(width: u32, height: u32, draw: F) -> Result<R, CanvasError>
+where
+    F: FnOnce(&OffscreenCanvas, &OffscreenCanvasRenderingContext2d) -> R,
+{
+    let key = bucket(width, height);
+    let scratch = match SCRATCH.with(|pool| pool.borrow_mut().buckets.get_mut(&key)?.pop()) {
+        Some(scratch) => scratch,
+        None => {
+            let canvas =
+                OffscreenCanvas::new(key.0, key.1).map_err(js_error_in("OffscreenCanvas::new"))?;
+            let context = context_2d(&canvas)?;
+            ScratchCanvas { canvas, context }
+        }
+    };
+
+    let result = draw(&scratch.canvas, &scratch.context);
+
+    // Restore the state the next user expects; the pool isn't borrowed while `draw` runs, so
+    // nested effects get their own canvas
+    let _ = scratch.context.set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+    scratch.context.set_global_alpha(1.0);
+    let _ = scratch
+        .context
+        .set_global_composite_operation("source-over");
+    scratch
+        .context
+        .clear_rect(0.0, 0.0, f64::from(key.0), f64::from(key.1));
+    SCRATCH.with(|pool| {
+        let mut pool = pool.borrow_mut();
+        let idle = pool.buckets.entry(key).or_default();
+        if idle.len() < MAX_PER_BUCKET {
+            idle.push(scratch);
+        }
+    });
+
+    Ok(result)
+}
+
+/// Drop the idle scratch canvases, e.g. when the application goes to the background
+pub fn clear_scratch_canvases() {
+    SCRATCH.with(|pool| pool.borrow_mut().buckets.clear());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_scratch_canvas_reuse() {
+        assert_eq!(bucket(10, 100), (64, 128));
+        assert_eq!(bucket(300, 64), (512, 64));
+
+        let first = with_scratch_canvas(100, 100, |canvas, _| canvas.clone()).unwrap();
+        let second = with_scratch_canvas(90, 120, |canvas, _| canvas.clone()).unwrap();
+        assert!(js_sys::Object::is(&first, &second));
+
+        // Nested requests don't share a canvas
+        let nested = with_scratch_canvas(100, 100, |outer, _| {
+            with_scratch_canvas(100, 100, |inner, _| js_sys::Object::is(outer, inner)).unwrap()
+        })
+        .unwrap();
+        assert!(!nested);
+    }
+}
@@ -0,0 +1,183 @@
+use plotters_backend::BackendStyle;
+
+use crate::canvas::OffscreenCanvasBackend;
+use crate::command::CommandStyle;
+
+/// What upstream code knows about the series it is about to draw
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SeriesHint {
+    /// Name of the series, used to attribute the stats
+    pub name: String,
+    /// The style most primitives of the series are drawn with
+    ///  - Lines with exactly this style are batched into a single path, stroked once when the
+    ///    series ends or another primitive is drawn
+    pub style: Option<CommandStyle>,
+}
+
+impl SeriesHint {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            style: None,
+        }
+    }
+
+    pub fn with_style<S: BackendStyle>(mut self, style: &S) -> Self {
+        self.style = Some(CommandStyle::from_style(style));
+        self
+    }
+}
+
+/// Lines queued in the current path
+pub(crate) struct LineBatch {
+    style: CommandStyle,
+    end: Option<(f64, f64)>,
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Bracket the primitives of a series, until end_series()
+    ///  - The state of the hinted style is applied upfront, and the lines drawn with it are
+    ///    batched
+    ///  - A series still open is ended first
+    pub fn begin_series(&mut self, hint: SeriesHint) {
+        self.end_series();
+        if let Some(style) = &hint.style {
+            self.set_fill_style(style);
+            self.set_line_style(style);
+        }
+        self.series_started = self.time_source().now_ms();
+        self.series = Some(hint);
+    }
+
+    /// Close the bracket opened by begin_series(), the batched lines are stroked
+    ///  - The time since begin_series() is added to the stats of the series in the frame
+    pub fn end_series(&mut self) {
+        self.flush_batch();
+        let series = match self.series.take() {
+            Some(series) => series,
+            None => return,
+        };
+        let elapsed = self.time_source().now_ms() - self.series_started;
+        if let Some(frame) = self.frame_stats_mut() {
+            frame.series_entry(&series.name).elapsed_ms += elapsed;
+        }
+    }
+
+    /// The series being drawn
+    pub fn current_series(&self) -> Option<&SeriesHint> {
+        self.series.as_ref()
+    }
+
+    /// Queue a line in the batch of the series, return false if it has to be drawn on its own
+    pub(crate) fn batch_line(
+        &mut self,
+        from: (f64, f64),
+        to: (f64, f64),
+        style: &impl BackendStyle,
+    ) -> bool {
+        let hinted = match self.series.as_ref().and_then(|s| s.style) {
+            Some(hinted) if hinted == CommandStyle::from_style(style) => hinted,
+            _ => {
+                self.flush_batch();
+                return false;
+            }
+        };
+
+        let batch = match self.line_batch.take() {
+            Some(batch) => batch,
+            None => {
+                self.set_polyline_style(&hinted);
+                self.context.begin_path();
+                LineBatch {
+                    style: hinted,
+                    end: None,
+                }
+            }
+        };
+        if batch.end != Some(from) {
+            self.context.move_to(from.0, from.1);
+        }
+        self.context.line_to(to.0, to.1);
+        self.line_batch = Some(LineBatch {
+            end: Some(to),
+            ..batch
+        });
+        true
+    }
+
+    /// Draw the batched lines and pixels
+    pub(crate) fn flush_batch(&mut self) {
+        self.flush_lines();
+        self.flush_pixels();
+    }
+
+    /// Stroke the batched lines
+    pub(crate) fn flush_lines(&mut self) {
+        if let Some(batch) = self.line_batch.take() {
+            // Another primitive may have changed the stroke since the batch started
+            self.set_polyline_style(&batch.style);
+            self.stroke_current_path();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::stats::PrimitiveKind;
+    use plotters::prelude::*;
+    use plotters_backend::DrawingBackend;
+    use wasm_bindgen_test::*;
+    use web_sys::OffscreenCanvas;
+
+    #[wasm_bindgen_test]
+    fn test_series_batches_lines() {
+        let canvas = OffscreenCanvas::new(100, 100).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        let style = BLUE.stroke_width(2);
+
+        backend.begin_series(SeriesHint::new("temperature").with_style(&style));
+        for i in 0..10 {
+            backend
+                .draw_line((i * 10, 50), (i * 10 + 10, 40), &style)
+                .unwrap();
+        }
+        assert!(backend.line_batch.is_some());
+        backend.end_series();
+        assert!(backend.line_batch.is_none());
+        assert!(backend.current_series().is_none());
+        assert_eq!(
+            backend.frame_stats().unwrap().count(PrimitiveKind::Line),
+            10
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_series_timing() {
+        let canvas = OffscreenCanvas::new(100, 100).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        let clock = crate::time::MockClock::new(0.0);
+        backend.set_time_source(std::rc::Rc::new(clock.clone()));
+
+        backend.begin_series(SeriesHint::new("fast"));
+        backend
+            .draw_pixel((1, 1), BLACK.to_backend_color())
+            .unwrap();
+        clock.advance(1.0);
+        backend.begin_series(SeriesHint::new("slow"));
+        backend
+            .draw_pixel((2, 2), BLACK.to_backend_color())
+            .unwrap();
+        backend
+            .draw_pixel((200, 2), BLACK.to_backend_color())
+            .unwrap();
+        clock.advance(9.0);
+        backend.end_series();
+
+        let stats = backend.frame_stats().unwrap();
+        assert_eq!(stats.series().len(), 2);
+        let slow = stats.slowest_series().unwrap();
+        assert_eq!(slow.name, "slow");
+        assert_eq!((slow.draw_calls, slow.culled, slow.elapsed_ms), (1, 1, 9.0));
+    }
+}
@@ -0,0 +1,167 @@
+use std::cell::{RefCell, RefMut};
+use std::rc::Rc;
+
+use plotters_backend::{
+    BackendColor, BackendCoord, BackendStyle, BackendTextStyle, DrawingBackend, DrawingErrorKind,
+};
+use web_sys::OffscreenCanvas;
+
+use crate::canvas::{CanvasError, OffscreenCanvasBackend};
+
+/// A reference counted handle on a backend owning its canvas
+///
+/// Clones draw on the same canvas and share the same state (stats, middlewares, series, ...),
+/// so several event handlers of a worker can each turn their clone into a drawing area.
+///  - Don't draw through a clone while another one is borrowed with [SharedBackend::borrow_mut]
+#[derive(Clone)]
+pub struct SharedBackend(Rc<RefCell<OffscreenCanvasBackend<'static>>>);
+
+impl OffscreenCanvasBackend<'static> {
+    /// Create a backend owning `canvas`, behind a clonable handle
+    pub fn new_shared(canvas: OffscreenCanvas) -> Option<SharedBackend> {
+        Self::with_canvas(canvas).map(SharedBackend::from)
+    }
+}
+
+impl From<OffscreenCanvasBackend<'static>> for SharedBackend {
+    fn from(backend: OffscreenCanvasBackend<'static>) -> Self {
+        Self(Rc::new(RefCell::new(backend)))
+    }
+}
+
+impl SharedBackend {
+    /// Access the backend, for the methods which aren't part of the DrawingBackend API
+    pub fn borrow_mut(&self) -> RefMut<'_, OffscreenCanvasBackend<'static>> {
+        self.0.borrow_mut()
+    }
+
+    /// Number of handles on the backend
+    pub fn handle_count(&self) -> usize {
+        Rc::strong_count(&self.0)
+    }
+}
+
+impl DrawingBackend for SharedBackend {
+    type ErrorType = CanvasError;
+
+    fn get_size(&self) -> (u32, u32) {
+        self.0.borrow().get_size()
+    }
+
+    fn ensure_prepared(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.0.borrow_mut().ensure_prepared()
+    }
+
+    fn present(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.0.borrow_mut().present()
+    }
+
+    fn draw_pixel(
+        &mut self,
+        point: BackendCoord,
+        color: BackendColor,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.0.borrow_mut().draw_pixel(point, color)
+    }
+
+    fn draw_line<S: BackendStyle>(
+        &mut self,
+        from: BackendCoord,
+        to: BackendCoord,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.0.borrow_mut().draw_line(from, to, style)
+    }
+
+    fn draw_rect<S: BackendStyle>(
+        &mut self,
+        upper_left: BackendCoord,
+        bottom_right: BackendCoord,
+        style: &S,
+        fill: bool,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.0
+            .borrow_mut()
+            .draw_rect(upper_left, bottom_right, style, fill)
+    }
+
+    fn draw_path<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        path: I,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.0.borrow_mut().draw_path(path, style)
+    }
+
+    fn draw_circle<S: BackendStyle>(
+        &mut self,
+        center: BackendCoord,
+        radius: u32,
+        style: &S,
+        fill: bool,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.0.borrow_mut().draw_circle(center, radius, style, fill)
+    }
+
+    fn fill_polygon<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        vert: I,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.0.borrow_mut().fill_polygon(vert, style)
+    }
+
+    fn draw_text<TStyle: BackendTextStyle>(
+        &mut self,
+        text: &str,
+        style: &TStyle,
+        pos: BackendCoord,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.0.borrow_mut().draw_text(text, style, pos)
+    }
+
+    fn estimate_text_size<TStyle: BackendTextStyle>(
+        &self,
+        text: &str,
+        style: &TStyle,
+    ) -> Result<(u32, u32), DrawingErrorKind<Self::ErrorType>> {
+        self.0.borrow().estimate_text_size(text, style)
+    }
+
+    fn blit_bitmap(
+        &mut self,
+        pos: BackendCoord,
+        size: (u32, u32),
+        src: &[u8],
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.0.borrow_mut().blit_bitmap(pos, size, src)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use plotters::prelude::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_shared_backend_clones() {
+        let shared = OffscreenCanvasBackend::new_shared(OffscreenCanvas::new(100, 100).unwrap())
+            .expect("cannot find canvas");
+
+        let handlers: Vec<Box<dyn Fn()>> = (0..2)
+            .map(|i| {
+                let root = shared.clone().into_drawing_area();
+                Box::new(move || {
+                    root.draw_pixel((i, i), &BLACK).unwrap();
+                }) as Box<dyn Fn()>
+            })
+            .collect();
+        for handler in handlers.iter() {
+            handler();
+        }
+
+        assert_eq!(shared.handle_count(), 3);
+        assert_eq!(shared.borrow_mut().frame_stats().unwrap().total(), 2);
+    }
+}
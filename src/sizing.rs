@@ -0,0 +1,173 @@
+use web_sys::OffscreenCanvas;
+
+use crate::canvas::OffscreenCanvasBackend;
+use crate::style_ext::StyleExtras;
+
+/// Physical size of a canvas displayed at `css_width` x `css_height` CSS pixels
+pub fn physical_size_for(css_width: f64, css_height: f64, device_pixel_ratio: f64) -> (u32, u32) {
+    let ratio = if device_pixel_ratio > 0.0 {
+        device_pixel_ratio
+    } else {
+        1.0
+    };
+    (
+        (css_width.max(0.0) * ratio).round() as u32,
+        (css_height.max(0.0) * ratio).round() as u32,
+    )
+}
+
+fn valid_ratio(device_pixel_ratio: f64) -> f64 {
+    if device_pixel_ratio > 0.0 && device_pixel_ratio.is_finite() {
+        device_pixel_ratio
+    } else {
+        1.0
+    }
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Create a backend drawing in logical pixels on a canvas whose bitmap already has the
+    /// physical size of a HiDPI screen
+    ///  - get_size() reports the bitmap size divided by `device_pixel_ratio`, line widths and
+    ///    fonts are scaled with the rest of the drawing
+    pub fn new_with_dpr(canvas: &'a OffscreenCanvas, device_pixel_ratio: f64) -> Option<Self> {
+        let mut backend = Self::new(canvas)?;
+        backend.set_device_pixel_ratio(device_pixel_ratio);
+        Some(backend)
+    }
+
+    /// Scale the drawing by `device_pixel_ratio`, without resizing the canvas
+    pub fn set_device_pixel_ratio(&mut self, device_pixel_ratio: f64) {
+        self.pixel_ratio = valid_ratio(device_pixel_ratio);
+        self.update_logical_size();
+    }
+
+    /// Size the canvas for an element of the given CSS size on a screen with the given
+    /// device pixel ratio, as posted from the main thread
+    ///  - The bitmap gets the physical size and the context is scaled, so that the chart is
+    ///    drawn in CSS pixels and get_size() keeps reporting the logical size
+    ///  - Resizing clears the canvas, the content has to be redrawn
+    pub fn resize_to_css(&mut self, css_width: f64, css_height: f64, device_pixel_ratio: f64) {
+        let (width, height) = physical_size_for(css_width, css_height, device_pixel_ratio);
+        self.set_canvas_size(width, height);
+        self.applied_extras = Some(StyleExtras::default());
+        self.style_cache.forget();
+        self.forget_clips();
+
+        self.pixel_ratio = valid_ratio(device_pixel_ratio);
+        let (sx, sy) = self.axis_scale;
+        self.logical_size = Some((
+            (css_width.max(0.0) / sx).round() as u32,
+            (css_height.max(0.0) / sy).round() as u32,
+        ));
+        self.apply_pixel_ratio();
+    }
+
+    /// Scale the drawing by `sx` horizontally and `sy` vertically on top of the device pixel
+    /// ratio, e.g. for an anamorphic display or to squeeze a chart into a stretched placeholder
+    ///  - get_size() reports the size divided by the scale, the chart fills the canvas
+    ///  - Line widths stay uniform, scaled by `sqrt(sx * sy)`, while texts and markers are
+    ///    stretched with the rest of the drawing
+    ///  - (1, 1) goes back to uniform scaling
+    pub fn set_axis_scale(&mut self, sx: f64, sy: f64) {
+        self.axis_scale = (valid_ratio(sx), valid_ratio(sy));
+        self.update_logical_size();
+    }
+
+    pub fn axis_scale(&self) -> (f64, f64) {
+        self.axis_scale
+    }
+
+    /// Ratio between physical and logical pixels, 1 unless resize_to_css() or a device pixel
+    /// ratio was used
+    pub fn pixel_ratio(&self) -> f64 {
+        self.pixel_ratio
+    }
+
+    /// Physical pixels per logical pixel along x and y, the pixel ratio times the axis scale
+    pub(crate) fn scale_factors(&self) -> (f64, f64) {
+        let (sx, sy) = self.axis_scale;
+        (self.pixel_ratio * sx, self.pixel_ratio * sy)
+    }
+
+    /// Set the context transform back to the logical to physical scaling
+    pub(crate) fn apply_pixel_ratio(&self) {
+        let (sx, sy) = self.scale_factors();
+        let _ = self.context.set_transform(sx, 0.0, 0.0, sy, 0.0, 0.0);
+    }
+
+    /// Stroke the current path, with a uniform line width when the axes are scaled apart
+    pub(crate) fn stroke_current_path(&self) {
+        let (sx, sy) = self.axis_scale;
+        if sx == sy {
+            self.context.stroke();
+            return;
+        }
+        // The path is already in physical pixels, only the line width uses the transform
+        let s = self.pixel_ratio * (sx * sy).sqrt();
+        self.context.save();
+        let _ = self.context.set_transform(s, 0.0, 0.0, s, 0.0, 0.0);
+        self.context.stroke();
+        self.context.restore();
+    }
+
+    fn update_logical_size(&mut self) {
+        let (sx, sy) = self.scale_factors();
+        let (width, height) = self.physical_size();
+        self.logical_size = Some((
+            (f64::from(width) / sx).round() as u32,
+            (f64::from(height) / sy).round() as u32,
+        ));
+        self.apply_pixel_ratio();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_physical_size_for() {
+        assert_eq!(physical_size_for(300.0, 150.0, 2.0), (600, 300));
+        assert_eq!(physical_size_for(100.5, 50.0, 1.5), (151, 75));
+        assert_eq!(physical_size_for(100.0, 50.0, 0.0), (100, 50));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_new_with_dpr() {
+        use plotters_backend::DrawingBackend;
+
+        let canvas = OffscreenCanvas::new(600, 300).unwrap();
+        let backend =
+            OffscreenCanvasBackend::new_with_dpr(&canvas, 2.0).expect("cannot find canvas");
+        assert_eq!(backend.get_size(), (300, 150));
+        assert_eq!(backend.pixel_ratio(), 2.0);
+        assert_eq!((canvas.width(), canvas.height()), (600, 300));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_axis_scale() {
+        use plotters::prelude::*;
+        use plotters_backend::DrawingBackend;
+
+        let canvas = OffscreenCanvas::new(80, 40).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        backend.set_axis_scale(2.0, 1.0);
+        assert_eq!(backend.get_size(), (40, 40));
+
+        // The rectangle is stretched, the lines keep the same width along both axes
+        backend.draw_rect((10, 10), (20, 20), &RED, true).unwrap();
+        assert_eq!(backend.pick_color(15, 15).unwrap().rgb, (255, 0, 0));
+        backend
+            .draw_line((0, 30), (40, 30), &BLACK.stroke_width(2))
+            .unwrap();
+        backend
+            .draw_line((30, 0), (30, 40), &BLACK.stroke_width(2))
+            .unwrap();
+        let data = backend.read_pixels(None).unwrap();
+        let alpha = |x: usize, y: usize| data[(y * 80 + x) * 4 + 3];
+        let horizontal = (0..40).filter(|&y| alpha(20, y) > 0).count();
+        let vertical = (0..80).filter(|&x| alpha(x, 5) > 0).count();
+        assert_eq!(horizontal, vertical);
+    }
+}
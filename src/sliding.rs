@@ -0,0 +1,296 @@
+use std::collections::VecDeque;
+
+use plotters_backend::DrawingErrorKind;
+use web_sys::{OffscreenCanvas, OffscreenCanvasRenderingContext2d};
+
+use crate::canvas::{context_2d, error_cast, js_error_in, CanvasError, OffscreenCanvasBackend};
+use crate::color::set_stroke_color;
+use crate::command::CommandColor;
+use crate::geometry::Rect;
+use crate::stats::PrimitiveKind;
+use crate::viewport::Viewport;
+
+/// A fixed capacity buffer, pushing to a full buffer drops the oldest item
+#[derive(Clone, Debug)]
+pub struct RingBuffer<T> {
+    items: VecDeque<T>,
+    capacity: usize,
+}
+
+impl<T> RingBuffer<T> {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            items: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Append an item, return the one evicted to make room for it
+    pub fn push(&mut self, item: T) -> Option<T> {
+        let evicted = if self.items.len() == self.capacity {
+            self.items.pop_front()
+        } else {
+            None
+        };
+        self.items.push_back(item);
+        evicted
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.items.back()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.items.get(index)
+    }
+
+    /// The items, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.items.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+}
+
+/// A "last N seconds" line chart over a ring buffer of samples
+///
+/// The x window follows the latest sample. On update the cached image is scrolled by the
+/// whole number of pixels the window moved and only the uncovered column is rendered, unless
+/// the window jumped by more than the width of the chart.
+pub struct SlidingWindow {
+    samples: RingBuffer<(f64, f64)>,
+    span: f64,
+    y_range: (f64, f64),
+    color: CommandColor,
+    line_width: f64,
+    cache: OffscreenCanvas,
+    context: OffscreenCanvasRenderingContext2d,
+    /// Data x at the right edge of the cached image, None before the first render
+    right: Option<f64>,
+    /// Samples pushed since the last update
+    pending: usize,
+}
+
+impl SlidingWindow {
+    /// Create a `width` x `height` chart keeping up to `capacity` samples, showing `span` units
+    /// of x and the `y_range` of y
+    pub fn new(
+        width: u32,
+        height: u32,
+        capacity: usize,
+        span: f64,
+        y_range: (f64, f64),
+        color: CommandColor,
+        line_width: f64,
+    ) -> Result<Self, CanvasError> {
+        let cache =
+            OffscreenCanvas::new(width, height).map_err(js_error_in("OffscreenCanvas::new"))?;
+        let context = context_2d(&cache)?;
+        Ok(Self {
+            samples: RingBuffer::new(capacity),
+            span,
+            y_range,
+            color,
+            line_width,
+            cache,
+            context,
+            right: None,
+            pending: 0,
+        })
+    }
+
+    /// Add a sample, x must not be lower than the one of the last sample
+    pub fn push(&mut self, x: f64, y: f64) {
+        self.samples.push((x, y));
+        self.pending = (self.pending + 1).min(self.samples.len());
+    }
+
+    pub fn samples(&self) -> &RingBuffer<(f64, f64)> {
+        &self.samples
+    }
+
+    /// Change the y range, the whole chart is drawn again on the next update
+    pub fn set_y_range(&mut self, y_range: (f64, f64)) {
+        self.y_range = y_range;
+        self.right = None;
+    }
+
+    fn size(&self) -> (f64, f64) {
+        (
+            f64::from(self.cache.width()),
+            f64::from(self.cache.height()),
+        )
+    }
+
+    fn viewport(&self, right: f64) -> Viewport {
+        let (w, h) = self.size();
+        Viewport::new(
+            (right - self.span, right),
+            self.y_range,
+            Rect::new(0.0, 0.0, w, h),
+        )
+    }
+
+    /// Scroll the cached image and render the samples pushed since the last update
+    pub fn update(&mut self) {
+        let latest = match self.samples.last() {
+            Some(&(x, _)) => x,
+            None => return,
+        };
+        let (w, h) = self.size();
+        let px_per_unit = if self.span > 0.0 { w / self.span } else { 0.0 };
+
+        let scroll = match self.right {
+            // Move by whole pixels only, so that the scrolled image stays sharp
+            Some(right) if latest >= right => ((latest - right) * px_per_unit).ceil(),
+            Some(_) => 0.0,
+            None => w,
+        };
+        if scroll >= w || px_per_unit == 0.0 {
+            self.right = Some(latest);
+            self.redraw_all();
+            return;
+        }
+        let right = self.right.unwrap_or(latest) + scroll / px_per_unit;
+        self.right = Some(right);
+
+        if scroll > 0.0 {
+            let _ = self.context.set_global_composite_operation("copy");
+            let _ = self
+                .context
+                .draw_image_with_offscreen_canvas(&self.cache, -scroll, 0.0);
+            let _ = self.context.set_global_composite_operation("source-over");
+            self.context.clear_rect(w - scroll, 0.0, scroll, h);
+        }
+
+        // Connect the new samples to the last one rendered before them
+        let from = self.samples.len() - self.pending;
+        self.stroke_from(from.saturating_sub(1), right);
+        self.pending = 0;
+    }
+
+    fn redraw_all(&mut self) {
+        let (w, h) = self.size();
+        self.context.clear_rect(0.0, 0.0, w, h);
+        if let Some(right) = self.right {
+            self.stroke_from(0, right);
+        }
+        self.pending = 0;
+    }
+
+    fn stroke_from(&self, from: usize, right: f64) {
+        if self.samples.len() < from + 2 {
+            return;
+        }
+        let viewport = self.viewport(right);
+        set_stroke_color(&self.context, self.color.to_backend_color());
+        self.context.set_line_width(self.line_width);
+        self.context.begin_path();
+        for (i, &p) in self.samples.iter().skip(from).enumerate() {
+            let (x, y) = viewport.to_pixel(p);
+            if i == 0 {
+                self.context.move_to(x, y);
+            } else {
+                self.context.line_to(x, y);
+            }
+        }
+        self.context.stroke();
+    }
+
+    pub fn cache(&self) -> &OffscreenCanvas {
+        &self.cache
+    }
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Update the sliding window chart and copy it onto the canvas at `pos`
+    pub fn draw_sliding_window(
+        &mut self,
+        window: &mut SlidingWindow,
+        pos: (f64, f64),
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        window.update();
+        let (w, h) = window.size();
+        if !self.begin_primitive(
+            PrimitiveKind::Bitmap,
+            Rect::new(pos.0, pos.1, pos.0 + w, pos.1 + h),
+        ) {
+            return Ok(());
+        }
+        self.context
+            .draw_image_with_offscreen_canvas(&window.cache, pos.0, pos.1)
+            .map_err(error_cast)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_ring_buffer() {
+        let mut buffer = RingBuffer::new(3);
+        assert_eq!(buffer.push(1), None);
+        assert_eq!(buffer.push(2), None);
+        assert_eq!(buffer.push(3), None);
+        assert_eq!(buffer.push(4), Some(1));
+        assert_eq!(buffer.iter().copied().collect::<Vec<_>>(), vec![2, 3, 4]);
+        assert_eq!(buffer.last(), Some(&4));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_sliding_window_update() {
+        let color = CommandColor {
+            rgb: (0, 0, 0),
+            alpha: 1.0,
+        };
+        // 1px per unit of x, y = 5 is on the row 10
+        let mut window = SlidingWindow::new(100, 20, 4, 100.0, (0.0, 10.0), color, 2.0).unwrap();
+        let context = context_2d(window.cache()).unwrap();
+        let alpha = |x: f64, y: f64| context.get_image_data(x, y, 1.0, 1.0).unwrap().data()[3];
+
+        window.push(0.0, 5.0);
+        window.push(10.0, 5.0);
+        window.update();
+        assert!(alpha(95.0, 10.0) > 0);
+        assert_eq!(alpha(88.0, 10.0), 0);
+
+        // Scrolled by 3 whole pixels, the new segment is drawn at the right edge
+        window.push(12.5, 5.0);
+        window.update();
+        assert!(alpha(88.0, 10.0) > 0);
+        assert_eq!(alpha(85.0, 10.0), 0);
+        assert!(alpha(98.0, 10.0) > 0);
+
+        // 2 samples are evicted, the new ones still connect to the last one rendered
+        window.push(20.0, 5.0);
+        window.push(30.0, 5.0);
+        window.push(40.0, 5.0);
+        window.update();
+        assert_eq!(window.samples().get(0), Some(&(12.5, 5.0)));
+        assert!(alpha(76.0, 10.0) > 0);
+
+        // The window jumps past the width of the chart, which is drawn again from scratch
+        window.push(1000.0, 9.0);
+        window.push(1100.0, 9.0);
+        window.update();
+        assert_eq!(alpha(76.0, 10.0), 0);
+        assert!(alpha(50.0, 2.0) > 0);
+    }
+}
@@ -0,0 +1,284 @@
+use plotters_backend::DrawingErrorKind;
+
+use crate::canvas::{error_cast, CanvasError, OffscreenCanvasBackend};
+use crate::color::{set_fill_color, set_stroke_color};
+use crate::command::CommandColor;
+use crate::geometry::Rect;
+use crate::stats::PrimitiveKind;
+
+/// How the distance between the pointer and a point is measured
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapMode {
+    /// Horizontal distance only, the usual hover of time series
+    X,
+    /// Euclidean distance, for scatter plots
+    Nearest,
+}
+
+/// The point the pointer snapped to
+#[derive(Clone, Debug, PartialEq)]
+pub struct SnapHit {
+    pub series: String,
+    /// Index of the point in its series
+    pub index: usize,
+    /// Position of the point, in canvas pixels
+    pub point: (f64, f64),
+    pub distance: f64,
+}
+
+/// The points of a series inside a brush selection
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Selection {
+    pub series: String,
+    /// Indices of the points in the series, in drawing order
+    pub indices: Vec<usize>,
+}
+
+/// Points of the series drawn on the canvas, in pixels, to find what the pointer is over
+#[derive(Clone, Debug, Default)]
+pub struct SnapIndex {
+    series: Vec<(String, Vec<(f64, f64)>)>,
+}
+
+impl SnapIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add points to a series, the series is created if needed
+    pub fn add_points<I: IntoIterator<Item = (f64, f64)>>(&mut self, series: &str, points: I) {
+        let index = match self.series.iter().position(|(name, _)| name == series) {
+            Some(index) => index,
+            None => {
+                self.series.push((series.to_string(), vec![]));
+                self.series.len() - 1
+            }
+        };
+        let points_of = &mut self.series[index].1;
+        for p in points {
+            // Connected lines repeat their shared vertex
+            if points_of.last() != Some(&p) {
+                points_of.push(p);
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.series.iter().all(|(_, points)| points.is_empty())
+    }
+
+    pub fn clear(&mut self) {
+        self.series.clear();
+    }
+
+    /// The points of every series inside `rect`, for brush selection
+    ///  - Series without any selected point are left out
+    pub fn query_rect(&self, rect: Rect) -> Vec<Selection> {
+        self.series
+            .iter()
+            .filter_map(|(name, points)| {
+                let indices: Vec<usize> = points
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, p)| rect.contains(p.0, p.1))
+                    .map(|(i, _)| i)
+                    .collect();
+                if indices.is_empty() {
+                    None
+                } else {
+                    Some(Selection {
+                        series: name.clone(),
+                        indices,
+                    })
+                }
+            })
+            .collect()
+    }
+
+    /// A point of a series, by its index
+    pub fn point(&self, series: &str, index: usize) -> Option<(f64, f64)> {
+        let (_, points) = self.series.iter().find(|(name, _)| name == series)?;
+        points.get(index).copied()
+    }
+
+    /// The closest point to `pointer`, not further than `max_distance` pixels
+    pub fn nearest(
+        &self,
+        pointer: (f64, f64),
+        mode: SnapMode,
+        max_distance: f64,
+    ) -> Option<SnapHit> {
+        let mut best: Option<SnapHit> = None;
+        for (name, points) in self.series.iter() {
+            for (index, &(x, y)) in points.iter().enumerate() {
+                let distance = match mode {
+                    SnapMode::X => (x - pointer.0).abs(),
+                    SnapMode::Nearest => ((x - pointer.0).powi(2) + (y - pointer.1).powi(2)).sqrt(),
+                };
+                if distance > max_distance
+                    || best.as_ref().map_or(false, |b| b.distance <= distance)
+                {
+                    continue;
+                }
+                best = Some(SnapHit {
+                    series: name.clone(),
+                    index,
+                    point: (x, y),
+                    distance,
+                });
+            }
+        }
+        best
+    }
+}
+
+/// Look of the snap guidelines
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GuideStyle {
+    pub color: CommandColor,
+    pub line_width: f64,
+    pub dash: Option<[f64; 2]>,
+    /// Radius of the marker drawn on the point, 0 for none
+    pub marker_radius: f64,
+    /// Draw the horizontal guideline too
+    pub horizontal: bool,
+}
+
+impl Default for GuideStyle {
+    fn default() -> Self {
+        Self {
+            color: CommandColor {
+                rgb: (80, 80, 80),
+                alpha: 0.8,
+            },
+            line_width: 1.0,
+            dash: Some([4.0, 4.0]),
+            marker_radius: 4.0,
+            horizontal: false,
+        }
+    }
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Record the points of the lines and pixels drawn inside series brackets into a
+    /// `SnapIndex`, or stop recording with false
+    pub fn set_record_geometry(&mut self, enabled: bool) {
+        self.geometry = if enabled {
+            Some(SnapIndex::new())
+        } else {
+            None
+        };
+    }
+
+    /// The geometry recorded since recording started or the last call
+    pub fn take_geometry(&mut self) -> Option<SnapIndex> {
+        self.geometry.as_mut().map(std::mem::take)
+    }
+
+    /// The recorded points inside `rect`, see SnapIndex::query_rect
+    pub fn query_rect(&self, rect: Rect) -> Vec<Selection> {
+        self.geometry
+            .as_ref()
+            .map_or_else(Vec::new, |g| g.query_rect(rect))
+    }
+
+    pub(crate) fn record_geometry(&mut self, points: &[(f64, f64)]) {
+        if let (Some(index), Some(series)) = (self.geometry.as_mut(), self.series.as_ref()) {
+            index.add_points(&series.name, points.iter().copied());
+        }
+    }
+
+    /// Draw the guidelines through the hit point across `area`, typically on an overlay layer
+    pub fn draw_snap_guides(
+        &mut self,
+        hit: &SnapHit,
+        area: Rect,
+        style: &GuideStyle,
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        if !self.begin_primitive(PrimitiveKind::Path, area.inflate(style.marker_radius)) {
+            return Ok(());
+        }
+
+        self.context.save();
+        let result = self.stroke_snap_guides(hit.point, area, style);
+        self.context.restore();
+        result
+    }
+
+    /// Draw the guides with the state of the guide style, between a save() and a restore()
+    fn stroke_snap_guides(
+        &self,
+        (x, y): (f64, f64),
+        area: Rect,
+        style: &GuideStyle,
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        set_stroke_color(&self.context, style.color.to_backend_color());
+        self.context.set_line_width(style.line_width);
+        if let Some(dash) = style.dash {
+            let segments: js_sys::Array = dash
+                .iter()
+                .map(|&v| wasm_bindgen::JsValue::from(v))
+                .collect();
+            self.context.set_line_dash(&segments).map_err(error_cast)?;
+        }
+        self.context.begin_path();
+        self.context.move_to(x, area.top);
+        self.context.line_to(x, area.bottom);
+        if style.horizontal {
+            self.context.move_to(area.left, y);
+            self.context.line_to(area.right, y);
+        }
+        self.stroke_current_path();
+
+        if style.marker_radius > 0.0 {
+            set_fill_color(&self.context, style.color.to_backend_color());
+            self.context.begin_path();
+            self.context
+                .arc(x, y, style.marker_radius, 0.0, std::f64::consts::PI * 2.0)
+                .map_err(error_cast)?;
+            self.context.fill();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_snap_nearest() {
+        let mut index = SnapIndex::new();
+        index.add_points(
+            "a",
+            vec![(0.0, 10.0), (10.0, 20.0), (10.0, 20.0), (20.0, 5.0)],
+        );
+        index.add_points("b", vec![(12.0, 80.0)]);
+
+        let hit = index.nearest((11.0, 75.0), SnapMode::X, 5.0).unwrap();
+        assert_eq!((hit.series.as_str(), hit.index), ("a", 1));
+        let hit = index
+            .nearest((11.0, 75.0), SnapMode::Nearest, 50.0)
+            .unwrap();
+        assert_eq!(hit.series, "b");
+        assert_eq!(index.nearest((50.0, 0.0), SnapMode::X, 5.0), None);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_query_rect() {
+        let mut index = SnapIndex::new();
+        index.add_points("a", vec![(0.0, 0.0), (10.0, 10.0), (20.0, 20.0)]);
+        index.add_points("b", vec![(50.0, 50.0)]);
+
+        let selection = index.query_rect(Rect::new(5.0, 5.0, 25.0, 25.0));
+        assert_eq!(
+            selection,
+            vec![Selection {
+                series: "a".to_string(),
+                indices: vec![1, 2],
+            }]
+        );
+        assert_eq!(index.point("a", 2), Some((20.0, 20.0)));
+    }
+}
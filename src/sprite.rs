@@ -0,0 +1,223 @@
+use std::hash::Hash;
+
+use plotters_backend::{BackendCoord, DrawingBackend};
+use web_sys::OffscreenCanvas;
+
+use crate::canvas::{js_error, js_error_in, CanvasError, OffscreenCanvasBackend};
+use crate::geometry::Rect;
+use crate::render_cache::fingerprint_of;
+use crate::replay::drawing_error;
+use crate::stats::PrimitiveKind;
+
+/// The box of a sprite, in logical pixels
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpriteFrame {
+    pub size: (u32, u32),
+    /// The point of the sprite placed on the stamped position
+    pub origin: (i32, i32),
+}
+
+impl SpriteFrame {
+    /// A sprite stamped by its center
+    pub fn centered(width: u32, height: u32) -> Self {
+        Self {
+            size: (width, height),
+            origin: ((width / 2) as i32, (height / 2) as i32),
+        }
+    }
+}
+
+struct Sprite {
+    canvas: OffscreenCanvas,
+    style: u64,
+    frame: SpriteFrame,
+    pixel_ratio: f64,
+}
+
+/// Rasterize composite symbols once, then stamp them with drawImage
+///
+/// A symbol made of several primitives (a marker with a label badge, a flag, ...) drawn at
+/// thousands of points is drawn once on a small canvas per key, at the pixel ratio of the
+/// target, and copied afterwards. The sprite is drawn again when the style it was drawn with
+/// changes, its frame changes or the target gets another pixel ratio.
+pub struct SpriteCache {
+    capacity: usize,
+    /// Least recently stamped first
+    sprites: Vec<(String, Sprite)>,
+    hits: u64,
+    misses: u64,
+}
+
+impl SpriteCache {
+    /// Keep up to `capacity` sprites
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            sprites: vec![],
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Stamp the sprite of `key` at `pos`, drawing it with `draw` first if needed
+    ///  - `style` is anything identifying the look of the symbol (colors, sizes, the label...),
+    ///    the sprite is drawn again when it changes
+    ///  - `draw` gets a backend of the size of the frame, in logical pixels
+    ///  - Return true if the sprite came from the cache
+    pub fn stamp<S, F>(
+        &mut self,
+        target: &mut OffscreenCanvasBackend,
+        key: &str,
+        style: &S,
+        frame: SpriteFrame,
+        pos: BackendCoord,
+        draw: F,
+    ) -> Result<bool, CanvasError>
+    where
+        S: Hash + ?Sized,
+        F: FnOnce(&mut OffscreenCanvasBackend) -> Result<(), CanvasError>,
+    {
+        let fingerprint = fingerprint_of(style);
+        let ratio = target.pixel_ratio();
+
+        let cached = self.sprites.iter().position(|(k, _)| k == key);
+        let hit = match cached {
+            Some(i) => {
+                let (key, sprite) = self.sprites.remove(i);
+                let fresh = sprite.style == fingerprint
+                    && sprite.frame == frame
+                    && sprite.pixel_ratio == ratio;
+                if fresh {
+                    self.sprites.push((key, sprite));
+                }
+                fresh
+            }
+            None => false,
+        };
+
+        if hit {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+            let canvas = render_sprite(frame, ratio, draw)?;
+            if self.sprites.len() >= self.capacity {
+                self.sprites.remove(0);
+            }
+            self.sprites.push((
+                key.to_string(),
+                Sprite {
+                    canvas,
+                    style: fingerprint,
+                    frame,
+                    pixel_ratio: ratio,
+                },
+            ));
+        }
+
+        if let Some((_, sprite)) = self.sprites.last() {
+            draw_sprite(target, sprite, pos)?;
+        }
+        Ok(hit)
+    }
+
+    /// Drop the sprite of a key
+    pub fn invalidate(&mut self, key: &str) {
+        self.sprites.retain(|(k, _)| k != key);
+    }
+
+    pub fn clear(&mut self) {
+        self.sprites.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.sprites.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sprites.is_empty()
+    }
+
+    /// Number of stamps served from the cache, and drawn
+    pub fn hit_counts(&self) -> (u64, u64) {
+        (self.hits, self.misses)
+    }
+}
+
+fn render_sprite<F>(frame: SpriteFrame, ratio: f64, draw: F) -> Result<OffscreenCanvas, CanvasError>
+where
+    F: FnOnce(&mut OffscreenCanvasBackend) -> Result<(), CanvasError>,
+{
+    let (w, h) = frame.size;
+    let physical = |v: u32| ((f64::from(v) * ratio).ceil() as u32).max(1);
+    let canvas = OffscreenCanvas::new(physical(w), physical(h))
+        .map_err(js_error_in("OffscreenCanvas::new"))?;
+    {
+        let mut backend =
+            OffscreenCanvasBackend::new_with_dpr(&canvas, ratio).ok_or_else(|| {
+                CanvasError::ContextUnavailable("cannot create the sprite context".to_string())
+            })?;
+        draw(&mut backend)?;
+        backend.present().map_err(drawing_error)?;
+    }
+    Ok(canvas)
+}
+
+fn draw_sprite(
+    target: &mut OffscreenCanvasBackend,
+    sprite: &Sprite,
+    pos: BackendCoord,
+) -> Result<(), CanvasError> {
+    let (x, y) = match target.coord(pos).map_err(drawing_error)? {
+        Some(p) => p,
+        None => return Ok(()),
+    };
+    let (x, y) = (
+        x - f64::from(sprite.frame.origin.0),
+        y - f64::from(sprite.frame.origin.1),
+    );
+    let (w, h) = (
+        f64::from(sprite.frame.size.0),
+        f64::from(sprite.frame.size.1),
+    );
+    if !target.begin_primitive(PrimitiveKind::Image, Rect::new(x, y, x + w, y + h)) {
+        return Ok(());
+    }
+    target
+        .context
+        .draw_image_with_offscreen_canvas_and_dw_and_dh(&sprite.canvas, x, y, w, h)
+        .map_err(js_error)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use plotters::prelude::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_sprite_cache() {
+        let canvas = OffscreenCanvas::new(100, 100).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        let mut sprites = SpriteCache::new(4);
+        let badge = |b: &mut OffscreenCanvasBackend| {
+            b.draw_rect((0, 0), (10, 10), &RED.filled(), true)
+                .map_err(drawing_error)
+        };
+
+        let frame = SpriteFrame::centered(10, 10);
+        assert!(!sprites
+            .stamp(&mut backend, "badge", "red", frame, (20, 20), badge)
+            .unwrap());
+        assert!(sprites
+            .stamp(&mut backend, "badge", "red", frame, (50, 50), badge)
+            .unwrap());
+        assert_eq!(backend.pick_color(50, 50).unwrap().rgb, (255, 0, 0));
+
+        // Another style draws the sprite again
+        assert!(!sprites
+            .stamp(&mut backend, "badge", "blue", frame, (80, 80), badge)
+            .unwrap());
+        assert_eq!(sprites.len(), 1);
+        assert_eq!(sprites.hit_counts(), (1, 2));
+    }
+}
@@ -0,0 +1,363 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::dirty::add_region;
+use crate::geometry::Rect;
+
+/// Categories of the draw calls issued to the canvas
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PrimitiveKind {
+    Pixel,
+    Line,
+    Rect,
+    Path,
+    Circle,
+    Polygon,
+    Text,
+    Bitmap,
+    Marker,
+    Image,
+}
+
+impl PrimitiveKind {
+    pub const ALL: [PrimitiveKind; 10] = [
+        PrimitiveKind::Pixel,
+        PrimitiveKind::Line,
+        PrimitiveKind::Rect,
+        PrimitiveKind::Path,
+        PrimitiveKind::Circle,
+        PrimitiveKind::Polygon,
+        PrimitiveKind::Text,
+        PrimitiveKind::Bitmap,
+        PrimitiveKind::Marker,
+        PrimitiveKind::Image,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Draw calls issued since the frame started
+#[derive(Clone, Debug, Default)]
+pub struct FrameStats {
+    /// Time the first primitive of the frame was drawn, in milliseconds
+    pub started_at: f64,
+    counts: [usize; PrimitiveKind::ALL.len()],
+    culled: usize,
+    series: Vec<SeriesStats>,
+    dirty: Option<Rect>,
+    regions: Vec<Rect>,
+}
+
+/// Draw calls and rendering time of a series, bracketed by begin_series()/end_series()
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SeriesStats {
+    pub name: String,
+    pub draw_calls: usize,
+    pub culled: usize,
+    /// Time spent between begin_series() and end_series(), in milliseconds
+    pub elapsed_ms: f64,
+}
+
+impl FrameStats {
+    pub(crate) fn start(now: f64) -> Self {
+        Self {
+            started_at: now,
+            counts: Default::default(),
+            culled: 0,
+            series: vec![],
+            dirty: None,
+            regions: vec![],
+        }
+    }
+
+    pub(crate) fn record(&mut self, kind: PrimitiveKind) {
+        self.counts[kind.index()] += 1;
+    }
+
+    pub(crate) fn record_dirty(&mut self, bounds: Rect) {
+        self.dirty = Some(self.dirty.map_or(bounds, |d| d.union(bounds)));
+        add_region(&mut self.regions, bounds);
+    }
+
+    /// The bounding box of the primitives drawn in the frame
+    pub fn dirty_rect(&self) -> Option<Rect> {
+        self.dirty
+    }
+
+    /// The areas drawn in the frame, at most MAX_DIRTY_REGIONS disjoint rectangles
+    pub fn dirty_regions(&self) -> &[Rect] {
+        &self.regions
+    }
+
+    pub(crate) fn record_culled(&mut self) {
+        self.culled += 1;
+    }
+
+    /// The stats of a series, created the first time the series is drawn in the frame
+    pub(crate) fn series_entry(&mut self, name: &str) -> &mut SeriesStats {
+        let index = match self.series.iter().position(|s| s.name == name) {
+            Some(index) => index,
+            None => {
+                self.series.push(SeriesStats {
+                    name: name.to_string(),
+                    ..Default::default()
+                });
+                self.series.len() - 1
+            }
+        };
+        &mut self.series[index]
+    }
+
+    /// The series drawn in the frame, in the order they started
+    pub fn series(&self) -> &[SeriesStats] {
+        &self.series
+    }
+
+    /// The series which took the longest to render
+    pub fn slowest_series(&self) -> Option<&SeriesStats> {
+        self.series.iter().max_by(|a, b| {
+            a.elapsed_ms
+                .partial_cmp(&b.elapsed_ms)
+                .unwrap_or(Ordering::Equal)
+        })
+    }
+
+    /// Primitives skipped because they were entirely outside of the visible area
+    pub fn culled(&self) -> usize {
+        self.culled
+    }
+
+    pub fn count(&self, kind: PrimitiveKind) -> usize {
+        self.counts[kind.index()]
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// The `n` categories with the most draw calls, most expensive first
+    pub fn top(&self, n: usize) -> Vec<(PrimitiveKind, usize)> {
+        let mut counts: Vec<_> = PrimitiveKind::ALL
+            .iter()
+            .map(|&kind| (kind, self.count(kind)))
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1));
+        counts.truncate(n);
+        counts
+    }
+}
+
+/// Per-frame limits, a warning is emitted on present() when one of them is exceeded
+#[derive(Clone, Debug, Default)]
+pub struct FrameBudget {
+    pub max_draw_calls: Option<usize>,
+    pub max_ms: Option<f64>,
+}
+
+impl FrameBudget {
+    pub(crate) fn check(&self, stats: &FrameStats, elapsed_ms: f64) -> Option<BudgetWarning> {
+        let draw_calls = stats.total();
+        let over_calls = self.max_draw_calls.map_or(false, |max| draw_calls > max);
+        let over_time = self.max_ms.map_or(false, |max| elapsed_ms > max);
+        if !over_calls && !over_time {
+            return None;
+        }
+        Some(BudgetWarning {
+            draw_calls,
+            elapsed_ms,
+            budget: self.clone(),
+            top: stats.top(3),
+            slowest_series: stats.slowest_series().cloned(),
+        })
+    }
+}
+
+/// Emitted when a frame goes over its budget
+#[derive(Clone, Debug)]
+pub struct BudgetWarning {
+    pub draw_calls: usize,
+    pub elapsed_ms: f64,
+    pub budget: FrameBudget,
+    /// The primitive categories with the most draw calls in the frame
+    pub top: Vec<(PrimitiveKind, usize)>,
+    /// The series which took the longest to render, if series were bracketed
+    pub slowest_series: Option<SeriesStats>,
+}
+
+impl fmt::Display for BudgetWarning {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            fmt,
+            "frame over budget: {} draw calls in {:.1}ms",
+            self.draw_calls, self.elapsed_ms
+        )?;
+        if let Some(max) = self.budget.max_draw_calls {
+            write!(fmt, ", max {} draw calls", max)?;
+        }
+        if let Some(max) = self.budget.max_ms {
+            write!(fmt, ", max {:.1}ms", max)?;
+        }
+        let top: Vec<_> = self
+            .top
+            .iter()
+            .map(|(kind, count)| format!("{:?}: {}", kind, count))
+            .collect();
+        write!(fmt, " (top: {})", top.join(", "))?;
+        if let Some(series) = &self.slowest_series {
+            write!(
+                fmt,
+                ", slowest series: {} ({} draw calls in {:.1}ms)",
+                series.name, series.draw_calls, series.elapsed_ms
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Upper bounds of the histogram buckets, in milliseconds
+const BUCKET_BOUNDS: [f64; 17] = [
+    1.0,
+    2.0,
+    4.0,
+    6.0,
+    8.0,
+    10.0,
+    12.0,
+    14.0,
+    16.7,
+    20.0,
+    25.0,
+    33.3,
+    50.0,
+    66.7,
+    100.0,
+    250.0,
+    f64::INFINITY,
+];
+
+/// Summary of the frame durations recorded by a [FrameHistogram]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FrameTimingSummary {
+    pub count: u64,
+    pub mean: f64,
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+/// Histogram of the frame durations (first draw call to present()), for rendering telemetry
+///  - Percentiles are interpolated inside the buckets, which are finer around the 60fps budget
+#[derive(Clone, Debug)]
+pub struct FrameHistogram {
+    counts: [u64; BUCKET_BOUNDS.len()],
+    count: u64,
+    sum: f64,
+    max: f64,
+}
+
+impl Default for FrameHistogram {
+    fn default() -> Self {
+        Self {
+            counts: [0; BUCKET_BOUNDS.len()],
+            count: 0,
+            sum: 0.0,
+            max: 0.0,
+        }
+    }
+}
+
+impl FrameHistogram {
+    pub fn record(&mut self, duration_ms: f64) {
+        let duration_ms = duration_ms.max(0.0);
+        let bucket = BUCKET_BOUNDS
+            .iter()
+            .position(|&upper| duration_ms <= upper)
+            .unwrap_or(BUCKET_BOUNDS.len() - 1);
+        self.counts[bucket] += 1;
+        self.count += 1;
+        self.sum += duration_ms;
+        self.max = self.max.max(duration_ms);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// The duration under which `p` percent of the frames were rendered
+    pub fn percentile(&self, p: f64) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        let rank = (p / 100.0).max(0.0).min(1.0) * self.count as f64;
+        let mut seen = 0.0;
+        for (i, &count) in self.counts.iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            let next = seen + count as f64;
+            if next >= rank {
+                let lower = if i == 0 { 0.0 } else { BUCKET_BOUNDS[i - 1] };
+                let upper = BUCKET_BOUNDS[i].min(self.max);
+                let fraction = (rank - seen) / count as f64;
+                return (lower + (upper - lower) * fraction).min(self.max);
+            }
+            seen = next;
+        }
+        self.max
+    }
+
+    pub fn summary(&self) -> FrameTimingSummary {
+        FrameTimingSummary {
+            count: self.count,
+            mean: if self.count == 0 {
+                0.0
+            } else {
+                self.sum / self.count as f64
+            },
+            p50: self.percentile(50.0),
+            p95: self.percentile(95.0),
+            p99: self.percentile(99.0),
+            max: self.max,
+        }
+    }
+
+    /// The buckets as (upper bound in milliseconds, number of frames)
+    pub fn buckets(&self) -> Vec<(f64, u64)> {
+        BUCKET_BOUNDS
+            .iter()
+            .cloned()
+            .zip(self.counts.iter().cloned())
+            .collect()
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_histogram_percentiles() {
+        let mut histogram = FrameHistogram::default();
+        for _ in 0..99 {
+            histogram.record(5.0);
+        }
+        histogram.record(80.0);
+
+        let summary = histogram.summary();
+        assert_eq!(summary.count, 100);
+        assert!(summary.p50 > 4.0 && summary.p50 <= 6.0);
+        assert!(summary.p95 <= 6.0);
+        assert!(summary.p99 <= 6.0);
+        assert_eq!(summary.max, 80.0);
+        assert_eq!(histogram.percentile(100.0), 80.0);
+    }
+}
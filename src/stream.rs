@@ -0,0 +1,207 @@
+use plotters_backend::DrawingErrorKind;
+use web_sys::{ImageBitmap, OffscreenCanvas};
+
+use crate::canvas::{context_2d, js_error, js_error_in, CanvasError, OffscreenCanvasBackend};
+use crate::geometry::Rect;
+
+/// Receives the bitmap of every presented frame, see [OffscreenCanvasBackend::stream_frames]
+pub type FrameSink = Box<dyn FnMut(ImageBitmap)>;
+
+/// Receives the region changed by every presented frame, and the rectangle of the canvas it
+/// covers in physical pixels, see [OffscreenCanvasBackend::stream_dirty_regions]
+pub type RegionSink = Box<dyn FnMut(ImageBitmap, Rect)>;
+
+/// Where the presented frames go
+pub(crate) enum FrameOutput {
+    Frames(FrameSink),
+    Regions(RegionSink),
+}
+
+/// Copy a region of a canvas, in physical pixels, into a bitmap
+fn crop(canvas: &OffscreenCanvas, region: Rect) -> Result<ImageBitmap, CanvasError> {
+    let (w, h) = (region.width(), region.height());
+    let copy =
+        OffscreenCanvas::new(w as u32, h as u32).map_err(js_error_in("OffscreenCanvas::new"))?;
+    context_2d(&copy)?
+        .draw_image_with_offscreen_canvas_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+            canvas,
+            region.left,
+            region.top,
+            w,
+            h,
+            0.0,
+            0.0,
+            w,
+            h,
+        )
+        .map_err(js_error)?;
+    copy.transfer_to_image_bitmap()
+        .map_err(js_error_in("transferToImageBitmap"))
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Hand the frames to `sink` on every present, e.g. to post them to the main thread which
+    /// paints them with an ImageBitmapRenderingContext
+    ///  - The canvas is transferred, not copied: it is blank after each present, the next
+    ///    frame must be drawn in full
+    ///  - Frames where nothing was drawn are not transferred
+    pub fn stream_frames<F: FnMut(ImageBitmap) + 'static>(&mut self, sink: F) {
+        self.frame_sink = Some(FrameOutput::Frames(Box::new(sink)));
+    }
+
+    /// Hand only the region each frame changed to `sink`, for dashboards which mostly stay
+    /// the same
+    ///  - The region is the dirty rectangle of the frame, see [FrameReport](crate::FrameReport),
+    ///    expanded to whole physical pixels. The main thread draws it at that rectangle over
+    ///    the frames it already shows, with a 2d context
+    ///  - The region is copied, the canvas keeps its content: draw the changes of each frame
+    ///    over the previous one
+    pub fn stream_dirty_regions<F: FnMut(ImageBitmap, Rect) + 'static>(&mut self, sink: F) {
+        self.frame_sink = Some(FrameOutput::Regions(Box::new(sink)));
+    }
+
+    /// Post the frames to a port, the bitmaps are transferred along with the messages
+    #[cfg(feature = "remote-backend")]
+    pub fn stream_frames_to_port(&mut self, port: web_sys::MessagePort) {
+        self.stream_frames(move |bitmap| {
+            let transfer = js_sys::Array::of1(&bitmap);
+            if port
+                .post_message_with_transferable(&bitmap, &transfer)
+                .is_err()
+            {
+                bitmap.close();
+            }
+        });
+    }
+
+    /// Post the dirty regions to a port, as `{ bitmap, x, y, width, height }` messages
+    #[cfg(feature = "remote-backend")]
+    pub fn stream_dirty_regions_to_port(&mut self, port: web_sys::MessagePort) {
+        self.stream_dirty_regions(move |bitmap, region| {
+            let message = js_sys::Object::new();
+            let fields = [
+                ("x", region.left),
+                ("y", region.top),
+                ("width", region.width()),
+                ("height", region.height()),
+            ];
+            let _ = js_sys::Reflect::set(&message, &"bitmap".into(), &bitmap);
+            for (name, value) in fields.iter() {
+                let _ = js_sys::Reflect::set(&message, &(*name).into(), &(*value).into());
+            }
+            let transfer = js_sys::Array::of1(&bitmap);
+            if port
+                .post_message_with_transferable(&message, &transfer)
+                .is_err()
+            {
+                bitmap.close();
+            }
+        });
+    }
+
+    /// Go back to presenting in place
+    pub fn stop_streaming_frames(&mut self) {
+        self.frame_sink = None;
+    }
+
+    pub fn is_streaming_frames(&self) -> bool {
+        self.frame_sink.is_some()
+    }
+
+    /// The dirty rectangle of a frame in physical pixels of the canvas, None if it's empty
+    fn physical_region(&self, dirty: Rect) -> Option<Rect> {
+        let (sx, sy) = self.scale_factors();
+        let region = Rect::new(
+            (dirty.left * sx).floor(),
+            (dirty.top * sy).floor(),
+            (dirty.right * sx).ceil(),
+            (dirty.bottom * sy).ceil(),
+        );
+        let (w, h) = self.physical_size();
+        let region = region.intersection(&Rect::new(0.0, 0.0, f64::from(w), f64::from(h)))?;
+        if region.width() < 1.0 || region.height() < 1.0 {
+            return None;
+        }
+        Some(region)
+    }
+
+    /// Hand the presented frame, or the region it changed, to the sink if one is set
+    pub(crate) fn stream_frame(
+        &mut self,
+        dirty: Option<Rect>,
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        let region = dirty.and_then(|dirty| self.physical_region(dirty));
+        // The frame was copied to the visible canvas, the hidden one keeps it for the next frame
+        let canvas: &OffscreenCanvas = match &self.front {
+            Some(front) => &front.canvas,
+            None => &self.canvas,
+        };
+        match self.frame_sink.as_mut() {
+            None => {}
+            Some(FrameOutput::Frames(sink)) => {
+                let bitmap = canvas.transfer_to_image_bitmap().map_err(|e| {
+                    DrawingErrorKind::DrawingError(js_error_in("transferToImageBitmap")(e))
+                })?;
+                sink(bitmap);
+            }
+            Some(FrameOutput::Regions(sink)) => {
+                if let Some(region) = region {
+                    let bitmap = crop(canvas, region).map_err(DrawingErrorKind::DrawingError)?;
+                    sink(bitmap, region);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use plotters::prelude::*;
+    use plotters_backend::DrawingBackend;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use wasm_bindgen_test::*;
+    use web_sys::OffscreenCanvas;
+
+    #[wasm_bindgen_test]
+    fn test_stream_frames() {
+        let canvas = OffscreenCanvas::new(30, 20).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        let sizes = Rc::new(RefCell::new(vec![]));
+        let seen = sizes.clone();
+        backend
+            .stream_frames(move |bitmap| seen.borrow_mut().push((bitmap.width(), bitmap.height())));
+
+        backend
+            .draw_pixel((1, 1), BLACK.to_backend_color())
+            .unwrap();
+        backend.present().unwrap();
+        // Nothing was drawn, no frame is posted
+        backend.present().unwrap();
+        assert_eq!(*sizes.borrow(), vec![(30, 20)]);
+        assert_eq!(backend.pick_color(1, 1).unwrap().alpha, 0.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_stream_dirty_regions() {
+        let canvas = OffscreenCanvas::new(100, 100).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        let regions = Rc::new(RefCell::new(vec![]));
+        let seen = regions.clone();
+        backend.stream_dirty_regions(move |bitmap, region| {
+            seen.borrow_mut()
+                .push(((bitmap.width(), bitmap.height()), region))
+        });
+
+        backend.draw_rect((10, 20), (30, 25), &RED, true).unwrap();
+        backend.present().unwrap();
+        assert_eq!(
+            *regions.borrow(),
+            vec![((20, 5), Rect::new(10.0, 20.0, 30.0, 25.0))]
+        );
+        // The canvas keeps the frame
+        assert_eq!(backend.pick_color(15, 22).unwrap().rgb, (255, 0, 0));
+    }
+}
@@ -0,0 +1,228 @@
+use plotters_backend::{BackendCoord, BackendStyle, DrawingErrorKind};
+
+use crate::canvas::{CanvasError, OffscreenCanvasBackend};
+use crate::geometry::Rect;
+use crate::stats::PrimitiveKind;
+
+/// Where the stroke of an outlined shape is placed relative to its geometry
+///  - The canvas always centers strokes on the path, so bar outlines bleed outside their fill,
+///    inner and outer alignment are emulated by insetting/outsetting the path by half the width
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StrokeAlignment {
+    Center,
+    Inner,
+    Outer,
+}
+
+impl Default for StrokeAlignment {
+    fn default() -> Self {
+        StrokeAlignment::Center
+    }
+}
+
+impl StrokeAlignment {
+    /// Distance the path is moved outward for a stroke of `width`
+    pub(crate) fn offset(&self, width: f64) -> f64 {
+        match self {
+            StrokeAlignment::Center => 0.0,
+            StrokeAlignment::Inner => -width / 2.0,
+            StrokeAlignment::Outer => width / 2.0,
+        }
+    }
+}
+
+/// The rectangle to stroke so that a stroke of `width` has the requested alignment
+pub(crate) fn aligned_rect(rect: Rect, width: f64, alignment: StrokeAlignment) -> Rect {
+    offset_rect(rect, alignment.offset(width))
+}
+
+/// Move every edge of the rectangle outward by `d` (inward if negative)
+pub(crate) fn offset_rect(rect: Rect, d: f64) -> Rect {
+    let (cx, cy) = (
+        (rect.left + rect.right) / 2.0,
+        (rect.top + rect.bottom) / 2.0,
+    );
+    // An inner stroke wider than the shape collapses onto its center
+    Rect {
+        left: (rect.left - d).min(cx),
+        top: (rect.top - d).min(cy),
+        right: (rect.right + d).max(cx),
+        bottom: (rect.bottom + d).max(cy),
+    }
+}
+
+/// Move every edge of the polygon outward by `distance` (inward if negative)
+///  - Vertices are moved along the miter direction, limited to 4 times the distance so that
+///    very acute corners don't produce spikes
+pub(crate) fn offset_polygon(points: &[(f64, f64)], distance: f64) -> Vec<(f64, f64)> {
+    let n = points.len();
+    if n < 3 || distance == 0.0 {
+        return points.to_vec();
+    }
+
+    let area: f64 = (0..n)
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % n]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum();
+    let sign = if area > 0.0 { 1.0 } else { -1.0 };
+
+    let normal = |a: (f64, f64), b: (f64, f64)| {
+        let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+        let len = (dx * dx + dy * dy).sqrt();
+        if len == 0.0 {
+            (0.0, 0.0)
+        } else {
+            (sign * dy / len, -sign * dx / len)
+        }
+    };
+
+    (0..n)
+        .map(|i| {
+            let prev = points[(i + n - 1) % n];
+            let cur = points[i];
+            let next = points[(i + 1) % n];
+            let n1 = normal(prev, cur);
+            let n2 = normal(cur, next);
+
+            let (mx, my) = (n1.0 + n2.0, n1.1 + n2.1);
+            let len = (mx * mx + my * my).sqrt();
+            if len < 1e-9 {
+                return (cur.0 + n1.0 * distance, cur.1 + n1.1 * distance);
+            }
+            let (mx, my) = (mx / len, my / len);
+            let cos = (mx * n1.0 + my * n1.1).max(0.25);
+            let scale = distance / cos;
+            (cur.0 + mx * scale, cur.1 + my * scale)
+        })
+        .collect()
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Set the stroke alignment used for outlined rectangles and polygons
+    pub fn set_stroke_alignment(&mut self, alignment: StrokeAlignment) {
+        self.stroke_alignment = alignment;
+    }
+
+    pub fn stroke_alignment(&self) -> StrokeAlignment {
+        self.stroke_alignment
+    }
+
+    /// Stroke the outline of a rectangle, honoring the stroke alignment
+    pub fn stroke_rect_aligned<S: BackendStyle>(
+        &mut self,
+        upper_left: BackendCoord,
+        bottom_right: BackendCoord,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        if style.color().alpha == 0.0 {
+            return Ok(());
+        }
+        let width = match self.line_width(style) {
+            Some(width) => width,
+            None => return Ok(()),
+        };
+        let (a, b) = match (
+            self.stroke_coord(upper_left)?,
+            self.stroke_coord(bottom_right)?,
+        ) {
+            (Some(a), Some(b)) => (a, b),
+            _ => return Ok(()),
+        };
+
+        let rect = aligned_rect(Rect::new(a.0, a.1, b.0, b.1), width, self.stroke_alignment);
+        if !self.begin_primitive(PrimitiveKind::Rect, rect.inflate(width / 2.0)) {
+            return Ok(());
+        }
+
+        self.set_line_style(style);
+        self.context.begin_path();
+        self.context
+            .rect(rect.left, rect.top, rect.width(), rect.height());
+        self.stroke_current_path();
+        Ok(())
+    }
+
+    /// Stroke the closed outline of a polygon, honoring the stroke alignment
+    pub fn stroke_polygon_aligned<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        vert: I,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        if style.color().alpha == 0.0 {
+            return Ok(());
+        }
+        let width = match self.line_width(style) {
+            Some(width) => width,
+            None => return Ok(()),
+        };
+
+        let mut points = vec![];
+        for p in vert {
+            match self.stroke_coord(p)? {
+                Some(p) => points.push(p),
+                None => return Ok(()),
+            }
+        }
+        let points = offset_polygon(&points, self.stroke_alignment.offset(width));
+
+        let bounds = match Rect::bounding(&points) {
+            Some(bounds) => bounds,
+            None => return Ok(()),
+        };
+        if !self.begin_primitive(PrimitiveKind::Polygon, bounds.inflate(width / 2.0)) {
+            return Ok(());
+        }
+
+        self.set_line_style(style);
+        self.apply_line_join(&points, true);
+        self.context.begin_path();
+        for (i, &(x, y)) in points.iter().enumerate() {
+            if i == 0 {
+                self.context.move_to(x, y);
+            } else {
+                self.context.line_to(x, y);
+            }
+        }
+        self.context.close_path();
+        self.stroke_current_path();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_aligned_rect() {
+        let rect = Rect::new(10.0, 10.0, 20.0, 30.0);
+        assert_eq!(
+            aligned_rect(rect, 4.0, StrokeAlignment::Inner),
+            Rect::new(12.0, 12.0, 18.0, 28.0)
+        );
+        assert_eq!(
+            aligned_rect(rect, 4.0, StrokeAlignment::Outer),
+            Rect::new(8.0, 8.0, 22.0, 32.0)
+        );
+        assert_eq!(aligned_rect(rect, 4.0, StrokeAlignment::Center), rect);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_offset_square() {
+        // Both orientations grow outward
+        let cw = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+        let ccw = [(0.0, 0.0), (0.0, 10.0), (10.0, 10.0), (10.0, 0.0)];
+        for square in [&cw[..], &ccw[..]].iter() {
+            let grown = offset_polygon(square, 1.0);
+            assert!(grown
+                .iter()
+                .any(|p| (p.0 + 1.0).abs() < 1e-9 && (p.1 + 1.0).abs() < 1e-9));
+            assert!(grown
+                .iter()
+                .any(|p| (p.0 - 11.0).abs() < 1e-9 && (p.1 - 11.0).abs() < 1e-9));
+        }
+    }
+}
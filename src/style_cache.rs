@@ -0,0 +1,141 @@
+use std::cell::{Cell, RefCell};
+
+use plotters_backend::BackendColor;
+use web_sys::OffscreenCanvasRenderingContext2d;
+
+use crate::color::{set_fill_css, set_stroke_css, write_canvas_color};
+use crate::style_ext::LineJoin;
+
+/// The fill, stroke, line width, join and font last set on the context, so that draw calls with the
+/// same style don't set them again
+///  - None if the value on the context is unknown, e.g. after a restore() or a fill with a
+///    gradient
+///  - The cache only follows the context through its setters: code setting the context
+///    directly outside of a save()/restore() pair has to forget the values it changed
+#[derive(Default)]
+pub(crate) struct StyleCache {
+    fill: Cell<Option<((u8, u8, u8), f64)>>,
+    stroke: Cell<Option<((u8, u8, u8), f64)>>,
+    line_width: Cell<Option<f64>>,
+    line_join: Cell<Option<LineJoin>>,
+    miter_limit: Cell<Option<f64>>,
+    font: RefCell<Option<String>>,
+    /// Reused to format the CSS colors
+    css: RefCell<String>,
+}
+
+impl StyleCache {
+    /// Set the fill color, already through the compositing mode
+    pub(crate) fn set_fill(
+        &self,
+        context: &OffscreenCanvasRenderingContext2d,
+        color: BackendColor,
+    ) {
+        let key = (color.rgb, color.alpha);
+        if self.fill.get() != Some(key) {
+            let mut css = self.css.borrow_mut();
+            write_canvas_color(&mut css, color);
+            set_fill_css(context, &css);
+            self.fill.set(Some(key));
+        }
+    }
+
+    /// Set the stroke color, already through the compositing mode
+    pub(crate) fn set_stroke(
+        &self,
+        context: &OffscreenCanvasRenderingContext2d,
+        color: BackendColor,
+    ) {
+        let key = (color.rgb, color.alpha);
+        if self.stroke.get() != Some(key) {
+            let mut css = self.css.borrow_mut();
+            write_canvas_color(&mut css, color);
+            set_stroke_css(context, &css);
+            self.stroke.set(Some(key));
+        }
+    }
+
+    pub(crate) fn set_line_width(&self, context: &OffscreenCanvasRenderingContext2d, width: f64) {
+        if self.line_width.get() != Some(width) {
+            context.set_line_width(width);
+            self.line_width.set(Some(width));
+        }
+    }
+
+    pub(crate) fn set_line_join(
+        &self,
+        context: &OffscreenCanvasRenderingContext2d,
+        join: LineJoin,
+    ) {
+        if self.line_join.get() != Some(join) {
+            context.set_line_join(join.as_str());
+            self.line_join.set(Some(join));
+        }
+    }
+
+    pub(crate) fn set_miter_limit(&self, context: &OffscreenCanvasRenderingContext2d, limit: f64) {
+        if self.miter_limit.get() != Some(limit) {
+            context.set_miter_limit(limit);
+            self.miter_limit.set(Some(limit));
+        }
+    }
+
+    pub(crate) fn set_font(&self, context: &OffscreenCanvasRenderingContext2d, font: &str) {
+        let mut current = self.font.borrow_mut();
+        if current.as_deref() != Some(font) {
+            context.set_font(font);
+            *current = Some(font.to_string());
+        }
+    }
+
+    /// The fill style was set to something else than a color
+    pub(crate) fn forget_fill(&self) {
+        self.fill.set(None);
+    }
+
+    /// The context state changed behind the cache (restore, reset, resize, ...)
+    pub(crate) fn forget(&self) {
+        self.fill.set(None);
+        self.stroke.set(None);
+        self.line_width.set(None);
+        self.line_join.set(None);
+        self.miter_limit.set(None);
+        *self.font.borrow_mut() = None;
+    }
+
+    #[cfg(test)]
+    pub(crate) fn font(&self) -> Option<String> {
+        self.font.borrow().clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::canvas::OffscreenCanvasBackend;
+    use crate::gradient::GradientStyle;
+    use crate::style_ext::CanvasShapeStyle;
+    use plotters::prelude::*;
+    use plotters_backend::DrawingBackend;
+    use wasm_bindgen_test::*;
+    use web_sys::OffscreenCanvas;
+
+    #[wasm_bindgen_test]
+    fn test_style_cache() {
+        let canvas = OffscreenCanvas::new(30, 10).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        backend.draw_rect((0, 0), (10, 10), &RED, true).unwrap();
+        assert_eq!(backend.style_cache.fill.get(), Some(((255, 0, 0), 1.0)));
+
+        // A gradient replaces the fill style, the next red fill sets it again
+        let gradient = GradientStyle::linear((10.0, 0.0), (20.0, 0.0))
+            .stop(0.0, BLUE.to_backend_color().into())
+            .stop(1.0, BLUE.to_backend_color().into());
+        let style = CanvasShapeStyle::new(GREEN.filled()).gradient(gradient);
+        backend.draw_rect((10, 0), (20, 10), &style, true).unwrap();
+        assert_eq!(backend.style_cache.fill.get(), None);
+        backend.draw_rect((20, 0), (30, 10), &RED, true).unwrap();
+
+        assert_eq!(backend.pick_color(15, 5).unwrap().rgb, (0, 0, 255));
+        assert_eq!(backend.pick_color(25, 5).unwrap().rgb, (255, 0, 0));
+    }
+}
@@ -0,0 +1,494 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use plotters::style::{RGBAColor, ShapeStyle};
+use plotters_backend::{BackendColor, BackendStyle};
+use wasm_bindgen::JsValue;
+
+use crate::canvas::OffscreenCanvasBackend;
+use crate::command::CommandColor;
+use crate::gradient::GradientStyle;
+use crate::layers::BlendMode;
+use crate::pattern::PatternFill;
+
+/// How the ends of the strokes are drawn
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineCap {
+    Butt,
+    Round,
+    Square,
+}
+
+impl LineCap {
+    /// The value of lineCap for the cap
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineCap::Butt => "butt",
+            LineCap::Round => "round",
+            LineCap::Square => "square",
+        }
+    }
+}
+
+/// How the corners of the strokes are drawn
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineJoin {
+    Miter,
+    Round,
+    Bevel,
+}
+
+impl LineJoin {
+    /// The value of lineJoin for the join
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineJoin::Miter => "miter",
+            LineJoin::Round => "round",
+            LineJoin::Bevel => "bevel",
+        }
+    }
+}
+
+/// A drop shadow cast by the shapes drawn with a style
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Shadow {
+    pub color: CommandColor,
+    /// Blur radius, in pixels
+    pub blur: f64,
+    pub offset: (f64, f64),
+}
+
+/// The canvas attributes a style carries beyond its color and width
+///  - None keeps the default of the canvas: solid lines, butt caps, the joins picked by the
+///    backend, no shadow, source-over compositing, the fill color of the style
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StyleExtras {
+    /// Lengths of the dashes and gaps, in pixels
+    pub dash: Option<Vec<f64>>,
+    /// Distance into the dash pattern the strokes start at, in pixels
+    pub dash_offset: Option<f64>,
+    pub line_cap: Option<LineCap>,
+    pub line_join: Option<LineJoin>,
+    pub shadow: Option<Shadow>,
+    pub composite: Option<BlendMode>,
+    /// Fill the shapes with a gradient instead of the color of the style
+    pub gradient: Option<GradientStyle>,
+    /// Fill the shapes with a hatch pattern, it takes precedence over the gradient
+    pub pattern: Option<PatternFill>,
+}
+
+/// Styles with extras registered at once, the least recently used one is evicted beyond
+pub const MAX_REGISTERED_STYLES: usize = 1024;
+
+/// Alpha difference between consecutive tags: the tags stay within
+/// `MAX_REGISTERED_STYLES * TAG_STEP` (about 1e-4) of the alpha of the style, far below the
+/// 1/255 steps of the canvas
+const TAG_STEP: f64 = 1e-7;
+
+/// Identifies a style by the exact values the backend receives
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct StyleKey {
+    rgb: (u8, u8, u8),
+    alpha: u64,
+    width: u32,
+}
+
+impl StyleKey {
+    fn new(rgb: (u8, u8, u8), alpha: f64, width: u32) -> Self {
+        Self {
+            rgb,
+            alpha: alpha.to_bits(),
+            width,
+        }
+    }
+}
+
+struct Registered {
+    /// The style before it was tagged
+    style: StyleKey,
+    extras: StyleExtras,
+    /// Registration or lookup count when the tag was last used, for the eviction
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Registry {
+    /// The tagged styles, by the values the backend receives
+    by_tag: HashMap<StyleKey, Registered>,
+    /// The tags given to a style, one per set of extras
+    by_style: HashMap<StyleKey, Vec<StyleKey>>,
+    clock: u64,
+    next_tag: usize,
+}
+
+impl Registry {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    /// The tag of a style with its extras, if it is registered
+    fn find(&mut self, style: StyleKey, extras: &StyleExtras) -> Option<StyleKey> {
+        let by_tag = &self.by_tag;
+        let tag = *self
+            .by_style
+            .get(&style)?
+            .iter()
+            .find(|tag| by_tag.get(tag).map_or(false, |r| &r.extras == extras))?;
+        let now = self.tick();
+        if let Some(entry) = self.by_tag.get_mut(&tag) {
+            entry.last_used = now;
+        }
+        Some(tag)
+    }
+
+    fn lookup(&mut self, tag: StyleKey) -> Option<(f64, StyleExtras)> {
+        let now = self.tick();
+        let entry = self.by_tag.get_mut(&tag)?;
+        entry.last_used = now;
+        Some((f64::from_bits(entry.style.alpha), entry.extras.clone()))
+    }
+
+    fn evict_least_recently_used(&mut self) {
+        let oldest = self
+            .by_tag
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(tag, entry)| (*tag, entry.style));
+        if let Some((tag, style)) = oldest {
+            self.by_tag.remove(&tag);
+            if let Some(tags) = self.by_style.get_mut(&style) {
+                tags.retain(|t| *t != tag);
+                if tags.is_empty() {
+                    self.by_style.remove(&style);
+                }
+            }
+        }
+    }
+
+    fn insert(&mut self, style: StyleKey, extras: StyleExtras) -> StyleKey {
+        while self.by_tag.len() >= MAX_REGISTERED_STYLES {
+            self.evict_least_recently_used();
+        }
+        let alpha = f64::from_bits(style.alpha);
+        // Every registered tag blocks at most one slot of this style, one of them is free
+        let tag = (0..MAX_REGISTERED_STYLES)
+            .map(|i| {
+                let slot = (self.next_tag + i) % MAX_REGISTERED_STYLES + 1;
+                let shift = slot as f64 * TAG_STEP;
+                // Towards the middle, so the tagged alpha stays within 0..=1
+                let tagged = if alpha >= 0.5 {
+                    alpha - shift
+                } else {
+                    alpha + shift
+                };
+                (slot, StyleKey::new(style.rgb, tagged, style.width))
+            })
+            .find(|(_, tag)| !self.by_tag.contains_key(tag));
+        let (slot, tag) = tag.expect("a registry below its capacity has a free tag");
+        self.next_tag = slot % MAX_REGISTERED_STYLES;
+
+        let last_used = self.tick();
+        self.by_tag.insert(
+            tag,
+            Registered {
+                style,
+                extras,
+                last_used,
+            },
+        );
+        self.by_style.entry(style).or_default().push(tag);
+        tag
+    }
+}
+
+thread_local! {
+    static STYLES: RefCell<Registry> = RefCell::new(Registry::default());
+}
+
+/// Plotters only hands `color()` and `stroke_width()` of a style to the backend, so the extras
+/// travel through a side channel: the style gets a unique, visually identical alpha value
+/// (the difference is far below the 8 bit precision of the canvas) the backend looks up.
+///  - Registering the same style with the same extras again reuses its tag
+///  - At most [MAX_REGISTERED_STYLES] styles are kept, e.g. a dash offset animated every frame
+///    evicts the styles of the previous frames
+fn register(style: ShapeStyle, extras: &StyleExtras) -> ShapeStyle {
+    let RGBAColor(r, g, b, a) = style.color;
+    let key = StyleKey::new((r, g, b), a, style.stroke_width);
+
+    STYLES.with(|styles| {
+        let mut styles = styles.borrow_mut();
+        let tag = match styles.find(key, extras) {
+            Some(tag) => tag,
+            None => styles.insert(key, extras.clone()),
+        };
+        ShapeStyle {
+            color: RGBAColor(r, g, b, f64::from_bits(tag.alpha)),
+            ..style
+        }
+    })
+}
+
+/// The extras registered for a style, and the alpha it had before it was tagged
+fn lookup(color: BackendColor, width: u32) -> Option<(f64, StyleExtras)> {
+    STYLES.with(|styles| {
+        styles
+            .borrow_mut()
+            .lookup(StyleKey::new(color.rgb, color.alpha, width))
+    })
+}
+
+/// A style carrying canvas attributes plotters can't express: dashes, caps and joins, shadows,
+/// compositing, gradients and patterns
+///  - It converts into a ShapeStyle for the plotters elements, and can be passed directly to
+///    the backend methods; other backends draw it as the plain style
+///
+/// ```ignore
+/// let style = CanvasShapeStyle::new(&BLUE)
+///     .line_cap(LineCap::Round)
+///     .shadow(Shadow {
+///         color: BLACK.to_backend_color().into(),
+///         blur: 4.0,
+///         offset: (0.0, 2.0),
+///     });
+/// chart.draw_series(LineSeries::new(data, style))?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct CanvasShapeStyle {
+    style: ShapeStyle,
+    extras: StyleExtras,
+}
+
+impl CanvasShapeStyle {
+    /// Wrap a style, keeping the extras it already carries (e.g. a dashed style)
+    pub fn new<S: Into<ShapeStyle>>(style: S) -> Self {
+        let mut style = style.into();
+        let extras = match lookup(style.color(), style.stroke_width) {
+            Some((alpha, extras)) => {
+                style.color.3 = alpha;
+                extras
+            }
+            None => StyleExtras::default(),
+        };
+        Self { style, extras }
+    }
+
+    fn with_extras(mut self, update: impl FnOnce(&mut StyleExtras)) -> Self {
+        update(&mut self.extras);
+        self
+    }
+
+    /// Dash the strokes, `pattern` alternates the lengths of dashes and gaps in pixels
+    pub fn dash(self, pattern: &[f64]) -> Self {
+        self.with_extras(|e| e.dash = Some(pattern.to_vec()))
+    }
+
+    /// Shift the dash pattern along the strokes, e.g. to animate marching ants
+    pub fn dash_offset(self, offset: f64) -> Self {
+        self.with_extras(|e| e.dash_offset = Some(offset))
+    }
+
+    pub fn line_cap(self, cap: LineCap) -> Self {
+        self.with_extras(|e| e.line_cap = Some(cap))
+    }
+
+    /// Use this join everywhere, instead of the joins picked from the sharpness of the corners
+    pub fn line_join(self, join: LineJoin) -> Self {
+        self.with_extras(|e| e.line_join = Some(join))
+    }
+
+    pub fn shadow(self, shadow: Shadow) -> Self {
+        self.with_extras(|e| e.shadow = Some(shadow))
+    }
+
+    /// Blend the shapes with what's already drawn
+    pub fn composite(self, mode: BlendMode) -> Self {
+        self.with_extras(|e| e.composite = Some(mode))
+    }
+
+    /// Fill the shapes with a gradient, strokes keep the color of the style
+    pub fn gradient(self, gradient: GradientStyle) -> Self {
+        self.with_extras(|e| e.gradient = Some(gradient))
+    }
+
+    /// Fill the shapes with a hatch pattern, strokes keep the color of the style
+    pub fn pattern(self, pattern: PatternFill) -> Self {
+        self.with_extras(|e| e.pattern = Some(pattern))
+    }
+
+    pub fn extras(&self) -> &StyleExtras {
+        &self.extras
+    }
+
+    /// The plain style, with the tagged color the backend recognizes
+    ///  - The style is registered again if it was evicted meanwhile
+    pub fn to_shape_style(&self) -> ShapeStyle {
+        if self.extras == StyleExtras::default() {
+            return self.style;
+        }
+        register(self.style, &self.extras)
+    }
+}
+
+impl From<CanvasShapeStyle> for ShapeStyle {
+    fn from(style: CanvasShapeStyle) -> Self {
+        style.to_shape_style()
+    }
+}
+
+impl From<&CanvasShapeStyle> for ShapeStyle {
+    fn from(style: &CanvasShapeStyle) -> Self {
+        style.to_shape_style()
+    }
+}
+
+impl BackendStyle for CanvasShapeStyle {
+    fn color(&self) -> BackendColor {
+        self.to_shape_style().color()
+    }
+
+    fn stroke_width(&self) -> u32 {
+        self.style.stroke_width
+    }
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Set the attributes registered for the style on the context, or go back to the defaults
+    ///  - The backend cap and join apply when the style doesn't set its own, the join is miter
+    ///    otherwise, see apply_line_join() for the polylines with acute corners
+    ///  - `polyline` strokes (lines and paths) also get the dash of the backend line dash mode
+    pub(crate) fn apply_style_extras<S: BackendStyle>(&mut self, style: &S, polyline: bool) {
+        let mut extras = lookup(style.color(), style.stroke_width())
+            .map(|(_, extras)| extras)
+            .unwrap_or_default();
+        if polyline && extras.dash.is_none() {
+            if let Some((pattern, offset)) = &self.line_dash {
+                extras.dash = Some(pattern.clone());
+                extras.dash_offset = Some(*offset);
+            }
+        }
+        if extras.line_cap.is_none() && self.line_cap != LineCap::Butt {
+            extras.line_cap = Some(self.line_cap);
+        }
+        if extras.line_join.is_none() {
+            extras.line_join = self.line_join;
+        }
+        // Set on every stroke, a polyline with acute corners may have rounded its joins
+        self.style_cache
+            .set_line_join(&self.context, extras.line_join.unwrap_or(LineJoin::Miter));
+        self.style_cache
+            .set_miter_limit(&self.context, self.miter_limit);
+        if self.applied_extras.as_ref() == Some(&extras) {
+            return;
+        }
+
+        let segments: js_sys::Array = extras
+            .dash
+            .iter()
+            .flatten()
+            .map(|&v| JsValue::from(v))
+            .collect();
+        let _ = self.context.set_line_dash(&segments);
+        self.context
+            .set_line_dash_offset(extras.dash_offset.unwrap_or(0.0));
+        self.context
+            .set_line_cap(extras.line_cap.unwrap_or(LineCap::Butt).as_str());
+
+        let shadow = extras.shadow.unwrap_or(Shadow {
+            color: CommandColor {
+                rgb: (0, 0, 0),
+                alpha: 0.0,
+            },
+            blur: 0.0,
+            offset: (0.0, 0.0),
+        });
+        let (r, g, b) = shadow.color.rgb;
+        self.context
+            .set_shadow_color(&format!("rgba({},{},{},{})", r, g, b, shadow.color.alpha));
+        self.context.set_shadow_blur(shadow.blur);
+        self.context.set_shadow_offset_x(shadow.offset.0);
+        self.context.set_shadow_offset_y(shadow.offset.1);
+
+        let composite = extras.composite.unwrap_or_default();
+        let _ = self
+            .context
+            .set_global_composite_operation(composite.as_str());
+
+        self.applied_extras = Some(extras);
+    }
+
+    /// The line join the style or the backend asks for, if any
+    pub(crate) fn style_line_join(&self) -> Option<LineJoin> {
+        self.applied_extras.as_ref().and_then(|e| e.line_join)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use plotters::prelude::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_canvas_shape_style_side_channel() {
+        let style = CanvasShapeStyle::new(&RED)
+            .line_cap(LineCap::Round)
+            .composite(BlendMode::Multiply);
+        let plain: ShapeStyle = style.clone().into();
+        assert_ne!(plain.color.3, 1.0);
+        assert_eq!(ShapeStyle::from(&style).color.3, plain.color.3);
+
+        let (alpha, extras) = lookup(plain.color(), plain.stroke_width).unwrap();
+        assert_eq!(alpha, 1.0);
+        assert_eq!(extras.line_cap, Some(LineCap::Round));
+
+        // Wrapping a tagged style keeps its extras
+        let again = CanvasShapeStyle::new(plain).line_join(LineJoin::Bevel);
+        assert_eq!(again.extras().line_cap, Some(LineCap::Round));
+        assert_eq!(again.extras().line_join, Some(LineJoin::Bevel));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_registry_is_bounded() {
+        let kept = CanvasShapeStyle::new(&RED).line_cap(LineCap::Round);
+        let first = kept.to_shape_style();
+
+        // A dash offset animated every frame registers a new style each time
+        for frame in 0..MAX_REGISTERED_STYLES * 2 {
+            let style = CanvasShapeStyle::new(BLUE.mix(0.5))
+                .dash(&[4.0, 2.0])
+                .dash_offset(frame as f64);
+            let alpha = style.to_shape_style().color.3;
+            assert!((alpha - 0.5).abs() <= MAX_REGISTERED_STYLES as f64 * TAG_STEP);
+        }
+        STYLES.with(|styles| {
+            assert!(styles.borrow().by_tag.len() <= MAX_REGISTERED_STYLES);
+        });
+
+        // The evicted style is registered again when it is used
+        assert_eq!(lookup(first.color(), first.stroke_width), None);
+        let again = kept.to_shape_style();
+        let (alpha, extras) = lookup(again.color(), again.stroke_width).unwrap();
+        assert_eq!(alpha, 1.0);
+        assert_eq!(extras.line_cap, Some(LineCap::Round));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_new_backend_clears_previous_extras() {
+        use plotters_backend::DrawingBackend;
+        use web_sys::OffscreenCanvas;
+
+        let canvas = OffscreenCanvas::new(10, 10).unwrap();
+        let context = crate::canvas::context_2d(&canvas).unwrap();
+        let dash: js_sys::Array = [4.0, 2.0].iter().map(|&v| JsValue::from(v)).collect();
+        context.set_line_dash(&dash).unwrap();
+        context.set_line_cap("round");
+
+        // A backend created on a context which already drew doesn't trust its state
+        let mut backend = OffscreenCanvasBackend::with_context(&canvas, context);
+        backend.draw_line((0, 5), (10, 5), &BLACK).unwrap();
+        backend.present().unwrap();
+        assert_eq!(backend.context.get_line_dash().length(), 0);
+        assert_eq!(backend.context.line_cap(), "butt");
+    }
+}
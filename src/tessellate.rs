@@ -0,0 +1,245 @@
+use lyon::math::{point, Box2D, Point};
+use lyon::path::Path;
+use lyon::tessellation::{
+    BuffersBuilder, FillOptions, FillTessellator, FillVertex, LineCap, LineJoin, StrokeOptions,
+    StrokeTessellator, StrokeVertex, TessellationError, VertexBuffers,
+};
+use plotters_backend::BackendCoord;
+
+use crate::canvas::CanvasError;
+
+/// A triangle mesh, three indices into `vertices` per triangle
+///  - This is the geometry format shared by the accelerated rendering paths
+#[derive(Clone, Debug, Default)]
+pub struct Mesh {
+    pub vertices: Vec<[f32; 2]>,
+    pub indices: Vec<u32>,
+}
+
+impl Mesh {
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.indices.clear();
+    }
+}
+
+/// Turn the backend primitives into triangle meshes
+///  - The tessellators are kept around so that their internal buffers are reused between shapes
+pub struct Tessellator {
+    fill: FillTessellator,
+    stroke: StrokeTessellator,
+    pub tolerance: f32,
+    pub line_join: LineJoin,
+    pub line_cap: LineCap,
+}
+
+impl Default for Tessellator {
+    fn default() -> Self {
+        Self {
+            fill: FillTessellator::new(),
+            stroke: StrokeTessellator::new(),
+            tolerance: 0.1,
+            line_join: LineJoin::Miter,
+            line_cap: LineCap::Butt,
+        }
+    }
+}
+
+fn to_point(p: BackendCoord) -> Point {
+    point(p.0 as f32, p.1 as f32)
+}
+
+fn build_path(points: &[BackendCoord], close: bool) -> Option<Path> {
+    let (first, rest) = points.split_first()?;
+    let mut builder = Path::builder();
+    builder.begin(to_point(*first));
+    for p in rest {
+        builder.line_to(to_point(*p));
+    }
+    builder.end(close);
+    Some(builder.build())
+}
+
+fn tessellation_error(e: TessellationError) -> CanvasError {
+    CanvasError::Other(format!("tessellation failed: {:?}", e))
+}
+
+impl Tessellator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn fill_options(&self) -> FillOptions {
+        FillOptions::tolerance(self.tolerance)
+    }
+
+    fn stroke_options(&self, width: u32) -> StrokeOptions {
+        StrokeOptions::tolerance(self.tolerance)
+            .with_line_width(width as f32)
+            .with_line_join(self.line_join)
+            .with_line_cap(self.line_cap)
+    }
+
+    /// Append the triangles of a stroked polyline to the mesh
+    pub fn stroke_path(
+        &mut self,
+        points: &[BackendCoord],
+        width: u32,
+        closed: bool,
+        mesh: &mut Mesh,
+    ) -> Result<(), CanvasError> {
+        let path = match build_path(points, closed) {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let options = self.stroke_options(width);
+        let mut buffers = into_buffers(mesh);
+        let result = self.stroke.tessellate_path(
+            &path,
+            &options,
+            &mut BuffersBuilder::new(&mut buffers, |v: StrokeVertex| v.position().to_array()),
+        );
+        from_buffers(buffers, mesh);
+        result.map_err(tessellation_error)
+    }
+
+    /// Append the triangles of a filled polygon to the mesh
+    pub fn fill_polygon(
+        &mut self,
+        points: &[BackendCoord],
+        mesh: &mut Mesh,
+    ) -> Result<(), CanvasError> {
+        let path = match build_path(points, true) {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let options = self.fill_options();
+        let mut buffers = into_buffers(mesh);
+        let result = self.fill.tessellate_path(
+            &path,
+            &options,
+            &mut BuffersBuilder::new(&mut buffers, |v: FillVertex| v.position().to_array()),
+        );
+        from_buffers(buffers, mesh);
+        result.map_err(tessellation_error)
+    }
+
+    /// Append the triangles of a rectangle, either filled or stroked with `width`
+    pub fn rect(
+        &mut self,
+        upper_left: BackendCoord,
+        bottom_right: BackendCoord,
+        fill: bool,
+        width: u32,
+        mesh: &mut Mesh,
+    ) -> Result<(), CanvasError> {
+        let rect = Box2D::new(to_point(upper_left), to_point(bottom_right));
+        let mut buffers = into_buffers(mesh);
+        let result = if fill {
+            let options = self.fill_options();
+            self.fill.tessellate_rectangle(
+                &rect,
+                &options,
+                &mut BuffersBuilder::new(&mut buffers, |v: FillVertex| v.position().to_array()),
+            )
+        } else {
+            let options = self.stroke_options(width);
+            self.stroke.tessellate_rectangle(
+                &rect,
+                &options,
+                &mut BuffersBuilder::new(&mut buffers, |v: StrokeVertex| {
+                    v.position().to_array()
+                }),
+            )
+        };
+        from_buffers(buffers, mesh);
+        result.map_err(tessellation_error)
+    }
+
+    /// Append the triangles of a circle, either filled or stroked with `width`
+    pub fn circle(
+        &mut self,
+        center: BackendCoord,
+        radius: u32,
+        fill: bool,
+        width: u32,
+        mesh: &mut Mesh,
+    ) -> Result<(), CanvasError> {
+        let center = to_point(center);
+        let radius = radius as f32;
+        let mut buffers = into_buffers(mesh);
+        let result = if fill {
+            let options = self.fill_options();
+            self.fill.tessellate_circle(
+                center,
+                radius,
+                &options,
+                &mut BuffersBuilder::new(&mut buffers, |v: FillVertex| v.position().to_array()),
+            )
+        } else {
+            let options = self.stroke_options(width);
+            self.stroke.tessellate_circle(
+                center,
+                radius,
+                &options,
+                &mut BuffersBuilder::new(&mut buffers, |v: StrokeVertex| {
+                    v.position().to_array()
+                }),
+            )
+        };
+        from_buffers(buffers, mesh);
+        result.map_err(tessellation_error)
+    }
+}
+
+fn into_buffers(mesh: &mut Mesh) -> VertexBuffers<[f32; 2], u32> {
+    VertexBuffers {
+        vertices: std::mem::take(&mut mesh.vertices),
+        indices: std::mem::take(&mut mesh.indices),
+    }
+}
+
+fn from_buffers(buffers: VertexBuffers<[f32; 2], u32>, mesh: &mut Mesh) {
+    mesh.vertices = buffers.vertices;
+    mesh.indices = buffers.indices;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    /// Area covered by the triangles of the mesh
+    fn area(mesh: &Mesh) -> f32 {
+        assert_eq!(mesh.indices.len() % 3, 0);
+        mesh.indices
+            .chunks(3)
+            .map(|t| {
+                let v = |i: usize| mesh.vertices[t[i] as usize];
+                let (a, b, c) = (v(0), v(1), v(2));
+                ((b[0] - a[0]) * (c[1] - a[1]) - (c[0] - a[0]) * (b[1] - a[1])).abs() / 2.0
+            })
+            .sum()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_fill_polygon_triangles() {
+        let mut tessellator = Tessellator::new();
+        let mut mesh = Mesh::default();
+
+        // An L shape, its notch isn't covered
+        let concave = [(0, 0), (20, 0), (20, 10), (10, 10), (10, 20), (0, 20)];
+        tessellator.fill_polygon(&concave, &mut mesh).unwrap();
+        assert!((area(&mesh) - 300.0).abs() < 1e-3);
+
+        // A bow tie crossing itself is two triangles meeting at the crossing
+        mesh.clear();
+        let bow_tie = [(0, 0), (10, 10), (10, 0), (0, 10)];
+        tessellator.fill_polygon(&bow_tie, &mut mesh).unwrap();
+        assert!((area(&mesh) - 50.0).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,76 @@
+use plotters_backend::{BackendCoord, BackendTextStyle, DrawingErrorKind};
+
+use crate::canvas::{font_string, CanvasError, OffscreenCanvasBackend};
+use crate::color::make_canvas_color;
+use crate::command::{DrawCommand, TextCommandStyle};
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Draw many texts at once, e.g. the tick labels of the axes and the data labels
+    ///  - The texts are grouped by font and color, so that each font is set once on the context
+    ///  - Within a group the texts keep their order, but a text may be drawn after a text with
+    ///    another font which comes later in `texts`: overlapping labels of different fonts can
+    ///    stack differently than with draw_text()
+    pub fn draw_texts<T: AsRef<str>, S: BackendTextStyle>(
+        &mut self,
+        texts: &[(T, BackendCoord, &S)],
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        let mut order: Vec<(String, String, usize)> = texts
+            .iter()
+            .enumerate()
+            .map(|(i, (_, _, style))| (font_string(*style), make_canvas_color(style.color()), i))
+            .collect();
+        order.sort();
+
+        let mut result = Ok(());
+        for (_, _, i) in order {
+            let (text, pos, style) = &texts[i];
+            result = if self.middleware.is_empty() {
+                self.execute_text(text.as_ref(), *style, *pos)
+            } else {
+                self.submit(DrawCommand::Text {
+                    text: text.as_ref().to_string(),
+                    pos: *pos,
+                    style: TextCommandStyle::from_style(*style),
+                })
+            };
+            if result.is_err() {
+                break;
+            }
+        }
+        result
+    }
+
+    /// Set the font of a text, only when it changes
+    pub(crate) fn set_text_font(&mut self, font: &str) {
+        self.style_cache.set_font(&self.context, font);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use plotters::prelude::*;
+    use wasm_bindgen_test::*;
+    use web_sys::OffscreenCanvas;
+
+    #[wasm_bindgen_test]
+    fn test_draw_texts() {
+        let canvas = OffscreenCanvas::new(200, 100).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        let small = ("sans-serif", 10).into_font().color(&BLACK);
+        let large = ("serif", 24).into_font().color(&BLACK);
+        backend
+            .draw_texts(&[
+                ("1", (10, 10), &small),
+                ("Title", (50, 50), &large),
+                ("2", (30, 10), &small),
+            ])
+            .unwrap();
+
+        assert_eq!(backend.frame_stats().unwrap().total(), 3);
+        assert_eq!(
+            backend.style_cache.font().as_deref(),
+            Some("normal 24px serif")
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use plotters_backend::DrawingErrorKind;
+
+use crate::canvas::{error_cast, CanvasError, OffscreenCanvasBackend};
+use crate::color::{make_canvas_color, set_stroke_css};
+use crate::command::CommandColor;
+use crate::style_ext::Shadow;
+
+/// Contrast effect applied to all the texts of a backend, so that labels stay legible over
+/// dense or dark data
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextEffect {
+    None,
+    /// A drop shadow under the glyphs
+    Shadow(Shadow),
+    /// An outline of `width` pixels around the glyphs, painted under them
+    Halo {
+        color: CommandColor,
+        width: f64,
+    },
+}
+
+impl Default for TextEffect {
+    fn default() -> Self {
+        TextEffect::None
+    }
+}
+
+impl TextEffect {
+    /// A white halo, the usual choice for dark labels over data
+    pub fn halo(width: f64) -> Self {
+        TextEffect::Halo {
+            color: CommandColor {
+                rgb: (255, 255, 255),
+                alpha: 0.8,
+            },
+            width,
+        }
+    }
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Set the effect all the texts are drawn with
+    pub fn set_text_effect(&mut self, effect: TextEffect) {
+        self.text_effect = effect;
+    }
+
+    pub fn text_effect(&self) -> TextEffect {
+        self.text_effect
+    }
+
+    /// Fill a text whose font, alignment and color are set, with the text effect
+    ///  - The context state the effect changes is restored afterwards
+    pub(crate) fn fill_text_with_effect(
+        &self,
+        text: &str,
+        x: f64,
+        y: f64,
+    ) -> Result<(), DrawingErrorKind<CanvasError>> {
+        let ctx = &self.context;
+        match self.text_effect {
+            TextEffect::None => ctx.fill_text(text, x, y).map_err(error_cast),
+            TextEffect::Shadow(shadow) => {
+                ctx.save();
+                ctx.set_shadow_color(&make_canvas_color(shadow.color.to_backend_color()));
+                ctx.set_shadow_blur(shadow.blur);
+                ctx.set_shadow_offset_x(shadow.offset.0);
+                ctx.set_shadow_offset_y(shadow.offset.1);
+                let result = ctx.fill_text(text, x, y).map_err(error_cast);
+                ctx.restore();
+                result
+            }
+            TextEffect::Halo { color, width } => {
+                ctx.save();
+                let color = self.compositing.apply(color.to_backend_color());
+                set_stroke_css(ctx, &make_canvas_color(color));
+                // The stroke is centered on the glyph outlines, the fill covers its inner half
+                ctx.set_line_width(width * 2.0);
+                ctx.set_line_join("round");
+                let _ = ctx.set_line_dash(&js_sys::Array::new());
+                let result = ctx
+                    .stroke_text(text, x, y)
+                    .and_then(|_| ctx.fill_text(text, x, y))
+                    .map_err(error_cast);
+                ctx.restore();
+                result
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use plotters::prelude::*;
+    use plotters_backend::DrawingBackend;
+    use wasm_bindgen_test::*;
+    use web_sys::OffscreenCanvas;
+
+    #[wasm_bindgen_test]
+    fn test_text_halo() {
+        let canvas = OffscreenCanvas::new(60, 30).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        backend.set_text_effect(TextEffect::Halo {
+            color: RED.to_backend_color().into(),
+            width: 4.0,
+        });
+        let style = ("sans-serif", 20).into_font().color(&BLACK);
+        backend.draw_text("I", &style, (20, 5)).unwrap();
+
+        // The halo paints the pixels left of the glyph, the line width is restored
+        assert!(backend.pick_color(19, 15).unwrap().alpha > 0.0);
+        assert_eq!(backend.context.line_width(), 1.0);
+    }
+}
@@ -0,0 +1,91 @@
+use plotters_backend::text_anchor::{HPos, Pos, VPos};
+use plotters_backend::FontTransform;
+
+use crate::canvas::OffscreenCanvasBackend;
+
+/// How the anchor of a rotated text is interpreted
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextRotation {
+    /// The text is anchored in its own frame, then rotated around the anchored point, as the
+    /// default plotters text rendering does
+    AroundAnchor,
+    /// The text is rotated first, then the anchor is applied to the rotated text box on the
+    /// screen: a Rotate90 label anchored Right/Center ends at the point, left of it
+    AnchorAfterRotation,
+}
+
+impl Default for TextRotation {
+    fn default() -> Self {
+        TextRotation::AroundAnchor
+    }
+}
+
+/// The rotation of a font transform, in radians
+pub(crate) fn rotation_radians(transform: FontTransform) -> f64 {
+    let degrees = match transform {
+        FontTransform::None => 0.0,
+        FontTransform::Rotate90 => 90.0,
+        FontTransform::Rotate180 => 180.0,
+        FontTransform::Rotate270 => 270.0,
+    };
+    degrees.to_radians()
+}
+
+/// Center of a `(width, height)` text whose rotated box is anchored at `pos`
+pub(crate) fn anchored_center(
+    pos: (f64, f64),
+    (width, height): (f64, f64),
+    anchor: Pos,
+    transform: FontTransform,
+) -> (f64, f64) {
+    let (w, h) = match transform {
+        FontTransform::Rotate90 | FontTransform::Rotate270 => (height, width),
+        FontTransform::None | FontTransform::Rotate180 => (width, height),
+    };
+    let dx = match anchor.h_pos {
+        HPos::Left => w / 2.0,
+        HPos::Center => 0.0,
+        HPos::Right => -w / 2.0,
+    };
+    let dy = match anchor.v_pos {
+        VPos::Top => h / 2.0,
+        VPos::Center => 0.0,
+        VPos::Bottom => -h / 2.0,
+    };
+    (pos.0 + dx, pos.1 + dy)
+}
+
+impl<'a> OffscreenCanvasBackend<'a> {
+    /// Set how the anchor of rotated texts is interpreted
+    pub fn set_text_rotation(&mut self, rotation: TextRotation) {
+        self.text_rotation = rotation;
+    }
+
+    pub fn text_rotation(&self) -> TextRotation {
+        self.text_rotation
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_anchor_after_rotation() {
+        let anchor = Pos::new(HPos::Right, VPos::Center);
+        assert_eq!(
+            anchored_center((100.0, 50.0), (40.0, 10.0), anchor, FontTransform::None),
+            (80.0, 50.0)
+        );
+        // Rotated a quarter turn the text is 10 pixels wide on the screen
+        assert_eq!(
+            anchored_center((100.0, 50.0), (40.0, 10.0), anchor, FontTransform::Rotate90),
+            (95.0, 50.0)
+        );
+        assert_eq!(
+            rotation_radians(FontTransform::Rotate180),
+            std::f64::consts::PI
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use js_sys::Reflect;
+use wasm_bindgen::JsCast;
+use web_sys::Performance;
+
+use crate::warn::warn_once;
+
+/// Current time in milliseconds, from `performance.now()` of the current global scope
+///  - Fall back to `Date.now()` when the performance API is not available, with a warning
+pub(crate) fn now_ms() -> f64 {
+    Reflect::get(&js_sys::global(), &"performance".into())
+        .ok()
+        .and_then(|p| p.dyn_into::<Performance>().ok())
+        .map(|p| p.now())
+        .unwrap_or_else(|| {
+            warn_once("performance.now() is not available, frame times use Date.now()");
+            js_sys::Date::now()
+        })
+}
+
+/// Where frame timings, render loops and animations get the current time from
+///  - Replace the default `PerformanceClock` with a `MockClock` in tests, or with a closure to
+///    follow an external clock
+pub trait TimeSource {
+    /// Current time in milliseconds
+    fn now_ms(&self) -> f64;
+}
+
+impl<F: Fn() -> f64> TimeSource for F {
+    fn now_ms(&self) -> f64 {
+        self()
+    }
+}
+
+/// The time of `performance.now()`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PerformanceClock;
+
+impl TimeSource for PerformanceClock {
+    fn now_ms(&self) -> f64 {
+        now_ms()
+    }
+}
+
+/// A clock which only moves when told to
+///  - Clones share the same time
+#[derive(Clone, Debug, Default)]
+pub struct MockClock(Rc<Cell<f64>>);
+
+impl MockClock {
+    pub fn new(now_ms: f64) -> Self {
+        Self(Rc::new(Cell::new(now_ms)))
+    }
+
+    pub fn set(&self, now_ms: f64) {
+        self.0.set(now_ms);
+    }
+
+    pub fn advance(&self, dt_ms: f64) {
+        self.0.set(self.0.get() + dt_ms);
+    }
+}
+
+impl TimeSource for MockClock {
+    fn now_ms(&self) -> f64 {
+        self.0.get()
+    }
+}
+
+/// A time source shared by the backend, the render loops and the queues
+pub type SharedTimeSource = Rc<dyn TimeSource>;
+
+pub(crate) fn default_time_source() -> SharedTimeSource {
+    Rc::new(PerformanceClock)
+}
@@ -0,0 +1,193 @@
+use crate::command::CommandColor;
+use crate::geometry::Rect;
+use crate::render_loop::{FrameTime, LoopControl};
+use crate::viewport::Viewport;
+
+/// How the progress of a transition is distributed over its duration
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    /// Cubic, starting slowly
+    EaseIn,
+    /// Cubic, ending slowly
+    EaseOut,
+    /// Cubic, starting and ending slowly
+    EaseInOut,
+}
+
+impl Default for Easing {
+    fn default() -> Self {
+        Easing::EaseInOut
+    }
+}
+
+impl Easing {
+    /// Eased progress for a linear progress `t` in 0..=1
+    pub fn apply(&self, t: f64) -> f64 {
+        let t = t.max(0.0).min(1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+/// Values which can be interpolated
+pub trait Lerp {
+    /// The value at `t` between `self` (0) and `other` (1)
+    fn lerp(&self, other: &Self, t: f64) -> Self;
+}
+
+impl Lerp for f64 {
+    fn lerp(&self, other: &Self, t: f64) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Lerp for (f64, f64) {
+    fn lerp(&self, other: &Self, t: f64) -> Self {
+        (self.0.lerp(&other.0, t), self.1.lerp(&other.1, t))
+    }
+}
+
+impl Lerp for Rect {
+    fn lerp(&self, other: &Self, t: f64) -> Self {
+        Rect {
+            left: self.left.lerp(&other.left, t),
+            top: self.top.lerp(&other.top, t),
+            right: self.right.lerp(&other.right, t),
+            bottom: self.bottom.lerp(&other.bottom, t),
+        }
+    }
+}
+
+impl Lerp for Viewport {
+    fn lerp(&self, other: &Self, t: f64) -> Self {
+        Viewport {
+            x_range: self.x_range.lerp(&other.x_range, t),
+            y_range: self.y_range.lerp(&other.y_range, t),
+            area: self.area.lerp(&other.area, t),
+        }
+    }
+}
+
+impl Lerp for CommandColor {
+    fn lerp(&self, other: &Self, t: f64) -> Self {
+        let channel = |a: u8, b: u8| f64::from(a).lerp(&f64::from(b), t).round() as u8;
+        CommandColor {
+            rgb: (
+                channel(self.rgb.0, other.rgb.0),
+                channel(self.rgb.1, other.rgb.1),
+                channel(self.rgb.2, other.rgb.2),
+            ),
+            alpha: self.alpha.lerp(&other.alpha, t),
+        }
+    }
+}
+
+/// A transition between two values, driven by the frame times of a render loop
+///  - e.g. an animated zoom is a `Tween<Viewport>`, a series fade-in is a `Tween<f64>` on
+///    its alpha
+#[derive(Clone, Debug)]
+pub struct Tween<T> {
+    from: T,
+    to: T,
+    duration_ms: f64,
+    easing: Easing,
+    started_at: Option<f64>,
+}
+
+impl<T: Lerp + Clone> Tween<T> {
+    /// The transition starts at the first frame it is sampled in
+    pub fn new(from: T, to: T, duration_ms: f64, easing: Easing) -> Self {
+        Self {
+            from,
+            to,
+            duration_ms,
+            easing,
+            started_at: None,
+        }
+    }
+
+    /// Eased progress at `now_ms`, in 0..=1
+    pub fn progress(&self, now_ms: f64) -> f64 {
+        let started_at = match self.started_at {
+            Some(started_at) => started_at,
+            None => return 0.0,
+        };
+        if self.duration_ms <= 0.0 {
+            return 1.0;
+        }
+        self.easing.apply((now_ms - started_at) / self.duration_ms)
+    }
+
+    /// The value at `now_ms`
+    pub fn value_at(&self, now_ms: f64) -> T {
+        self.from.lerp(&self.to, self.progress(now_ms))
+    }
+
+    /// The value for a frame of a render loop, the first call starts the transition
+    pub fn sample(&mut self, time: &FrameTime) -> T {
+        if self.started_at.is_none() {
+            self.started_at = Some(time.now);
+        }
+        self.value_at(time.now)
+    }
+
+    pub fn is_finished(&self, now_ms: f64) -> bool {
+        self.started_at
+            .map_or(false, |s| now_ms - s >= self.duration_ms)
+    }
+
+    /// Keep the loop running until the transition is over
+    pub fn control(&self, time: &FrameTime) -> LoopControl {
+        if self.is_finished(time.now) {
+            LoopControl::Stop
+        } else {
+            LoopControl::Continue
+        }
+    }
+
+    /// Head to a new target from the current value, e.g. when the user zooms again mid-way
+    pub fn retarget(&mut self, to: T, now_ms: f64) {
+        self.from = self.value_at(now_ms);
+        self.to = to;
+        self.started_at = Some(now_ms);
+    }
+
+    pub fn target(&self) -> &T {
+        &self.to
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_tween() {
+        let mut tween = Tween::new(0.0, 10.0, 100.0, Easing::Linear);
+        let frame = |now| FrameTime {
+            now,
+            dt: 0.0,
+            frame: 0,
+        };
+        assert_eq!(tween.sample(&frame(1000.0)), 0.0);
+        assert_eq!(tween.sample(&frame(1050.0)), 5.0);
+        assert_eq!(tween.control(&frame(1050.0)), LoopControl::Continue);
+        assert_eq!(tween.sample(&frame(1200.0)), 10.0);
+        assert_eq!(tween.control(&frame(1200.0)), LoopControl::Stop);
+
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+        assert_eq!(Easing::EaseOut.apply(1.0), 1.0);
+    }
+}
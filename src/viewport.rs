@@ -0,0 +1,72 @@
+use plotters_backend::BackendCoord;
+
+use crate::coord::pixel_to_coord;
+use crate::geometry::Rect;
+
+/// An axis of the plot
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+}
+
+/// Maps data coordinates to the pixels of the plot area, with the y axis pointing up
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    /// Data range shown on the x axis, left to right
+    pub x_range: (f64, f64),
+    /// Data range shown on the y axis, bottom to top
+    pub y_range: (f64, f64),
+    /// The plot area on the canvas
+    pub area: Rect,
+}
+
+impl Viewport {
+    pub fn new(x_range: (f64, f64), y_range: (f64, f64), area: Rect) -> Self {
+        Self {
+            x_range,
+            y_range,
+            area,
+        }
+    }
+
+    pub fn x_to_pixel(&self, x: f64) -> f64 {
+        let (x0, x1) = self.x_range;
+        if x1 == x0 {
+            return self.area.left;
+        }
+        self.area.left + (x - x0) / (x1 - x0) * self.area.width()
+    }
+
+    pub fn y_to_pixel(&self, y: f64) -> f64 {
+        let (y0, y1) = self.y_range;
+        if y1 == y0 {
+            return self.area.bottom;
+        }
+        self.area.bottom - (y - y0) / (y1 - y0) * self.area.height()
+    }
+
+    pub fn to_pixel(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        (self.x_to_pixel(x), self.y_to_pixel(y))
+    }
+
+    /// The pixel of a data point, rounded to backend coordinates
+    pub fn to_coord(&self, p: (f64, f64)) -> BackendCoord {
+        let (x, y) = self.to_pixel(p);
+        pixel_to_coord(x, y)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_viewport_mapping() {
+        let viewport = Viewport::new((0.0, 10.0), (0.0, 1.0), Rect::new(10.0, 10.0, 110.0, 60.0));
+        assert_eq!(viewport.to_pixel((0.0, 0.0)), (10.0, 60.0));
+        assert_eq!(viewport.to_pixel((10.0, 1.0)), (110.0, 10.0));
+        assert_eq!(viewport.to_coord((5.0, 0.5)), (60, 35));
+    }
+}
@@ -0,0 +1,43 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+use web_sys::console;
+
+thread_local! {
+    static WARNED: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+}
+
+/// Log a warning on the console, once per message and thread
+///  - Used when the backend silently degrades (a missing browser API, a clamped coordinate,
+///    ...), so that it shows up once instead of on every frame. Keep the message free of
+///    per-call values, it is the key
+///  - Return true if the warning was logged
+pub fn warn_once(message: &str) -> bool {
+    let first = WARNED.with(|warned| warned.borrow_mut().insert(message.to_string()));
+    if first {
+        console::warn_1(&message.into());
+    }
+    first
+}
+
+/// Forget the warnings already logged, so that they are logged again
+pub fn reset_warnings() {
+    WARNED.with(|warned| warned.borrow_mut().clear());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_warn_once() {
+        let message = "test_warn_once: degraded";
+        assert!(warn_once(message));
+        assert!(!warn_once(message));
+        assert!(warn_once("test_warn_once: another degradation"));
+
+        reset_warnings();
+        assert!(warn_once(message));
+    }
+}
@@ -0,0 +1,171 @@
+use js_sys::{Object, Reflect};
+use wasm_bindgen::JsValue;
+
+use crate::canvas::OffscreenCanvasBackend;
+use crate::chunked::CancellationToken;
+use crate::stats::FrameStats;
+use crate::time::{default_time_source, SharedTimeSource};
+
+/// Diagnostic of a frame the watchdog aborted
+#[derive(Clone, Debug)]
+pub struct WatchdogReport {
+    /// Time the frame had been rendering for when it was aborted, in milliseconds
+    pub elapsed_ms: f64,
+    pub limit_ms: f64,
+    /// Name of the series being drawn
+    pub series: Option<String>,
+    /// The draw calls of the aborted frame, if any primitive was drawn
+    pub stats: Option<FrameStats>,
+}
+
+impl WatchdogReport {
+    /// The report as a plain object, to post it to the main thread
+    pub fn to_js(&self) -> JsValue {
+        let report = Object::new();
+        let _ = Reflect::set(&report, &"elapsedMs".into(), &self.elapsed_ms.into());
+        let _ = Reflect::set(&report, &"limitMs".into(), &self.limit_ms.into());
+        if let Some(series) = &self.series {
+            let _ = Reflect::set(&report, &"series".into(), &series.into());
+        }
+        if let Some(stats) = &self.stats {
+            let _ = Reflect::set(&report, &"drawCalls".into(), &(stats.total() as f64).into());
+        }
+        report.into()
+    }
+}
+
+/// Hard limit on the duration of a frame
+///
+/// The watchdog is armed when a frame starts and checked at the yield points of the render (the
+/// batch boundaries of a `ChunkedRender`, with `check` as the `keep_going` condition). Once the
+/// limit is exceeded the token is cancelled, so the chunked work stops, and `recover` reports
+/// the stuck frame and brings the backend back to a drawable state.
+pub struct Watchdog {
+    limit_ms: f64,
+    time_source: SharedTimeSource,
+    started: Option<f64>,
+    token: CancellationToken,
+    tripped: Option<f64>,
+    on_trip: Option<Box<dyn FnMut(&WatchdogReport)>>,
+}
+
+impl Watchdog {
+    pub fn new(limit_ms: f64) -> Self {
+        Self::with_time_source(limit_ms, default_time_source())
+    }
+
+    pub fn with_time_source(limit_ms: f64, time_source: SharedTimeSource) -> Self {
+        Self {
+            limit_ms,
+            time_source,
+            started: None,
+            token: CancellationToken::new(),
+            tripped: None,
+            on_trip: None,
+        }
+    }
+
+    /// Set the callback the report of an aborted frame is posted with
+    pub fn on_trip<F: FnMut(&WatchdogReport) + 'static>(&mut self, callback: F) {
+        self.on_trip = Some(Box::new(callback));
+    }
+
+    /// Start timing a frame, `token` is cancelled if the frame goes over the limit
+    pub fn arm(&mut self, token: CancellationToken) {
+        self.started = Some(self.time_source.now_ms());
+        self.token = token;
+        self.tripped = None;
+    }
+
+    /// Stop timing the frame, which completed in time
+    pub fn disarm(&mut self) {
+        self.started = None;
+    }
+
+    pub fn is_armed(&self) -> bool {
+        self.started.is_some()
+    }
+
+    pub fn is_tripped(&self) -> bool {
+        self.tripped.is_some()
+    }
+
+    /// Return false once the frame went over the limit, the token is then cancelled
+    pub fn check(&mut self) -> bool {
+        let started = match self.started {
+            Some(started) => started,
+            None => return true,
+        };
+        let elapsed = self.time_source.now_ms() - started;
+        if elapsed > self.limit_ms && self.tripped.is_none() {
+            self.tripped = Some(elapsed);
+            self.token.cancel();
+        }
+        self.tripped.is_none()
+    }
+
+    /// Recover the backend if the watchdog tripped, and post the report of the aborted frame
+    pub fn recover(&mut self, backend: &mut OffscreenCanvasBackend) -> Option<WatchdogReport> {
+        let elapsed_ms = self.tripped.take()?;
+        self.started = None;
+        let series = backend.current_series().map(|s| s.name.clone());
+        let report = WatchdogReport {
+            elapsed_ms,
+            limit_ms: self.limit_ms,
+            series,
+            stats: backend.recover(),
+        };
+        if let Some(on_trip) = &mut self.on_trip {
+            on_trip(&report);
+        }
+        Some(report)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::chunked::{ChunkStatus, ChunkedRender};
+    use crate::time::MockClock;
+    use plotters_backend::{BackendColor, DrawingBackend};
+    use std::rc::Rc;
+    use wasm_bindgen_test::*;
+    use web_sys::OffscreenCanvas;
+
+    #[wasm_bindgen_test]
+    fn test_watchdog_aborts_chunked_render() {
+        let canvas = OffscreenCanvas::new(100, 100).unwrap();
+        let mut backend = OffscreenCanvasBackend::new(&canvas).expect("cannot find canvas");
+        let clock = MockClock::new(0.0);
+        let mut watchdog = Watchdog::with_time_source(2000.0, Rc::new(clock.clone()));
+
+        let token = CancellationToken::new();
+        let mut render = ChunkedRender::new(0..100, 10, token.clone());
+        watchdog.arm(token);
+        let color = BackendColor {
+            alpha: 1.0,
+            rgb: (0, 0, 0),
+        };
+        let status = render
+            .run_while(
+                |_| watchdog.check(),
+                |batch| {
+                    clock.advance(500.0);
+                    for &i in batch {
+                        backend.draw_pixel((i, i), color)?;
+                    }
+                    Ok::<_, plotters_backend::DrawingErrorKind<_>>(())
+                },
+            )
+            .unwrap();
+        // The render stops at the yield point where the limit was noticed
+        assert_eq!(status, ChunkStatus::Pending);
+        assert!(render.token().is_cancelled());
+
+        let report = watchdog.recover(&mut backend).unwrap();
+        assert!(report.elapsed_ms > 2000.0);
+        assert_eq!(report.stats.unwrap().total(), 50);
+        assert!(backend.frame_stats().is_none());
+        assert!(watchdog.recover(&mut backend).is_none());
+    }
+}
@@ -0,0 +1,292 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{DedicatedWorkerGlobalScope, MessageEvent, Worker, WorkerOptions};
+
+use crate::canvas::{js_error, js_error_in, CanvasError};
+
+/// Called once with the result the worker posted back for a job
+pub type JobCallback = Box<dyn FnOnce(Result<JsValue, JsValue>)>;
+
+/// Called when a chart fails, with its key and the error posted back by the worker
+pub type ChartErrorCallback = Box<dyn FnMut(&str, &JsValue)>;
+
+struct PoolWorker {
+    worker: Worker,
+    in_flight: usize,
+    _onmessage: Closure<dyn FnMut(MessageEvent)>,
+}
+
+#[derive(Default)]
+struct PoolState {
+    workers: Vec<PoolWorker>,
+    pending: HashMap<u32, JobCallback>,
+    affinity: HashMap<String, usize>,
+    next_id: u32,
+    errored: HashMap<String, JsValue>,
+    on_chart_error: Option<ChartErrorCallback>,
+}
+
+/// Distribute chart render jobs across a pool of workers
+///
+/// Every job is posted as `{ id, key, payload }` to one of the workers, which replies with
+/// `{ id, key, ok, result }` (see [serve_pool_jobs] for the worker side). Jobs submitted with the same
+/// key always go to the same worker, so a canvas transferred to a worker once keeps being
+/// rendered by the worker owning it.
+///
+/// The key also delimits the failures: a job failing marks only its chart errored, further jobs
+/// for that chart are refused until [WorkerPool::clear_chart_error], while the other charts keep
+/// rendering.
+pub struct WorkerPool {
+    state: Rc<RefCell<PoolState>>,
+}
+
+impl WorkerPool {
+    /// Spawn `size` workers running the given script
+    pub fn new(script_url: &str, size: usize) -> Result<Self, CanvasError> {
+        Self::create(size, || Worker::new(script_url))
+    }
+
+    /// Spawn `size` workers with the given options (e.g. module workers)
+    pub fn new_with_options(
+        script_url: &str,
+        size: usize,
+        options: &WorkerOptions,
+    ) -> Result<Self, CanvasError> {
+        Self::create(size, || Worker::new_with_options(script_url, options))
+    }
+
+    fn create(
+        size: usize,
+        spawn: impl Fn() -> Result<Worker, JsValue>,
+    ) -> Result<Self, CanvasError> {
+        if size == 0 {
+            return Err(CanvasError::InvalidInput(
+                "worker pool needs at least one worker".to_string(),
+            ));
+        }
+
+        let state = Rc::new(RefCell::new(PoolState::default()));
+        for index in 0..size {
+            let worker = spawn().map_err(js_error_in("new Worker"))?;
+            let weak = Rc::downgrade(&state);
+            let onmessage = Closure::wrap(Box::new(move |event: MessageEvent| {
+                on_worker_message(&weak, index, event.data());
+            }) as Box<dyn FnMut(MessageEvent)>);
+            worker.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+
+            state.borrow_mut().workers.push(PoolWorker {
+                worker,
+                in_flight: 0,
+                _onmessage: onmessage,
+            });
+        }
+
+        Ok(Self { state })
+    }
+
+    /// Submit a job to the pool
+    ///  - `key` pins the job to the worker which received the first job with the same key
+    ///  - `transfer` lists the transferable objects (e.g. an OffscreenCanvas) moved with the payload
+    ///  - Return the id of the job
+    pub fn submit<F: FnOnce(Result<JsValue, JsValue>) + 'static>(
+        &self,
+        key: Option<&str>,
+        payload: &JsValue,
+        transfer: Option<&Array>,
+        on_complete: F,
+    ) -> Result<u32, CanvasError> {
+        let mut state = self.state.borrow_mut();
+
+        if let Some(k) = key.filter(|k| state.errored.contains_key(*k)) {
+            return Err(CanvasError::Other(format!(
+                "chart {} is in an errored state",
+                k
+            )));
+        }
+        let pinned = key.and_then(|k| state.affinity.get(k).copied());
+        let index = match pinned {
+            Some(index) => index,
+            None => {
+                let index = least_loaded(&state.workers);
+                if let Some(k) = key {
+                    state.affinity.insert(k.to_string(), index);
+                }
+                index
+            }
+        };
+
+        let id = state.next_id;
+        state.next_id = state.next_id.wrapping_add(1);
+
+        let message = Object::new();
+        Reflect::set(&message, &"id".into(), &id.into()).map_err(js_error)?;
+        Reflect::set(&message, &"payload".into(), payload).map_err(js_error)?;
+        if let Some(k) = key {
+            Reflect::set(&message, &"key".into(), &k.into()).map_err(js_error)?;
+        }
+
+        let worker = &state.workers[index].worker;
+        match transfer {
+            Some(transfer) => worker.post_message_with_transfer(&message, transfer),
+            None => worker.post_message(&message),
+        }
+        .map_err(js_error_in("postMessage"))?;
+
+        state.workers[index].in_flight += 1;
+        state.pending.insert(id, Box::new(on_complete));
+
+        Ok(id)
+    }
+
+    /// Set the callback told about the charts failing
+    pub fn on_chart_error<F: FnMut(&str, &JsValue) + 'static>(&self, callback: F) {
+        self.state.borrow_mut().on_chart_error = Some(Box::new(callback));
+    }
+
+    /// The error which stopped the chart, as posted back by the worker
+    ///  - `{ key, name, message }`, see [serve_pool_jobs]
+    pub fn chart_error(&self, key: &str) -> Option<JsValue> {
+        self.state.borrow().errored.get(key).cloned()
+    }
+
+    /// The keys of the charts in an errored state
+    pub fn errored_charts(&self) -> Vec<String> {
+        self.state.borrow().errored.keys().cloned().collect()
+    }
+
+    /// Accept jobs for the chart again
+    pub fn clear_chart_error(&self, key: &str) {
+        self.state.borrow_mut().errored.remove(key);
+    }
+
+    /// Number of workers in the pool
+    pub fn size(&self) -> usize {
+        self.state.borrow().workers.len()
+    }
+
+    /// Number of jobs which haven't completed yet
+    pub fn in_flight(&self) -> usize {
+        self.state.borrow().pending.len()
+    }
+
+    /// Stop all the workers, pending jobs never complete
+    pub fn terminate(self) {
+        let mut state = self.state.borrow_mut();
+        for w in state.workers.iter() {
+            w.worker.terminate();
+        }
+        state.pending.clear();
+    }
+}
+
+fn least_loaded(workers: &[PoolWorker]) -> usize {
+    workers
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, w)| w.in_flight)
+        .map(|(i, _)| i)
+        .unwrap_or(0)
+}
+
+fn on_worker_message(state: &Weak<RefCell<PoolState>>, index: usize, data: JsValue) {
+    let state = match state.upgrade() {
+        Some(state) => state,
+        None => return,
+    };
+
+    let id = match Reflect::get(&data, &"id".into()).ok().and_then(|v| v.as_f64()) {
+        Some(id) => id as u32,
+        None => return,
+    };
+    let ok = Reflect::get(&data, &"ok".into())
+        .ok()
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let result = Reflect::get(&data, &"result".into()).unwrap_or(JsValue::UNDEFINED);
+    let key = Reflect::get(&data, &"key".into())
+        .ok()
+        .and_then(|v| v.as_string());
+
+    let (callback, on_chart_error) = {
+        let mut state = state.borrow_mut();
+        if let Some(w) = state.workers.get_mut(index) {
+            w.in_flight = w.in_flight.saturating_sub(1);
+        }
+        let on_chart_error = match &key {
+            Some(key) if !ok => {
+                state.errored.insert(key.clone(), result.clone());
+                state.on_chart_error.take()
+            }
+            _ => None,
+        };
+        (state.pending.remove(&id), on_chart_error)
+    };
+
+    // The state isn't borrowed while the callbacks run, so that they can submit jobs
+    if let (Some(mut on_chart_error), Some(key)) = (on_chart_error, &key) {
+        on_chart_error(key, &result);
+        let mut state = state.borrow_mut();
+        if state.on_chart_error.is_none() {
+            state.on_chart_error = Some(on_chart_error);
+        }
+    }
+    if let Some(callback) = callback {
+        callback(if ok { Ok(result) } else { Err(result) });
+    }
+}
+
+/// The structured error posted back for a failed job, `{ key, name, message }`
+fn chart_error(key: &JsValue, error: &JsValue) -> JsValue {
+    let (name, message) = match error.dyn_ref::<js_sys::Error>() {
+        Some(e) => (String::from(e.name()), String::from(e.message())),
+        None => (
+            "Error".to_string(),
+            error.as_string().unwrap_or_else(|| format!("{:?}", error)),
+        ),
+    };
+    let report = Object::new();
+    let _ = Reflect::set(&report, &"key".into(), key);
+    let _ = Reflect::set(&report, &"name".into(), &name.into());
+    let _ = Reflect::set(&report, &"message".into(), &message.into());
+    report.into()
+}
+
+/// Worker side of the pool: run `handler` for each job payload and post the result back
+///  - A failure is posted back as a structured error, the worker keeps serving the other jobs
+pub fn serve_pool_jobs<F: FnMut(JsValue) -> Result<JsValue, JsValue> + 'static>(
+    mut handler: F,
+) -> Result<(), CanvasError> {
+    let scope: DedicatedWorkerGlobalScope = js_sys::global().dyn_into().map_err(|_| {
+        CanvasError::Unsupported("not running inside a dedicated worker".to_string())
+    })?;
+
+    let reply_scope = scope.clone();
+    let onmessage = Closure::wrap(Box::new(move |event: MessageEvent| {
+        let data = event.data();
+        let id = Reflect::get(&data, &"id".into()).unwrap_or(JsValue::UNDEFINED);
+        let payload = Reflect::get(&data, &"payload".into()).unwrap_or(JsValue::UNDEFINED);
+        let key = Reflect::get(&data, &"key".into()).unwrap_or(JsValue::UNDEFINED);
+
+        let (ok, result) = match handler(payload) {
+            Ok(v) => (true, v),
+            Err(e) => (false, chart_error(&key, &e)),
+        };
+
+        let reply = Object::new();
+        let _ = Reflect::set(&reply, &"id".into(), &id);
+        let _ = Reflect::set(&reply, &"key".into(), &key);
+        let _ = Reflect::set(&reply, &"ok".into(), &ok.into());
+        let _ = Reflect::set(&reply, &"result".into(), &result);
+        let _ = reply_scope.post_message(&reply);
+    }) as Box<dyn FnMut(MessageEvent)>);
+
+    scope.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+    onmessage.forget();
+
+    Ok(())
+}